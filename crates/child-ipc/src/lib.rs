@@ -152,4 +152,7 @@ pub struct RunCapture {
     pub device: Device,
     pub connection_timeout: Duration,
     pub report_frequency: Duration,
+    /// Keep traffic between private addresses, e.g. to internal offices, instead of dropping it.
+    #[serde(default)]
+    pub keep_private: bool,
 }
@@ -46,7 +46,7 @@ impl IpcService for Service {
             }
         };
 
-        let cap = match api.open_capture(params.device) {
+        let cap = match api.open_capture(params.device, params.keep_private) {
             Ok(capture) => capture,
             Err(e) => ipc::exit_with_error(parent, Error::runtime(e.to_string())),
         };
@@ -0,0 +1,191 @@
+//! Autonomous system (ASN) databases, answering *who* owns an address rather than where it is.
+
+use std::{
+    io::Read,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use indexmap::IndexSet;
+use rustc_hash::FxBuildHasher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use treebitmap::IpLookupTable;
+
+use crate::{
+    AsnLookup, Error,
+    locations::{StringDict, StringDictKey},
+    reader,
+};
+
+/// Index of an autonomous system in an [`AsnStore`], as stored in the lookup tables.
+#[doc(hidden)]
+pub type AsnKey = u32;
+
+/// The autonomous system that owns a network.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AsnInfo {
+    /// Autonomous system number, e.g. `13335`
+    pub number: u32,
+    /// Name of the organization operating the system, e.g. `Cloudflare, Inc.`
+    pub organization: Option<String>,
+}
+
+/// A database of the autonomous systems that own IPv4 and IPv6 networks.
+///
+/// Built from the sapics `asn-ipv[4/6][-num].csv` or `asn-ipv[4/6].mmdb` files,
+/// a database read from a single file will only have one address type populated.
+#[derive(PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct AsnDatabase {
+    pub(crate) ipv4: IpLookupTable<Ipv4Addr, AsnKey>,
+    pub(crate) ipv6: IpLookupTable<Ipv6Addr, AsnKey>,
+    pub(crate) systems: AsnStore,
+}
+
+impl Default for AsnDatabase {
+    fn default() -> Self {
+        Self {
+            ipv4: IpLookupTable::new(),
+            ipv6: IpLookupTable::new(),
+            systems: AsnStore::default(),
+        }
+    }
+}
+
+impl AsnDatabase {
+    pub fn from_csv(ipv4_csv: impl Read, ipv6_csv: impl Read, is_num: bool) -> Result<Self, Error> {
+        let mut db = Self::default();
+
+        reader::csv::read_asn(ipv4_csv, is_num, &mut db.ipv4, &mut db.systems)?;
+        reader::csv::read_asn(ipv6_csv, is_num, &mut db.ipv6, &mut db.systems)?;
+
+        Ok(db)
+    }
+
+    pub fn from_mmdb<S: AsRef<[u8]>>(reader: maxminddb::Reader<S>) -> Result<Self, Error> {
+        let mut db = Self::default();
+
+        match reader.metadata.ip_version {
+            4 => reader::mmdb::read_asn(&reader, &mut db.ipv4, &mut db.systems)?,
+            6 => {
+                // IPv6 databases usually hold IPv4 too, under `::/96`
+                reader::mmdb::read_asn(&reader, &mut db.ipv4, &mut db.systems)?;
+                reader::mmdb::read_asn(&reader, &mut db.ipv6, &mut db.systems)?;
+            }
+            _ => return Err(Error::MalformedMaxMindDb),
+        }
+
+        Ok(db)
+    }
+}
+
+impl AsnLookup for AsnDatabase {
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo> {
+        let key = match ip {
+            IpAddr::V4(ip) => self.ipv4.longest_match(ip).map(|(_, _, k)| *k),
+            IpAddr::V6(ip) => self.ipv6.longest_match(ip).map(|(_, _, k)| *k),
+        }?;
+
+        self.systems.get(key)
+    }
+}
+
+/// A deduplicated store of autonomous systems and their organization names.
+#[derive(Default, PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub(crate) struct AsnStore {
+    pub(crate) systems: IndexSet<AsnIndices, FxBuildHasher>,
+    pub(crate) strings: StringDict,
+}
+
+impl AsnStore {
+    /// Insert an autonomous system, returning the key to store in a lookup table.
+    pub(crate) fn insert(&mut self, number: u32, organization: &[u8]) -> AsnKey {
+        let organization = self.strings.insert_bytes(organization);
+        self.systems
+            .insert_full(AsnIndices {
+                number,
+                organization,
+            })
+            .0 as AsnKey
+    }
+
+    pub(crate) fn get(&self, key: AsnKey) -> Option<AsnInfo> {
+        self.systems.get_index(key as usize).map(|sys| AsnInfo {
+            number: sys.number,
+            organization: sys.organization.and_then(|i| self.strings.get(i)),
+        })
+    }
+}
+
+/// The autonomous system number and its organization as an index into a `StringDict` database.
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Hash, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+pub(crate) struct AsnIndices {
+    pub(crate) number: u32,
+    pub(crate) organization: Option<StringDictKey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    };
+
+    use crate::{AsnDatabase, AsnInfo, AsnLookup, testing::archived};
+
+    const IPV4_CSV: &str = "1.0.0.0,1.0.0.255,13335,\"Cloudflare, Inc.\"
+1.0.4.0,1.0.7.255,38803,Gtelecom Pty Ltd
+1.0.16.0,1.0.16.255,2519,
+";
+
+    const IPV6_CSV: &str = "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,2500,WIDE Project
+";
+
+    #[test]
+    fn asn_lookup() -> Result<(), Box<dyn error::Error>> {
+        let db = AsnDatabase::from_csv(IPV4_CSV.as_bytes(), IPV6_CSV.as_bytes(), false)?;
+
+        let cases: [(IpAddr, Option<AsnInfo>); 5] = [
+            (
+                Ipv4Addr::new(1, 0, 0, 1).into(),
+                Some(AsnInfo {
+                    number: 13335,
+                    organization: Some("Cloudflare, Inc.".into()),
+                }),
+            ),
+            (
+                Ipv4Addr::new(1, 0, 5, 9).into(),
+                Some(AsnInfo {
+                    number: 38803,
+                    organization: Some("Gtelecom Pty Ltd".into()),
+                }),
+            ),
+            (
+                Ipv4Addr::new(1, 0, 16, 1).into(),
+                Some(AsnInfo {
+                    number: 2519,
+                    organization: None,
+                }),
+            ),
+            (
+                "2001:200::1".parse::<Ipv6Addr>()?.into(),
+                Some(AsnInfo {
+                    number: 2500,
+                    organization: Some("WIDE Project".into()),
+                }),
+            ),
+            (Ipv4Addr::new(1, 0, 2, 1).into(), None),
+        ];
+
+        archived(&db, |archived| {
+            for (ip, expected) in cases {
+                assert_eq!(db.get_asn(ip), expected, "{ip}");
+                assert_eq!(archived.get_asn(ip), expected, "{ip}");
+            }
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+//! Data-quality checks of the locations in a database, to compare how far each vendor can be trusted.
+
+use std::collections::{HashMap, HashSet};
+
+use rustc_hash::FxBuildHasher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    Audit, CombinedDatabase, Coordinate, GenericIp, Location, SingleDatabase,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationIndices, LocationKey, LocationStore, StringDictKey},
+};
+
+/// Coordinates this close to 0,0 are at null island, the usual placeholder for unknown addresses.
+const NULL_ISLAND_KM: f32 = 10.0;
+/// Cities this close to their country's centroid were likely placed there for lack of a coordinate.
+const CENTROID_KM: f32 = 5.0;
+/// Country-level locations further than this from their country's centroid look more precise than they are.
+///
+/// Vendors use their own centroids, which can be a couple hundred kilometres from ours in large countries.
+const SPECIFIC_KM: f32 = 500.0;
+
+/// A problem found by an [`Audit`].
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Type,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditCheck {
+    /// At or near 0,0, where addresses without a known location often end up.
+    NullIsland,
+    /// A city at its country's centroid, where the city is likely a guess.
+    CountryCentroid,
+    /// Outside the bounding box of the location's country.
+    OutsideCountry,
+    /// Another city at the same coordinate, within the precision coordinates are stored with.
+    ///
+    /// Imports keep the first location at a coordinate, and set the others aside for this check.
+    /// Names that only differ in case or accents aren't a conflict.
+    ConflictingNames,
+    /// Only the country is known, but the coordinate is far from the country's centroid,
+    /// so it looks like a specific place.
+    SpecificWithoutCity,
+}
+
+/// A location that failed an [`AuditCheck`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    pub check: AuditCheck,
+    pub crd: Coordinate,
+    pub loc: Location,
+    /// Network prefixes placed at the coordinate.
+    pub prefixes: u64,
+    /// The other location, for [`AuditCheck::ConflictingNames`].
+    pub conflict: Option<Location>,
+}
+
+/// Audited locations and prefixes in a single country.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryAudit {
+    pub country_code: String,
+    /// Distinct coordinates with a location in the country.
+    pub locations: u64,
+    pub prefixes: u64,
+    /// Locations with at least one finding.
+    pub flagged_locations: u64,
+    /// Prefixes placed at a flagged location.
+    pub flagged_prefixes: u64,
+}
+
+/// The findings of an [`Audit`] of a database.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    /// Distinct coordinates with a location.
+    pub locations: u64,
+    pub prefixes: u64,
+    /// Every finding, sorted by country code, check, and coordinate.
+    pub findings: Vec<AuditFinding>,
+    /// Totals per country, sorted by country code, to compare databases region by region.
+    pub countries: Vec<CountryAudit>,
+}
+
+impl AuditReport {
+    /// Audit the parts of a [`LocationStore`] with the coordinate of every network prefix.
+    ///
+    /// Takes iterators rather than a [`LocationStore`] so it can be shared with the archived store.
+    pub(crate) fn new<'a>(
+        strings: impl Iterator<Item = &'a str>,
+        locations: impl Iterator<Item = LocationIndices>,
+        coordinates: impl Iterator<Item = (PackedCoordinate, LocationKey)>,
+        conflicts: impl Iterator<Item = (PackedCoordinate, LocationIndices)>,
+        prefixes: impl Iterator<Item = PackedCoordinate>,
+    ) -> Self {
+        let mut counts = HashMap::<PackedCoordinate, u64, FxBuildHasher>::default();
+        for crd in prefixes {
+            *counts.entry(crd).or_default() += 1;
+        }
+
+        let strings = strings.collect::<Vec<_>>();
+        let locations = locations.collect::<Vec<_>>();
+        let coordinates = coordinates
+            .filter(|(_, key)| *key < locations.len())
+            .collect::<HashMap<_, _, FxBuildHasher>>();
+
+        let string =
+            |key: StringDictKey| strings.get(key.get() as usize - 1).map(|s| s.to_string());
+        let count = |crd: &PackedCoordinate| counts.get(crd).copied().unwrap_or_default();
+
+        // conflicts are referred to by their index
+        let conflicts = conflicts
+            .filter(|(crd, _)| coordinates.contains_key(crd))
+            .collect::<Vec<_>>();
+
+        let mut found = Vec::<(String, AuditCheck, PackedCoordinate, Option<usize>)>::new();
+        let mut flagged = HashSet::<PackedCoordinate, FxBuildHasher>::default();
+        let mut countries = HashMap::<CountryCode, CountryAudit, FxBuildHasher>::default();
+
+        for (packed, key) in &coordinates {
+            let loc = &locations[*key];
+            let crd = Coordinate::from(packed);
+            let centroid = loc.country_code.centroid();
+
+            let country = countries
+                .entry(loc.country_code)
+                .or_insert_with(|| CountryAudit {
+                    country_code: loc.country_code.to_string(),
+                    ..Default::default()
+                });
+            country.locations += 1;
+            country.prefixes += count(packed);
+
+            let mut checks = Vec::new();
+
+            // every other check would fail too
+            if crd.within_km(&Coordinate::default(), NULL_ISLAND_KM) {
+                checks.push(AuditCheck::NullIsland);
+            } else {
+                if loc.city.is_some() && centroid.is_some_and(|c| c.within_km(&crd, CENTROID_KM)) {
+                    checks.push(AuditCheck::CountryCentroid);
+                }
+                if loc.country_code.contains(crd) == Some(false) {
+                    checks.push(AuditCheck::OutsideCountry);
+                }
+                if loc.is_country_level()
+                    && centroid.is_some_and(|c| !c.within_km(&crd, SPECIFIC_KM))
+                {
+                    checks.push(AuditCheck::SpecificWithoutCity);
+                }
+            }
+
+            if !checks.is_empty() {
+                flagged.insert(*packed);
+            }
+            for check in checks {
+                found.push((loc.country_code.to_string(), check, *packed, None));
+            }
+        }
+
+        for (i, (packed, _)) in conflicts.iter().enumerate() {
+            flagged.insert(*packed);
+            found.push((
+                locations[coordinates[packed]].country_code.to_string(),
+                AuditCheck::ConflictingNames,
+                *packed,
+                Some(i),
+            ));
+        }
+
+        for crd in &flagged {
+            let loc = &locations[coordinates[crd]];
+
+            // UNWRAP: every coordinate's country was added above
+            let country = countries.get_mut(&loc.country_code).unwrap();
+            country.flagged_locations += 1;
+            country.flagged_prefixes += count(crd);
+        }
+
+        found.sort_unstable();
+        let findings = found
+            .into_iter()
+            .map(|(_, check, packed, conflict)| AuditFinding {
+                check,
+                crd: Coordinate::from(&packed),
+                loc: locations[coordinates[&packed]].populate_with(string),
+                prefixes: count(&packed),
+                conflict: conflict.map(|i| conflicts[i].1.populate_with(string)),
+            })
+            .collect();
+
+        let mut countries = countries.into_values().collect::<Vec<_>>();
+        countries.sort_unstable_by(|a, b| a.country_code.cmp(&b.country_code));
+
+        Self {
+            locations: coordinates.len() as u64,
+            prefixes: counts.values().sum(),
+            findings,
+            countries,
+        }
+    }
+
+    /// The number of findings of a check.
+    pub fn count(&self, check: AuditCheck) -> usize {
+        self.findings.iter().filter(|f| f.check == check).count()
+    }
+}
+
+impl LocationStore {
+    pub(crate) fn audit(&self, prefixes: impl Iterator<Item = PackedCoordinate>) -> AuditReport {
+        AuditReport::new(
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().copied(),
+            self.coordinates.iter().map(|(c, k)| (*c, *k)),
+            self.conflicts
+                .iter()
+                .flat_map(|(c, locs)| locs.iter().map(|loc| (*c, *loc))),
+            prefixes,
+        )
+    }
+}
+
+impl<Ip: GenericIp> Audit for SingleDatabase<Ip> {
+    fn audit(&self) -> AuditReport {
+        self.locations.audit(self.ips.iter().map(|(_, _, c)| *c))
+    }
+}
+
+impl Audit for CombinedDatabase {
+    fn audit(&self) -> AuditReport {
+        self.locations.audit(
+            self.ipv4
+                .iter()
+                .map(|(_, _, c)| *c)
+                .chain(self.ipv6.iter().map(|(_, _, c)| *c)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+
+    use crate::{
+        Audit, CombinedDatabase,
+        testing::{BRISBANE, archived, csv},
+    };
+
+    use super::AuditCheck;
+
+    #[test]
+    fn audit() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(
+            csv(&[
+                BRISBANE,
+                "1.0.1.0,1.0.1.255,AU,Queensland,,Brisbane,,-27.4679,153.028",
+                "1.0.2.0,1.0.2.255,ZZ,,,,,0.0,0.0",
+                "1.0.3.0,1.0.3.255,DE,Hesse,,Frankfurt,,48.857,2.352",
+                "1.0.4.0,1.0.4.255,DE,Thuringia,,Niederdorla,,51.1657,10.4515",
+                "1.0.5.0,1.0.5.255,RU,,,,,55.7386,37.6068",
+                "1.0.6.0,1.0.6.255,US,,,,,37.751,-97.822",
+                "1.0.7.0,1.0.7.255,JP,Tokyo,,Shinjuku,,35.6938,139.7034",
+                "1.0.8.0,1.0.8.255,JP,Tokyo,,Shinjuku-ku,,35.6938,139.7034",
+                "1.0.9.0,1.0.9.255,JP,Tokyo,,shinjuku,,35.6938,139.7034",
+                "1.0.10.0,1.0.10.255,JP,Tokyo,,Shinjuku-ku,,35.6938,139.7034",
+            ])
+            .as_bytes(),
+            "".as_bytes(),
+            false,
+        )?;
+
+        let report = db.audit();
+        assert_eq!(report.locations, 7);
+        assert_eq!(report.prefixes, 11);
+
+        let findings = report
+            .findings
+            .iter()
+            .map(|f| (f.loc.country_code.as_str(), f.check, f.prefixes))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            findings,
+            [
+                ("DE", AuditCheck::CountryCentroid, 1),
+                ("DE", AuditCheck::OutsideCountry, 1),
+                ("JP", AuditCheck::ConflictingNames, 4),
+                ("RU", AuditCheck::SpecificWithoutCity, 1),
+                ("ZZ", AuditCheck::NullIsland, 1),
+            ]
+        );
+
+        let conflict = &report.findings[2];
+        assert_eq!(conflict.loc.city.as_deref(), Some("Shinjuku"));
+        assert_eq!(
+            conflict.conflict.as_ref().and_then(|c| c.city.as_deref()),
+            Some("Shinjuku-ku")
+        );
+
+        // a differently cased name isn't a conflict, and a location is only flagged once
+        let flagged = report
+            .countries
+            .iter()
+            .map(|c| (c.country_code.as_str(), c.locations, c.flagged_locations))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flagged,
+            [
+                ("AU", 1, 0),
+                ("DE", 2, 2),
+                ("JP", 1, 1),
+                ("RU", 1, 1),
+                ("US", 1, 0),
+                ("ZZ", 1, 1),
+            ]
+        );
+        assert_eq!(report.count(AuditCheck::OutsideCountry), 1);
+
+        assert_eq!(archived(&db, |db| db.audit())?, report);
+
+        Ok(())
+    }
+}
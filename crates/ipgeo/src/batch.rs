@@ -0,0 +1,196 @@
+//! Looking up many addresses at once, e.g. every client in a web server's access logs.
+
+use std::{
+    io::{BufRead, Write},
+    net::IpAddr,
+    num::NonZero,
+    thread,
+};
+
+use crate::{Database, Error, LookupInfo, LookupMatch};
+
+/// Batches smaller than this are looked up on the calling thread, it isn't worth spawning more.
+const MIN_PARALLEL_CHUNK: usize = 4096;
+
+/// See [`Database::get_batch`].
+pub(crate) fn get_batch<Ip, D>(db: &D, ips: &[Ip], parallel: bool) -> Vec<Option<LookupMatch>>
+where
+    D: Database<Ip> + Sync + ?Sized,
+    Ip: Copy + Ord + Send + Sync,
+{
+    // neighbouring addresses walk the same nodes of the table, so they're looked up in order
+    let mut order = (0..ips.len()).collect::<Vec<_>>();
+    order.sort_unstable_by_key(|&i| ips[i]);
+
+    let threads = match parallel {
+        true => thread::available_parallelism().map_or(1, NonZero::get),
+        false => 1,
+    };
+    let chunk_len = ips.len().div_ceil(threads).max(MIN_PARALLEL_CHUNK);
+
+    let mut results = vec![None; ips.len()];
+
+    // a single chunk is looked up on the calling thread
+    if chunk_len >= ips.len() {
+        for i in order {
+            results[i] = db.get_match(ips[i]);
+        }
+
+        return results;
+    }
+
+    thread::scope(|scope| {
+        let chunks = order
+            .chunks(chunk_len)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&i| (i, db.get_match(ips[i])))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for chunk in chunks {
+            // UNWRAP: lookups don't panic, and if they did it should be propagated
+            for (i, matched) in chunk.join().unwrap() {
+                results[i] = matched;
+            }
+        }
+    });
+
+    results
+}
+
+/// Read a list of addresses, one per line.
+///
+/// Only the first field of each line (up to whitespace or a comma) is read, so common
+/// access log formats and CSV files with the address first can be read as they are.
+/// Empty lines and `#` comments are skipped, returns the addresses and how many other lines weren't one.
+pub fn read_ips(read: impl BufRead) -> Result<(Vec<IpAddr>, u64), Error> {
+    let mut ips = Vec::new();
+    let mut invalid = 0;
+
+    for line in read.lines() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let field = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .next()
+            .unwrap_or_default();
+
+        match field.parse() {
+            Ok(ip) => ips.push(ip),
+            Err(_) => invalid += 1,
+        }
+    }
+
+    Ok((ips, invalid))
+}
+
+/// Write lookup results as a CSV file with an `ip,country,region,city,lat,lng` header.
+///
+/// Addresses that weren't found only have their `ip` column filled.
+pub fn write_lookups_csv<'a>(
+    w: impl Write,
+    lookups: impl IntoIterator<Item = (IpAddr, Option<&'a LookupInfo>)>,
+) -> Result<(), Error> {
+    let mut w = csv::Writer::from_writer(w);
+
+    w.write_record(["ip", "country", "region", "city", "lat", "lng"])?;
+
+    for (ip, info) in lookups {
+        let ip = ip.to_string();
+
+        match info {
+            Some(info) => w.write_record([
+                ip.as_str(),
+                info.loc.country_code.as_str(),
+                info.loc.region.as_deref().unwrap_or_default(),
+                info.loc.city.as_deref().unwrap_or_default(),
+                &info.crd.lat.to_string(),
+                &info.crd.lng.to_string(),
+            ])?,
+            None => w.write_record([ip.as_str(), "", "", "", "", ""])?,
+        }
+    }
+
+    w.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::IpAddr};
+
+    use crate::{
+        CombinedDatabase, Database,
+        testing::{BRISBANE, FUZHOU, TOKYO_IPV6, csv},
+    };
+
+    use super::{read_ips, write_lookups_csv};
+
+    #[test]
+    fn batch() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(
+            csv(&[BRISBANE, FUZHOU]).as_bytes(),
+            csv(&[TOKYO_IPV6]).as_bytes(),
+            false,
+        )?;
+
+        let (ips, invalid) = read_ips(
+            "# clients
+1.0.1.7 - - [10/Oct/2025:13:55:36 +0000] \"GET / HTTP/1.1\" 200
+2001:200::1,GET,/
+not-an-ip
+
+1.0.0.1
+9.9.9.9
+"
+            .as_bytes(),
+        )?;
+        assert_eq!(invalid, 1);
+        assert_eq!(ips.len(), 4);
+
+        // results keep the input's order, and are the same as looking up one at a time
+        let many = ips.repeat(2000);
+        for parallel in [false, true] {
+            let results = db.get_batch(&many, parallel);
+
+            assert_eq!(results.len(), many.len());
+            for (ip, matched) in many.iter().zip(&results) {
+                assert_eq!(matched, &db.get_match(*ip));
+            }
+        }
+
+        // coordinates are written as stored, within the packing error
+        let results = db.get_batch(&ips, true);
+        let mut csv = Vec::new();
+        write_lookups_csv(
+            &mut csv,
+            ips.iter()
+                .zip(&results)
+                .map(|(ip, matched)| (*ip, matched.as_ref().map(|m| &m.info))),
+        )?;
+        assert_eq!(
+            String::from_utf8(csv)?,
+            "ip,country,region,city,lat,lng
+1.0.1.7,CN,Fujian,Fuzhou,26.06134,119.305115
+2001:200::1,JP,Tokyo,Tokyo,35.688263,139.69055
+1.0.0.1,AU,Queensland,Brisbane,-27.467613,153.02817
+9.9.9.9,,,,,
+"
+        );
+
+        assert!(db.get_batch(&[] as &[IpAddr], true).is_empty());
+
+        Ok(())
+    }
+}
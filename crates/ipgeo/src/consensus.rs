@@ -0,0 +1,203 @@
+//! Combining the answers of several databases for the same address.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{Coordinate, LookupInfo};
+
+/// Where most databases place an address, and how far apart they are, see [`consensus`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Consensus {
+    /// The country the most answers agree on, ties go to the earliest answer.
+    pub country_code: String,
+    /// Median latitude and longitude of the answers in `country_code`.
+    ///
+    /// Longitudes are compared the short way around, so answers either side of the antimeridian
+    /// have a median next to it rather than on the other side of the world.
+    pub crd: Coordinate,
+    /// Answers combined.
+    pub answers: u32,
+    /// Answers placing the address in `country_code`.
+    pub agreeing: u32,
+    /// Greatest distance between any two answers.
+    pub max_spread_km: f32,
+    /// Mean distance between every pair of answers.
+    pub mean_spread_km: f32,
+}
+
+impl Consensus {
+    /// True if every answer is in the same country and within `radius_km` of each other.
+    pub fn is_certain(&self, radius_km: f32) -> bool {
+        self.agreeing == self.answers && self.max_spread_km <= radius_km
+    }
+}
+
+/// Combine the answers of several databases for the same address,
+/// by majority country and median coordinate.
+///
+/// Returns [`None`] without any answers.
+pub fn consensus<'a>(answers: impl IntoIterator<Item = &'a LookupInfo>) -> Option<Consensus> {
+    let answers = answers.into_iter().collect::<Vec<_>>();
+
+    // countries in the order they first appear, so ties go to the earliest
+    let mut countries = Vec::<(&str, u32)>::new();
+    for info in &answers {
+        match countries
+            .iter_mut()
+            .find(|(c, _)| c.eq_ignore_ascii_case(&info.loc.country_code))
+        {
+            Some((_, count)) => *count += 1,
+            None => countries.push((&info.loc.country_code, 1)),
+        }
+    }
+
+    let (country_code, agreeing) = countries
+        .into_iter()
+        .reduce(|best, c| if c.1 > best.1 { c } else { best })?;
+
+    let (mut lats, mut lngs): (Vec<_>, Vec<_>) = answers
+        .iter()
+        .filter(|info| info.loc.country_code.eq_ignore_ascii_case(country_code))
+        .map(|info| (info.crd.lat, info.crd.lng))
+        .unzip();
+
+    // within 180° of the first answer, past ±180 if that's the short way
+    let reference = lngs[0];
+    for lng in &mut lngs {
+        match *lng - reference {
+            d if d > 180.0 => *lng -= 360.0,
+            d if d < -180.0 => *lng += 360.0,
+            _ => (),
+        }
+    }
+
+    let mut pairs = 0;
+    let mut total_km = 0.0;
+    let mut max_spread_km = 0f32;
+    for (i, a) in answers.iter().enumerate() {
+        for b in &answers[i + 1..] {
+            let km = a.crd.distance_km(&b.crd);
+
+            pairs += 1;
+            total_km += km as f64;
+            max_spread_km = max_spread_km.max(km);
+        }
+    }
+
+    Some(Consensus {
+        country_code: country_code.to_string(),
+        crd: Coordinate {
+            lat: median(&mut lats),
+            lng: wrap_lng(median(&mut lngs)),
+        },
+        answers: answers.len() as u32,
+        agreeing,
+        max_spread_km,
+        mean_spread_km: match pairs {
+            0 => 0.0,
+            pairs => (total_km / pairs as f64) as f32,
+        },
+    })
+}
+
+/// The median of a non-empty list, the mean of the middle two for an even length.
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_unstable_by(f32::total_cmp);
+
+    let mid = values.len() / 2;
+    match values.len() % 2 {
+        0 => (values[mid - 1] + values[mid]) / 2.0,
+        _ => values[mid],
+    }
+}
+
+/// Bring a longitude up to a turn past the antimeridian back within ±180°.
+fn wrap_lng(lng: f32) -> f32 {
+    match lng {
+        lng if lng > 180.0 => lng - 360.0,
+        lng if lng < -180.0 => lng + 360.0,
+        lng => lng,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Coordinate, Location, LookupInfo};
+
+    use super::consensus;
+
+    fn info(country_code: &str, lat: f32, lng: f32) -> LookupInfo {
+        LookupInfo {
+            crd: Coordinate { lat, lng },
+            loc: Location {
+                country_code: country_code.to_string(),
+                ..Default::default()
+            },
+            country_level: true,
+            translated: false,
+        }
+    }
+
+    #[test]
+    fn consensus_of_answers() {
+        assert_eq!(consensus([]), None);
+
+        let single = consensus([&info("DE", 52.52, 13.405)]).unwrap();
+        assert_eq!(single.agreeing, 1);
+        assert_eq!(single.max_spread_km, 0.0);
+        assert!(single.is_certain(0.0));
+
+        // Berlin, Hamburg and Munich outvote Vienna, whose coordinate is left out of the median
+        let answers = [
+            info("DE", 52.52, 13.405),
+            info("AT", 48.2082, 16.3738),
+            info("DE", 53.5511, 9.9937),
+            info("de", 48.1351, 11.582),
+        ];
+        let agreed = consensus(&answers).unwrap();
+        assert_eq!(agreed.country_code, "DE");
+        assert_eq!((agreed.answers, agreed.agreeing), (4, 3));
+        assert_eq!(
+            agreed.crd,
+            Coordinate {
+                lat: 52.52,
+                lng: 11.582
+            }
+        );
+
+        // Hamburg to Vienna is the furthest pair
+        let furthest = answers[1].crd.distance_km(&answers[2].crd);
+        assert_eq!(agreed.max_spread_km, furthest);
+        assert!(agreed.mean_spread_km > 0.0 && agreed.mean_spread_km < furthest);
+        assert!(!agreed.is_certain(1000.0));
+
+        // ties go to the earliest answer
+        let tied = consensus(&answers[..2]).unwrap();
+        assert_eq!(tied.country_code, "DE");
+        assert_eq!(tied.crd.lat, 52.52);
+    }
+
+    #[test]
+    fn across_antimeridian() {
+        // Fiji's islands are on both sides, the median is east of the first answer
+        let answers = [
+            info("FJ", -17.0, 179.0),
+            info("FJ", -17.0, -179.0),
+            info("FJ", -17.0, -178.0),
+        ];
+        assert_eq!(consensus(&answers).unwrap().crd.lng, -179.0);
+
+        // and west of it
+        let answers = [
+            info("FJ", -17.0, -179.0),
+            info("FJ", -17.0, 179.0),
+            info("FJ", -17.0, 178.0),
+        ];
+        assert_eq!(consensus(&answers).unwrap().crd.lng, 179.0);
+
+        // the mean of the middle two is still next to the antimeridian
+        let answers = [info("FJ", -17.0, 179.5), info("FJ", -17.0, -179.5)];
+        assert_eq!(consensus(&answers).unwrap().crd.lng.abs(), 180.0);
+    }
+}
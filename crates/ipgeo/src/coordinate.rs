@@ -4,6 +4,8 @@ use std::hash;
 
 const LAT_RANGE: f32 = 90.0;
 const LNG_RANGE: f32 = 180.0;
+/// Mean radius of the earth.
+pub(crate) const EARTH_RADIUS_KM: f64 = 6371.0088;
 
 /// A basic latitude/longitude pair.
 #[derive(Copy, Clone, Debug, Default, specta::Type, serde::Serialize, serde::Deserialize)]
@@ -21,6 +23,25 @@ impl Coordinate {
             && pack_degree(self.lng, LNG_RANGE) == pack_degree(other.lng, LNG_RANGE)
     }
 
+    /// Great-circle distance to another coordinate in kilometres, using the haversine formula.
+    pub fn distance_km(&self, other: &Self) -> f32 {
+        let (lat1, lat2) = (
+            (self.lat as f64).to_radians(),
+            (other.lat as f64).to_radians(),
+        );
+        let dlat = lat2 - lat1;
+        let dlng = (other.lng as f64 - self.lng as f64).to_radians();
+
+        let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+
+        (2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()) as f32
+    }
+
+    /// Returns true if the great-circle distance to another coordinate is at most `radius_km`.
+    pub fn within_km(&self, other: &Self, radius_km: f32) -> bool {
+        self.distance_km(other) <= radius_km
+    }
+
     fn as_bytes(&self) -> u64 {
         let mut out = [0; 8];
         let (one, two) = out.split_at_mut(4);
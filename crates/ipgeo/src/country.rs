@@ -0,0 +1,543 @@
+//! Built-in ISO 3166 country data: names, continents, flags and centroids.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{Coordinate, locations::CountryCode};
+
+use Continent::{Africa, Antarctica, Asia, Europe, NorthAmerica, Oceania, SouthAmerica};
+
+/// Languages that country and continent names are available in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Es,
+    /// Simplified Chinese.
+    Zh,
+}
+
+/// A continent, as assigned to countries by GeoNames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Continent {
+    Africa,
+    Antarctica,
+    Asia,
+    Europe,
+    NorthAmerica,
+    Oceania,
+    SouthAmerica,
+}
+
+impl Continent {
+    /// The two-letter continent code, e.g. `EU`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Africa => "AF",
+            Antarctica => "AN",
+            Asia => "AS",
+            Europe => "EU",
+            NorthAmerica => "NA",
+            Oceania => "OC",
+            SouthAmerica => "SA",
+        }
+    }
+
+    pub fn name(&self, lang: Language) -> &'static str {
+        let [en, es, zh] = match self {
+            Africa => ["Africa", "África", "非洲"],
+            Antarctica => ["Antarctica", "Antártida", "南极洲"],
+            Asia => ["Asia", "Asia", "亚洲"],
+            Europe => ["Europe", "Europa", "欧洲"],
+            NorthAmerica => ["North America", "América del Norte", "北美洲"],
+            Oceania => ["Oceania", "Oceanía", "大洋洲"],
+            SouthAmerica => ["South America", "América del Sur", "南美洲"],
+        };
+
+        match lang {
+            Language::En => en,
+            Language::Es => es,
+            Language::Zh => zh,
+        }
+    }
+}
+
+/// A country's name in each [`Language`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
+pub struct CountryNames {
+    pub en: Cow<'static, str>,
+    pub es: Cow<'static, str>,
+    pub zh: Cow<'static, str>,
+}
+
+/// Details of an ISO 3166-1 country, see [`CountryCode::country`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Country {
+    pub names: CountryNames,
+    pub continent: Continent,
+    /// Flag emoji, made of the code's two regional indicator symbols.
+    pub flag: Cow<'static, str>,
+}
+
+impl Country {
+    /// The country with an ISO 3166-1 alpha-2 code, in either case.
+    pub fn from_code(code: &str) -> Option<Self> {
+        CountryCode::from(code).country()
+    }
+
+    pub fn name(&self, lang: Language) -> &str {
+        match lang {
+            Language::En => &self.names.en,
+            Language::Es => &self.names.es,
+            Language::Zh => &self.names.zh,
+        }
+    }
+}
+
+/// A country code with its flag, continent, English, Spanish and Chinese names,
+/// centroid as `(lat, lng)` and bounding box as `(west, south, east, north)`.
+type CountryEntry = (
+    [u8; 2],
+    &'static str,
+    Continent,
+    [&'static str; 3],
+    (f32, f32),
+    (f32, f32, f32, f32),
+);
+
+/// How far outside its bounding box a coordinate can be and still be in the country, in degrees.
+const BOUNDS_MARGIN_DEG: f32 = 0.5;
+
+/// Every ISO 3166-1 country, including the user-assigned `XK` for Kosovo, sorted by country code.
+///
+/// Centroids and bounding boxes are approximate, the boxes include outlying islands
+/// and have `west > east` if they cross the antimeridian.
+#[rustfmt::skip]
+static COUNTRIES: &[CountryEntry] = &[
+    (*b"AD", "🇦🇩", Europe, ["Andorra", "Andorra", "安道尔"], (42.5462, 1.6016), (1.41, 42.43, 1.79, 42.66)),
+    (*b"AE", "🇦🇪", Asia, ["United Arab Emirates", "Emiratos Árabes Unidos", "阿拉伯联合酋长国"], (23.4241, 53.8478), (51.5, 22.6, 56.4, 26.1)),
+    (*b"AF", "🇦🇫", Asia, ["Afghanistan", "Afganistán", "阿富汗"], (33.9391, 67.71), (60.5, 29.3, 74.9, 38.5)),
+    (*b"AG", "🇦🇬", NorthAmerica, ["Antigua and Barbuda", "Antigua y Barbuda", "安提瓜和巴布达"], (17.0608, -61.7964), (-62.4, 16.9, -61.6, 17.8)),
+    (*b"AI", "🇦🇮", NorthAmerica, ["Anguilla", "Anguila", "安圭拉"], (18.2206, -63.0686), (-63.5, 18.1, -62.9, 18.6)),
+    (*b"AL", "🇦🇱", Europe, ["Albania", "Albania", "阿尔巴尼亚"], (41.1533, 20.1683), (19.3, 39.6, 21.1, 42.7)),
+    (*b"AM", "🇦🇲", Asia, ["Armenia", "Armenia", "亚美尼亚"], (40.0691, 45.0382), (43.4, 38.8, 46.7, 41.3)),
+    (*b"AO", "🇦🇴", Africa, ["Angola", "Angola", "安哥拉"], (-11.2027, 17.8739), (11.6, -18.1, 24.1, -4.4)),
+    (*b"AQ", "🇦🇶", Antarctica, ["Antarctica", "Antártida", "南极洲"], (-75.251, -0.0714), (-180.0, -90.0, 180.0, -60.0)),
+    (*b"AR", "🇦🇷", SouthAmerica, ["Argentina", "Argentina", "阿根廷"], (-38.4161, -63.6167), (-73.6, -55.1, -53.6, -21.8)),
+    (*b"AS", "🇦🇸", Oceania, ["American Samoa", "Samoa Americana", "美属萨摩亚"], (-14.271, -170.1322), (-171.1, -14.6, -168.1, -11.0)),
+    (*b"AT", "🇦🇹", Europe, ["Austria", "Austria", "奥地利"], (47.5162, 14.5501), (9.5, 46.4, 17.2, 49.0)),
+    (*b"AU", "🇦🇺", Oceania, ["Australia", "Australia", "澳大利亚"], (-25.2744, 133.7751), (112.9, -54.8, 159.2, -9.1)),
+    (*b"AW", "🇦🇼", NorthAmerica, ["Aruba", "Aruba", "阿鲁巴"], (12.5211, -69.9683), (-70.1, 12.4, -69.8, 12.7)),
+    (*b"AX", "🇦🇽", Europe, ["Åland Islands", "Islas Åland", "奥兰群岛"], (60.1785, 19.9156), (19.3, 59.7, 21.1, 60.5)),
+    (*b"AZ", "🇦🇿", Asia, ["Azerbaijan", "Azerbaiyán", "阿塞拜疆"], (40.1431, 47.5769), (44.8, 38.4, 50.6, 41.9)),
+    (*b"BA", "🇧🇦", Europe, ["Bosnia and Herzegovina", "Bosnia y Herzegovina", "波斯尼亚和黑塞哥维那"], (43.9159, 17.6791), (15.7, 42.5, 19.7, 45.3)),
+    (*b"BB", "🇧🇧", NorthAmerica, ["Barbados", "Barbados", "巴巴多斯"], (13.1939, -59.5432), (-59.7, 13.0, -59.4, 13.4)),
+    (*b"BD", "🇧🇩", Asia, ["Bangladesh", "Bangladés", "孟加拉国"], (23.685, 90.3563), (88.0, 20.6, 92.7, 26.6)),
+    (*b"BE", "🇧🇪", Europe, ["Belgium", "Bélgica", "比利时"], (50.5039, 4.4699), (2.5, 49.5, 6.4, 51.5)),
+    (*b"BF", "🇧🇫", Africa, ["Burkina Faso", "Burkina Faso", "布基纳法索"], (12.2383, -1.5616), (-5.5, 9.4, 2.4, 15.1)),
+    (*b"BG", "🇧🇬", Europe, ["Bulgaria", "Bulgaria", "保加利亚"], (42.7339, 25.4858), (22.4, 41.2, 28.6, 44.2)),
+    (*b"BH", "🇧🇭", Asia, ["Bahrain", "Baréin", "巴林"], (25.9304, 50.6378), (50.3, 25.5, 50.9, 26.3)),
+    (*b"BI", "🇧🇮", Africa, ["Burundi", "Burundi", "布隆迪"], (-3.3731, 29.9189), (29.0, -4.5, 30.9, -2.3)),
+    (*b"BJ", "🇧🇯", Africa, ["Benin", "Benín", "贝宁"], (9.3077, 2.3158), (0.8, 6.1, 3.9, 12.4)),
+    (*b"BL", "🇧🇱", NorthAmerica, ["Saint Barthélemy", "San Bartolomé", "圣巴泰勒米"], (17.9, -62.8333), (-62.95, 17.85, -62.78, 17.97)),
+    (*b"BM", "🇧🇲", NorthAmerica, ["Bermuda", "Bermudas", "百慕大"], (32.3214, -64.7574), (-64.9, 32.2, -64.6, 32.4)),
+    (*b"BN", "🇧🇳", Asia, ["Brunei", "Brunéi", "文莱"], (4.5353, 114.7277), (114.0, 4.0, 115.4, 5.1)),
+    (*b"BO", "🇧🇴", SouthAmerica, ["Bolivia", "Bolivia", "玻利维亚"], (-16.2902, -63.5887), (-69.7, -22.9, -57.4, -9.6)),
+    (*b"BQ", "🇧🇶", NorthAmerica, ["Caribbean Netherlands", "Caribe Neerlandés", "荷兰加勒比区"], (12.1784, -68.2385), (-68.5, 12.0, -62.9, 17.7)),
+    (*b"BR", "🇧🇷", SouthAmerica, ["Brazil", "Brasil", "巴西"], (-14.235, -51.9253), (-74.0, -33.8, -28.8, 5.3)),
+    (*b"BS", "🇧🇸", NorthAmerica, ["Bahamas", "Bahamas", "巴哈马"], (25.0343, -77.3963), (-79.4, 20.9, -72.7, 27.3)),
+    (*b"BT", "🇧🇹", Asia, ["Bhutan", "Bután", "不丹"], (27.5142, 90.4336), (88.7, 26.7, 92.1, 28.3)),
+    (*b"BV", "🇧🇻", Antarctica, ["Bouvet Island", "Isla Bouvet", "布韦岛"], (-54.4232, 3.4132), (3.3, -54.5, 3.5, -54.4)),
+    (*b"BW", "🇧🇼", Africa, ["Botswana", "Botsuana", "博茨瓦纳"], (-22.3285, 24.6849), (20.0, -26.9, 29.4, -17.8)),
+    (*b"BY", "🇧🇾", Europe, ["Belarus", "Bielorrusia", "白俄罗斯"], (53.7098, 27.9534), (23.2, 51.3, 32.8, 56.2)),
+    (*b"BZ", "🇧🇿", NorthAmerica, ["Belize", "Belice", "伯利兹"], (17.1899, -88.4976), (-89.2, 15.9, -87.5, 18.5)),
+    (*b"CA", "🇨🇦", NorthAmerica, ["Canada", "Canadá", "加拿大"], (56.1304, -106.3468), (-141.0, 41.7, -52.6, 83.1)),
+    (*b"CC", "🇨🇨", Asia, ["Cocos (Keeling) Islands", "Islas Cocos", "科科斯（基林）群岛"], (-12.1642, 96.871), (96.8, -12.2, 96.95, -11.8)),
+    (*b"CD", "🇨🇩", Africa, ["DR Congo", "República Democrática del Congo", "刚果（金）"], (-4.0383, 21.7587), (12.2, -13.5, 31.3, 5.4)),
+    (*b"CF", "🇨🇫", Africa, ["Central African Republic", "República Centroafricana", "中非共和国"], (6.6111, 20.9394), (14.4, 2.2, 27.5, 11.0)),
+    (*b"CG", "🇨🇬", Africa, ["Congo", "Congo", "刚果（布）"], (-0.228, 15.8277), (11.1, -5.0, 18.6, 3.7)),
+    (*b"CH", "🇨🇭", Europe, ["Switzerland", "Suiza", "瑞士"], (46.8182, 8.2275), (5.96, 45.8, 10.5, 47.8)),
+    (*b"CI", "🇨🇮", Africa, ["Côte d'Ivoire", "Costa de Marfil", "科特迪瓦"], (7.54, -5.5471), (-8.6, 4.3, -2.5, 10.7)),
+    (*b"CK", "🇨🇰", Oceania, ["Cook Islands", "Islas Cook", "库克群岛"], (-21.2367, -159.7777), (-166.0, -21.95, -157.3, -8.9)),
+    (*b"CL", "🇨🇱", SouthAmerica, ["Chile", "Chile", "智利"], (-35.6751, -71.543), (-109.5, -56.6, -66.4, -17.5)),
+    (*b"CM", "🇨🇲", Africa, ["Cameroon", "Camerún", "喀麦隆"], (7.3697, 12.3547), (8.5, 1.7, 16.2, 13.1)),
+    (*b"CN", "🇨🇳", Asia, ["China", "China", "中国"], (35.8617, 104.1954), (73.5, 18.1, 134.8, 53.6)),
+    (*b"CO", "🇨🇴", SouthAmerica, ["Colombia", "Colombia", "哥伦比亚"], (4.5709, -74.2973), (-81.8, -4.3, -66.9, 13.4)),
+    (*b"CR", "🇨🇷", NorthAmerica, ["Costa Rica", "Costa Rica", "哥斯达黎加"], (9.7489, -83.7534), (-87.1, 5.5, -82.6, 11.2)),
+    (*b"CU", "🇨🇺", NorthAmerica, ["Cuba", "Cuba", "古巴"], (21.5218, -77.7812), (-85.0, 19.8, -74.1, 23.3)),
+    (*b"CV", "🇨🇻", Africa, ["Cape Verde", "Cabo Verde", "佛得角"], (16.0021, -24.0132), (-25.4, 14.8, -22.7, 17.2)),
+    (*b"CW", "🇨🇼", NorthAmerica, ["Curaçao", "Curazao", "库拉索"], (12.1696, -68.99), (-69.2, 12.0, -68.7, 12.4)),
+    (*b"CX", "🇨🇽", Oceania, ["Christmas Island", "Isla de Navidad", "圣诞岛"], (-10.4475, 105.6904), (105.5, -10.6, 105.8, -10.4)),
+    (*b"CY", "🇨🇾", Europe, ["Cyprus", "Chipre", "塞浦路斯"], (35.1264, 33.4299), (32.2, 34.5, 34.6, 35.7)),
+    (*b"CZ", "🇨🇿", Europe, ["Czechia", "Chequia", "捷克"], (49.8175, 15.473), (12.1, 48.5, 18.9, 51.1)),
+    (*b"DE", "🇩🇪", Europe, ["Germany", "Alemania", "德国"], (51.1657, 10.4515), (5.9, 47.3, 15.1, 55.1)),
+    (*b"DJ", "🇩🇯", Africa, ["Djibouti", "Yibuti", "吉布提"], (11.8251, 42.5903), (41.7, 10.9, 43.5, 12.8)),
+    (*b"DK", "🇩🇰", Europe, ["Denmark", "Dinamarca", "丹麦"], (56.2639, 9.5018), (8.0, 54.5, 15.2, 57.8)),
+    (*b"DM", "🇩🇲", NorthAmerica, ["Dominica", "Dominica", "多米尼克"], (15.415, -61.371), (-61.5, 15.2, -61.2, 15.7)),
+    (*b"DO", "🇩🇴", NorthAmerica, ["Dominican Republic", "República Dominicana", "多米尼加共和国"], (18.7357, -70.1627), (-72.0, 17.5, -68.3, 19.95)),
+    (*b"DZ", "🇩🇿", Africa, ["Algeria", "Argelia", "阿尔及利亚"], (28.0339, 1.6596), (-8.7, 18.9, 12.0, 37.1)),
+    (*b"EC", "🇪🇨", SouthAmerica, ["Ecuador", "Ecuador", "厄瓜多尔"], (-1.8312, -78.1834), (-92.0, -5.0, -75.2, 1.7)),
+    (*b"EE", "🇪🇪", Europe, ["Estonia", "Estonia", "爱沙尼亚"], (58.5953, 25.0136), (21.8, 57.5, 28.2, 59.7)),
+    (*b"EG", "🇪🇬", Africa, ["Egypt", "Egipto", "埃及"], (26.8206, 30.8025), (24.7, 22.0, 36.9, 31.7)),
+    (*b"EH", "🇪🇭", Africa, ["Western Sahara", "Sáhara Occidental", "西撒哈拉"], (24.2155, -12.8858), (-17.1, 20.8, -8.7, 27.7)),
+    (*b"ER", "🇪🇷", Africa, ["Eritrea", "Eritrea", "厄立特里亚"], (15.1794, 39.7823), (36.4, 12.4, 43.1, 18.0)),
+    (*b"ES", "🇪🇸", Europe, ["Spain", "España", "西班牙"], (40.4637, -3.7492), (-18.2, 27.6, 4.3, 43.8)),
+    (*b"ET", "🇪🇹", Africa, ["Ethiopia", "Etiopía", "埃塞俄比亚"], (9.145, 40.4897), (33.0, 3.4, 48.0, 14.9)),
+    (*b"FI", "🇫🇮", Europe, ["Finland", "Finlandia", "芬兰"], (61.9241, 25.7482), (19.1, 59.8, 31.6, 70.1)),
+    (*b"FJ", "🇫🇯", Oceania, ["Fiji", "Fiyi", "斐济"], (-16.5782, 179.4144), (174.0, -21.8, -178.2, -12.5)),
+    (*b"FK", "🇫🇰", SouthAmerica, ["Falkland Islands", "Islas Malvinas", "福克兰群岛"], (-51.7963, -59.5236), (-61.4, -52.5, -57.7, -51.0)),
+    (*b"FM", "🇫🇲", Oceania, ["Micronesia", "Micronesia", "密克罗尼西亚"], (7.4256, 150.5508), (137.9, 0.9, 163.1, 10.1)),
+    (*b"FO", "🇫🇴", Europe, ["Faroe Islands", "Islas Feroe", "法罗群岛"], (61.8926, -6.9118), (-7.7, 61.4, -6.2, 62.4)),
+    (*b"FR", "🇫🇷", Europe, ["France", "Francia", "法国"], (46.2276, 2.2137), (-5.2, 41.3, 9.6, 51.1)),
+    (*b"GA", "🇬🇦", Africa, ["Gabon", "Gabón", "加蓬"], (-0.8037, 11.6094), (8.7, -4.0, 14.5, 2.3)),
+    (*b"GB", "🇬🇧", Europe, ["United Kingdom", "Reino Unido", "英国"], (55.3781, -3.436), (-8.7, 49.9, 1.8, 60.9)),
+    (*b"GD", "🇬🇩", NorthAmerica, ["Grenada", "Granada", "格林纳达"], (12.2628, -61.6042), (-61.8, 11.98, -61.4, 12.55)),
+    (*b"GE", "🇬🇪", Asia, ["Georgia", "Georgia", "格鲁吉亚"], (42.3154, 43.3569), (40.0, 41.0, 46.7, 43.6)),
+    (*b"GF", "🇬🇫", SouthAmerica, ["French Guiana", "Guayana Francesa", "法属圭亚那"], (3.9339, -53.1258), (-54.6, 2.1, -51.6, 5.8)),
+    (*b"GG", "🇬🇬", Europe, ["Guernsey", "Guernsey", "根西岛"], (49.4657, -2.5853), (-2.7, 49.4, -2.2, 49.75)),
+    (*b"GH", "🇬🇭", Africa, ["Ghana", "Ghana", "加纳"], (7.9465, -1.0232), (-3.3, 4.7, 1.2, 11.2)),
+    (*b"GI", "🇬🇮", Europe, ["Gibraltar", "Gibraltar", "直布罗陀"], (36.1377, -5.3454), (-5.37, 36.1, -5.33, 36.16)),
+    (*b"GL", "🇬🇱", NorthAmerica, ["Greenland", "Groenlandia", "格陵兰"], (71.7069, -42.6043), (-73.3, 59.8, -11.3, 83.7)),
+    (*b"GM", "🇬🇲", Africa, ["Gambia", "Gambia", "冈比亚"], (13.4432, -15.3101), (-16.9, 13.0, -13.8, 13.9)),
+    (*b"GN", "🇬🇳", Africa, ["Guinea", "Guinea", "几内亚"], (9.9456, -9.6966), (-15.1, 7.2, -7.6, 12.7)),
+    (*b"GP", "🇬🇵", NorthAmerica, ["Guadeloupe", "Guadalupe", "瓜德罗普"], (16.996, -62.0676), (-61.9, 15.8, -61.0, 16.6)),
+    (*b"GQ", "🇬🇶", Africa, ["Equatorial Guinea", "Guinea Ecuatorial", "赤道几内亚"], (1.6508, 10.2679), (5.6, -1.5, 11.4, 3.8)),
+    (*b"GR", "🇬🇷", Europe, ["Greece", "Grecia", "希腊"], (39.0742, 21.8243), (19.4, 34.8, 29.7, 41.8)),
+    (*b"GS", "🇬🇸", Antarctica, ["South Georgia and the South Sandwich Islands", "Islas Georgias del Sur y Sandwich del Sur", "南乔治亚和南桑威奇群岛"], (-54.4296, -36.5879), (-38.1, -59.5, -26.2, -53.9)),
+    (*b"GT", "🇬🇹", NorthAmerica, ["Guatemala", "Guatemala", "危地马拉"], (15.7835, -90.2308), (-92.3, 13.7, -88.2, 17.8)),
+    (*b"GU", "🇬🇺", Oceania, ["Guam", "Guam", "关岛"], (13.4443, 144.7937), (144.6, 13.2, 145.0, 13.7)),
+    (*b"GW", "🇬🇼", Africa, ["Guinea-Bissau", "Guinea-Bisáu", "几内亚比绍"], (11.8037, -15.1804), (-16.8, 10.9, -13.6, 12.7)),
+    (*b"GY", "🇬🇾", SouthAmerica, ["Guyana", "Guyana", "圭亚那"], (4.8604, -58.9302), (-61.4, 1.2, -56.5, 8.6)),
+    (*b"HK", "🇭🇰", Asia, ["Hong Kong", "Hong Kong", "香港"], (22.3964, 114.1095), (113.8, 22.15, 114.45, 22.6)),
+    (*b"HM", "🇭🇲", Antarctica, ["Heard Island and McDonald Islands", "Islas Heard y McDonald", "赫德岛和麦克唐纳群岛"], (-53.0818, 73.5042), (73.2, -53.2, 73.9, -52.9)),
+    (*b"HN", "🇭🇳", NorthAmerica, ["Honduras", "Honduras", "洪都拉斯"], (15.2, -86.2419), (-89.4, 12.98, -83.1, 17.5)),
+    (*b"HR", "🇭🇷", Europe, ["Croatia", "Croacia", "克罗地亚"], (45.1, 15.2), (13.4, 42.4, 19.5, 46.6)),
+    (*b"HT", "🇭🇹", NorthAmerica, ["Haiti", "Haití", "海地"], (18.9712, -72.2852), (-74.5, 18.0, -71.6, 20.1)),
+    (*b"HU", "🇭🇺", Europe, ["Hungary", "Hungría", "匈牙利"], (47.1625, 19.5033), (16.1, 45.7, 22.9, 48.6)),
+    (*b"ID", "🇮🇩", Asia, ["Indonesia", "Indonesia", "印度尼西亚"], (-0.7893, 113.9213), (95.0, -11.0, 141.1, 6.1)),
+    (*b"IE", "🇮🇪", Europe, ["Ireland", "Irlanda", "爱尔兰"], (53.4129, -8.2439), (-10.7, 51.4, -6.0, 55.4)),
+    (*b"IL", "🇮🇱", Asia, ["Israel", "Israel", "以色列"], (31.0461, 34.8516), (34.2, 29.4, 35.9, 33.4)),
+    (*b"IM", "🇮🇲", Europe, ["Isle of Man", "Isla de Man", "马恩岛"], (54.2361, -4.5481), (-4.8, 54.0, -4.3, 54.45)),
+    (*b"IN", "🇮🇳", Asia, ["India", "India", "印度"], (20.5937, 78.9629), (68.1, 6.7, 97.4, 37.1)),
+    (*b"IO", "🇮🇴", Asia, ["British Indian Ocean Territory", "Territorio Británico del Océano Índico", "英属印度洋领地"], (-6.3432, 71.8765), (71.2, -7.5, 72.5, -5.2)),
+    (*b"IQ", "🇮🇶", Asia, ["Iraq", "Irak", "伊拉克"], (33.2232, 43.6793), (38.8, 29.0, 48.6, 37.4)),
+    (*b"IR", "🇮🇷", Asia, ["Iran", "Irán", "伊朗"], (32.4279, 53.688), (44.0, 25.0, 63.4, 39.8)),
+    (*b"IS", "🇮🇸", Europe, ["Iceland", "Islandia", "冰岛"], (64.9631, -19.0208), (-24.6, 63.3, -13.4, 67.2)),
+    (*b"IT", "🇮🇹", Europe, ["Italy", "Italia", "意大利"], (41.8719, 12.5674), (6.6, 35.4, 18.6, 47.1)),
+    (*b"JE", "🇯🇪", Europe, ["Jersey", "Jersey", "泽西岛"], (49.2144, -2.1313), (-2.3, 49.15, -2.0, 49.3)),
+    (*b"JM", "🇯🇲", NorthAmerica, ["Jamaica", "Jamaica", "牙买加"], (18.1096, -77.2975), (-78.4, 17.0, -75.9, 18.6)),
+    (*b"JO", "🇯🇴", Asia, ["Jordan", "Jordania", "约旦"], (30.5852, 36.2384), (34.9, 29.2, 39.3, 33.4)),
+    (*b"JP", "🇯🇵", Asia, ["Japan", "Japón", "日本"], (36.2048, 138.2529), (122.9, 20.4, 154.0, 45.6)),
+    (*b"KE", "🇰🇪", Africa, ["Kenya", "Kenia", "肯尼亚"], (-0.0236, 37.9062), (33.9, -4.7, 41.9, 5.5)),
+    (*b"KG", "🇰🇬", Asia, ["Kyrgyzstan", "Kirguistán", "吉尔吉斯斯坦"], (41.2044, 74.7661), (69.2, 39.2, 80.3, 43.3)),
+    (*b"KH", "🇰🇭", Asia, ["Cambodia", "Camboya", "柬埔寨"], (12.5657, 104.991), (102.3, 10.4, 107.6, 14.7)),
+    (*b"KI", "🇰🇮", Oceania, ["Kiribati", "Kiribati", "基里巴斯"], (-3.3704, -168.734), (169.5, -11.5, -150.2, 4.7)),
+    (*b"KM", "🇰🇲", Africa, ["Comoros", "Comoras", "科摩罗"], (-11.875, 43.8722), (43.2, -12.4, 44.6, -11.3)),
+    (*b"KN", "🇰🇳", NorthAmerica, ["Saint Kitts and Nevis", "San Cristóbal y Nieves", "圣基茨和尼维斯"], (17.3578, -62.783), (-62.9, 17.1, -62.5, 17.45)),
+    (*b"KP", "🇰🇵", Asia, ["North Korea", "Corea del Norte", "朝鲜"], (40.3399, 127.5101), (124.2, 37.7, 130.7, 43.0)),
+    (*b"KR", "🇰🇷", Asia, ["South Korea", "Corea del Sur", "韩国"], (35.9078, 127.7669), (124.6, 33.1, 131.9, 38.6)),
+    (*b"KW", "🇰🇼", Asia, ["Kuwait", "Kuwait", "科威特"], (29.3117, 47.4818), (46.5, 28.5, 48.5, 30.1)),
+    (*b"KY", "🇰🇾", NorthAmerica, ["Cayman Islands", "Islas Caimán", "开曼群岛"], (19.5135, -80.567), (-81.5, 19.2, -79.7, 19.8)),
+    (*b"KZ", "🇰🇿", Asia, ["Kazakhstan", "Kazajistán", "哈萨克斯坦"], (48.0196, 66.9237), (46.5, 40.6, 87.4, 55.4)),
+    (*b"LA", "🇱🇦", Asia, ["Laos", "Laos", "老挝"], (19.8563, 102.4955), (100.1, 13.9, 107.7, 22.5)),
+    (*b"LB", "🇱🇧", Asia, ["Lebanon", "Líbano", "黎巴嫩"], (33.8547, 35.8623), (35.1, 33.05, 36.6, 34.7)),
+    (*b"LC", "🇱🇨", NorthAmerica, ["Saint Lucia", "Santa Lucía", "圣卢西亚"], (13.9094, -60.9789), (-61.1, 13.7, -60.85, 14.1)),
+    (*b"LI", "🇱🇮", Europe, ["Liechtenstein", "Liechtenstein", "列支敦士登"], (47.166, 9.5554), (9.47, 47.05, 9.64, 47.27)),
+    (*b"LK", "🇱🇰", Asia, ["Sri Lanka", "Sri Lanka", "斯里兰卡"], (7.8731, 80.7718), (79.5, 5.9, 81.9, 9.9)),
+    (*b"LR", "🇱🇷", Africa, ["Liberia", "Liberia", "利比里亚"], (6.4281, -9.4295), (-11.5, 4.3, -7.4, 8.6)),
+    (*b"LS", "🇱🇸", Africa, ["Lesotho", "Lesoto", "莱索托"], (-29.61, 28.2336), (27.0, -30.7, 29.5, -28.6)),
+    (*b"LT", "🇱🇹", Europe, ["Lithuania", "Lituania", "立陶宛"], (55.1694, 23.8813), (20.9, 53.9, 26.9, 56.5)),
+    (*b"LU", "🇱🇺", Europe, ["Luxembourg", "Luxemburgo", "卢森堡"], (49.8153, 6.1296), (5.7, 49.4, 6.55, 50.2)),
+    (*b"LV", "🇱🇻", Europe, ["Latvia", "Letonia", "拉脱维亚"], (56.8796, 24.6032), (20.9, 55.7, 28.3, 58.1)),
+    (*b"LY", "🇱🇾", Africa, ["Libya", "Libia", "利比亚"], (26.3351, 17.2283), (9.3, 19.5, 25.2, 33.2)),
+    (*b"MA", "🇲🇦", Africa, ["Morocco", "Marruecos", "摩洛哥"], (31.7917, -7.0926), (-13.2, 27.6, -1.0, 35.95)),
+    (*b"MC", "🇲🇨", Europe, ["Monaco", "Mónaco", "摩纳哥"], (43.7503, 7.4128), (7.4, 43.72, 7.44, 43.76)),
+    (*b"MD", "🇲🇩", Europe, ["Moldova", "Moldavia", "摩尔多瓦"], (47.4116, 28.3699), (26.6, 45.4, 30.2, 48.5)),
+    (*b"ME", "🇲🇪", Europe, ["Montenegro", "Montenegro", "黑山"], (42.7087, 19.3744), (18.4, 41.8, 20.4, 43.6)),
+    (*b"MF", "🇲🇫", NorthAmerica, ["Saint Martin", "San Martín", "法属圣马丁"], (18.0826, -63.0523), (-63.2, 18.0, -62.95, 18.13)),
+    (*b"MG", "🇲🇬", Africa, ["Madagascar", "Madagascar", "马达加斯加"], (-18.7669, 46.8691), (43.2, -25.7, 50.5, -11.9)),
+    (*b"MH", "🇲🇭", Oceania, ["Marshall Islands", "Islas Marshall", "马绍尔群岛"], (7.1315, 171.1845), (160.8, 4.5, 172.2, 14.7)),
+    (*b"MK", "🇲🇰", Europe, ["North Macedonia", "Macedonia del Norte", "北马其顿"], (41.6086, 21.7453), (20.4, 40.8, 23.1, 42.4)),
+    (*b"ML", "🇲🇱", Africa, ["Mali", "Malí", "马里"], (17.5707, -3.9962), (-12.3, 10.1, 4.3, 25.0)),
+    (*b"MM", "🇲🇲", Asia, ["Myanmar", "Myanmar", "缅甸"], (21.914, 95.9562), (92.1, 9.6, 101.2, 28.6)),
+    (*b"MN", "🇲🇳", Asia, ["Mongolia", "Mongolia", "蒙古"], (46.8625, 103.8467), (87.7, 41.5, 119.95, 52.2)),
+    (*b"MO", "🇲🇴", Asia, ["Macao", "Macao", "澳门"], (22.1987, 113.5439), (113.5, 22.1, 113.65, 22.22)),
+    (*b"MP", "🇲🇵", Oceania, ["Northern Mariana Islands", "Islas Marianas del Norte", "北马里亚纳群岛"], (17.3308, 145.3847), (144.8, 14.1, 146.1, 20.6)),
+    (*b"MQ", "🇲🇶", NorthAmerica, ["Martinique", "Martinica", "马提尼克"], (14.6415, -61.0242), (-61.25, 14.35, -60.8, 14.9)),
+    (*b"MR", "🇲🇷", Africa, ["Mauritania", "Mauritania", "毛里塔尼亚"], (21.0079, -10.9408), (-17.1, 14.7, -4.8, 27.3)),
+    (*b"MS", "🇲🇸", NorthAmerica, ["Montserrat", "Montserrat", "蒙特塞拉特"], (16.7425, -62.1874), (-62.25, 16.65, -62.1, 16.85)),
+    (*b"MT", "🇲🇹", Europe, ["Malta", "Malta", "马耳他"], (35.9375, 14.3754), (14.15, 35.8, 14.6, 36.1)),
+    (*b"MU", "🇲🇺", Africa, ["Mauritius", "Mauricio", "毛里求斯"], (-20.3484, 57.5522), (56.5, -20.6, 63.6, -10.3)),
+    (*b"MV", "🇲🇻", Asia, ["Maldives", "Maldivas", "马尔代夫"], (3.2028, 73.2207), (72.6, -0.7, 73.8, 7.1)),
+    (*b"MW", "🇲🇼", Africa, ["Malawi", "Malaui", "马拉维"], (-13.2543, 34.3015), (32.7, -17.2, 35.95, -9.4)),
+    (*b"MX", "🇲🇽", NorthAmerica, ["Mexico", "México", "墨西哥"], (23.6345, -102.5528), (-118.5, 14.5, -86.7, 32.7)),
+    (*b"MY", "🇲🇾", Asia, ["Malaysia", "Malasia", "马来西亚"], (4.2105, 101.9758), (99.6, 0.85, 119.3, 7.4)),
+    (*b"MZ", "🇲🇿", Africa, ["Mozambique", "Mozambique", "莫桑比克"], (-18.6657, 35.5296), (30.2, -26.9, 40.9, -10.4)),
+    (*b"NA", "🇳🇦", Africa, ["Namibia", "Namibia", "纳米比亚"], (-22.9576, 18.4904), (11.7, -29.0, 25.3, -16.95)),
+    (*b"NC", "🇳🇨", Oceania, ["New Caledonia", "Nueva Caledonia", "新喀里多尼亚"], (-20.9043, 165.618), (158.2, -22.9, 172.1, -17.9)),
+    (*b"NE", "🇳🇪", Africa, ["Niger", "Níger", "尼日尔"], (17.6078, 8.0817), (0.2, 11.7, 16.0, 23.5)),
+    (*b"NF", "🇳🇫", Oceania, ["Norfolk Island", "Isla Norfolk", "诺福克岛"], (-29.0408, 167.9547), (167.8, -29.2, 168.1, -28.9)),
+    (*b"NG", "🇳🇬", Africa, ["Nigeria", "Nigeria", "尼日利亚"], (9.082, 8.6753), (2.7, 4.2, 14.7, 13.9)),
+    (*b"NI", "🇳🇮", NorthAmerica, ["Nicaragua", "Nicaragua", "尼加拉瓜"], (12.8654, -85.2072), (-87.7, 10.7, -82.6, 15.05)),
+    (*b"NL", "🇳🇱", Europe, ["Netherlands", "Países Bajos", "荷兰"], (52.1326, 5.2913), (3.3, 50.75, 7.25, 53.6)),
+    (*b"NO", "🇳🇴", Europe, ["Norway", "Noruega", "挪威"], (60.472, 8.4689), (4.5, 57.9, 31.2, 71.2)),
+    (*b"NP", "🇳🇵", Asia, ["Nepal", "Nepal", "尼泊尔"], (28.3949, 84.124), (80.0, 26.3, 88.2, 30.45)),
+    (*b"NR", "🇳🇷", Oceania, ["Nauru", "Nauru", "瑙鲁"], (-0.5228, 166.9315), (166.89, -0.56, 166.96, -0.5)),
+    (*b"NU", "🇳🇺", Oceania, ["Niue", "Niue", "纽埃"], (-19.0544, -169.8672), (-169.99, -19.16, -169.77, -18.95)),
+    (*b"NZ", "🇳🇿", Oceania, ["New Zealand", "Nueva Zelanda", "新西兰"], (-40.9006, 174.886), (166.4, -52.7, -176.2, -29.2)),
+    (*b"OM", "🇴🇲", Asia, ["Oman", "Omán", "阿曼"], (21.5126, 55.9233), (51.9, 16.6, 59.9, 26.4)),
+    (*b"PA", "🇵🇦", NorthAmerica, ["Panama", "Panamá", "巴拿马"], (8.538, -80.7821), (-83.05, 7.2, -77.15, 9.65)),
+    (*b"PE", "🇵🇪", SouthAmerica, ["Peru", "Perú", "秘鲁"], (-9.19, -75.0152), (-81.4, -18.4, -68.65, 0.0)),
+    (*b"PF", "🇵🇫", Oceania, ["French Polynesia", "Polinesia Francesa", "法属波利尼西亚"], (-17.6797, -149.4068), (-154.7, -27.7, -134.9, -7.9)),
+    (*b"PG", "🇵🇬", Oceania, ["Papua New Guinea", "Papúa Nueva Guinea", "巴布亚新几内亚"], (-6.315, 143.9555), (140.8, -11.7, 159.5, -0.8)),
+    (*b"PH", "🇵🇭", Asia, ["Philippines", "Filipinas", "菲律宾"], (12.8797, 121.774), (116.9, 4.6, 126.7, 21.1)),
+    (*b"PK", "🇵🇰", Asia, ["Pakistan", "Pakistán", "巴基斯坦"], (30.3753, 69.3451), (60.9, 23.7, 77.8, 37.1)),
+    (*b"PL", "🇵🇱", Europe, ["Poland", "Polonia", "波兰"], (51.9194, 19.1451), (14.1, 49.0, 24.2, 54.9)),
+    (*b"PM", "🇵🇲", NorthAmerica, ["Saint Pierre and Miquelon", "San Pedro y Miquelón", "圣皮埃尔和密克隆"], (46.9419, -56.2711), (-56.45, 46.75, -56.1, 47.15)),
+    (*b"PN", "🇵🇳", Oceania, ["Pitcairn Islands", "Islas Pitcairn", "皮特凯恩群岛"], (-24.7036, -127.4393), (-130.8, -25.1, -124.75, -23.9)),
+    (*b"PR", "🇵🇷", NorthAmerica, ["Puerto Rico", "Puerto Rico", "波多黎各"], (18.2208, -66.5901), (-68.0, 17.85, -65.2, 18.55)),
+    (*b"PS", "🇵🇸", Asia, ["Palestine", "Palestina", "巴勒斯坦"], (31.9522, 35.2332), (34.2, 31.2, 35.6, 32.6)),
+    (*b"PT", "🇵🇹", Europe, ["Portugal", "Portugal", "葡萄牙"], (39.3999, -8.2245), (-31.3, 30.0, -6.2, 42.2)),
+    (*b"PW", "🇵🇼", Oceania, ["Palau", "Palaos", "帕劳"], (7.515, 134.5825), (131.1, 2.9, 134.75, 8.1)),
+    (*b"PY", "🇵🇾", SouthAmerica, ["Paraguay", "Paraguay", "巴拉圭"], (-23.4425, -58.4438), (-62.65, -27.6, -54.25, -19.3)),
+    (*b"QA", "🇶🇦", Asia, ["Qatar", "Catar", "卡塔尔"], (25.3548, 51.1839), (50.75, 24.45, 51.65, 26.2)),
+    (*b"RE", "🇷🇪", Africa, ["Réunion", "Reunión", "留尼汪"], (-21.1151, 55.5364), (55.2, -21.4, 55.85, -20.85)),
+    (*b"RO", "🇷🇴", Europe, ["Romania", "Rumania", "罗马尼亚"], (45.9432, 24.9668), (20.2, 43.6, 29.75, 48.3)),
+    (*b"RS", "🇷🇸", Europe, ["Serbia", "Serbia", "塞尔维亚"], (44.0165, 21.0059), (18.8, 42.2, 23.0, 46.2)),
+    (*b"RU", "🇷🇺", Europe, ["Russia", "Rusia", "俄罗斯"], (61.524, 105.3188), (19.6, 41.2, -169.0, 81.9)),
+    (*b"RW", "🇷🇼", Africa, ["Rwanda", "Ruanda", "卢旺达"], (-1.9403, 29.8739), (28.85, -2.85, 30.9, -1.05)),
+    (*b"SA", "🇸🇦", Asia, ["Saudi Arabia", "Arabia Saudí", "沙特阿拉伯"], (23.8859, 45.0792), (34.5, 16.35, 55.7, 32.2)),
+    (*b"SB", "🇸🇧", Oceania, ["Solomon Islands", "Islas Salomón", "所罗门群岛"], (-9.6457, 160.1562), (155.5, -12.3, 170.2, -5.0)),
+    (*b"SC", "🇸🇨", Africa, ["Seychelles", "Seychelles", "塞舌尔"], (-4.6796, 55.492), (46.2, -10.25, 56.3, -3.7)),
+    (*b"SD", "🇸🇩", Africa, ["Sudan", "Sudán", "苏丹"], (12.8628, 30.2176), (21.8, 8.7, 38.6, 23.2)),
+    (*b"SE", "🇸🇪", Europe, ["Sweden", "Suecia", "瑞典"], (60.1282, 18.6435), (10.95, 55.3, 24.2, 69.1)),
+    (*b"SG", "🇸🇬", Asia, ["Singapore", "Singapur", "新加坡"], (1.3521, 103.8198), (103.6, 1.15, 104.1, 1.48)),
+    (*b"SH", "🇸🇭", Africa, ["Saint Helena, Ascension and Tristan da Cunha", "Santa Elena, Ascensión y Tristán de Acuña", "圣赫勒拿、阿森松和特里斯坦-达库尼亚"], (-24.1435, -10.0307), (-14.5, -40.4, -5.6, -7.9)),
+    (*b"SI", "🇸🇮", Europe, ["Slovenia", "Eslovenia", "斯洛文尼亚"], (46.1512, 14.9955), (13.35, 45.4, 16.6, 46.9)),
+    (*b"SJ", "🇸🇯", Europe, ["Svalbard and Jan Mayen", "Svalbard y Jan Mayen", "斯瓦尔巴和扬马延"], (77.5536, 23.6703), (-9.1, 70.8, 33.6, 80.9)),
+    (*b"SK", "🇸🇰", Europe, ["Slovakia", "Eslovaquia", "斯洛伐克"], (48.669, 19.699), (16.8, 47.7, 22.6, 49.6)),
+    (*b"SL", "🇸🇱", Africa, ["Sierra Leone", "Sierra Leona", "塞拉利昂"], (8.4606, -11.7799), (-13.35, 6.9, -10.25, 10.0)),
+    (*b"SM", "🇸🇲", Europe, ["San Marino", "San Marino", "圣马力诺"], (43.9424, 12.4578), (12.4, 43.89, 12.52, 43.99)),
+    (*b"SN", "🇸🇳", Africa, ["Senegal", "Senegal", "塞内加尔"], (14.4974, -14.4524), (-17.55, 12.3, -11.35, 16.7)),
+    (*b"SO", "🇸🇴", Africa, ["Somalia", "Somalia", "索马里"], (5.1521, 46.1996), (40.95, -1.7, 51.45, 12.0)),
+    (*b"SR", "🇸🇷", SouthAmerica, ["Suriname", "Surinam", "苏里南"], (3.9193, -56.0278), (-58.1, 1.8, -53.95, 6.05)),
+    (*b"SS", "🇸🇸", Africa, ["South Sudan", "Sudán del Sur", "南苏丹"], (6.877, 31.307), (23.4, 3.45, 36.0, 12.25)),
+    (*b"ST", "🇸🇹", Africa, ["São Tomé and Príncipe", "Santo Tomé y Príncipe", "圣多美和普林西比"], (0.1864, 6.6131), (6.45, -0.05, 7.5, 1.75)),
+    (*b"SV", "🇸🇻", NorthAmerica, ["El Salvador", "El Salvador", "萨尔瓦多"], (13.7942, -88.8965), (-90.15, 13.1, -87.65, 14.45)),
+    (*b"SX", "🇸🇽", NorthAmerica, ["Sint Maarten", "Sint Maarten", "荷属圣马丁"], (18.0425, -63.0548), (-63.15, 18.0, -63.0, 18.07)),
+    (*b"SY", "🇸🇾", Asia, ["Syria", "Siria", "叙利亚"], (34.8021, 38.9968), (35.7, 32.3, 42.4, 37.35)),
+    (*b"SZ", "🇸🇿", Africa, ["Eswatini", "Esuatini", "斯威士兰"], (-26.5225, 31.4659), (30.75, -27.35, 32.15, -25.7)),
+    (*b"TC", "🇹🇨", NorthAmerica, ["Turks and Caicos Islands", "Islas Turcas y Caicos", "特克斯和凯科斯群岛"], (21.694, -71.7979), (-72.5, 21.2, -71.1, 21.98)),
+    (*b"TD", "🇹🇩", Africa, ["Chad", "Chad", "乍得"], (15.4542, 18.7322), (13.45, 7.4, 24.0, 23.45)),
+    (*b"TF", "🇹🇫", Antarctica, ["French Southern Territories", "Territorios Australes Franceses", "法属南部领地"], (-49.2804, 69.3486), (39.6, -50.1, 77.6, -11.5)),
+    (*b"TG", "🇹🇬", Africa, ["Togo", "Togo", "多哥"], (8.6195, 0.8248), (-0.15, 6.1, 1.8, 11.15)),
+    (*b"TH", "🇹🇭", Asia, ["Thailand", "Tailandia", "泰国"], (15.87, 100.9925), (97.35, 5.6, 105.65, 20.5)),
+    (*b"TJ", "🇹🇯", Asia, ["Tajikistan", "Tayikistán", "塔吉克斯坦"], (38.861, 71.2761), (67.35, 36.65, 75.15, 41.05)),
+    (*b"TK", "🇹🇰", Oceania, ["Tokelau", "Tokelau", "托克劳"], (-8.9674, -171.8559), (-172.55, -9.45, -171.15, -8.5)),
+    (*b"TL", "🇹🇱", Oceania, ["Timor-Leste", "Timor Oriental", "东帝汶"], (-8.8742, 125.7275), (124.0, -9.5, 127.35, -8.1)),
+    (*b"TM", "🇹🇲", Asia, ["Turkmenistan", "Turkmenistán", "土库曼斯坦"], (38.9697, 59.5563), (52.45, 35.1, 66.7, 42.8)),
+    (*b"TN", "🇹🇳", Africa, ["Tunisia", "Túnez", "突尼斯"], (33.8869, 9.5375), (7.5, 30.2, 11.6, 37.55)),
+    (*b"TO", "🇹🇴", Oceania, ["Tonga", "Tonga", "汤加"], (-21.179, -175.1982), (-176.25, -22.4, -173.7, -15.55)),
+    (*b"TR", "🇹🇷", Asia, ["Türkiye", "Turquía", "土耳其"], (38.9637, 35.2433), (25.65, 35.8, 44.8, 42.1)),
+    (*b"TT", "🇹🇹", NorthAmerica, ["Trinidad and Tobago", "Trinidad y Tobago", "特立尼达和多巴哥"], (10.6918, -61.2225), (-61.95, 10.0, -60.5, 11.4)),
+    (*b"TV", "🇹🇻", Oceania, ["Tuvalu", "Tuvalu", "图瓦卢"], (-7.1095, 177.6493), (176.05, -10.8, 179.9, -5.6)),
+    (*b"TW", "🇹🇼", Asia, ["Taiwan", "Taiwán", "台湾"], (23.6978, 120.9605), (116.7, 20.6, 122.0, 26.4)),
+    (*b"TZ", "🇹🇿", Africa, ["Tanzania", "Tanzania", "坦桑尼亚"], (-6.369, 34.8888), (29.3, -11.75, 40.45, -0.95)),
+    (*b"UA", "🇺🇦", Europe, ["Ukraine", "Ucrania", "乌克兰"], (48.3794, 31.1656), (22.1, 44.35, 40.25, 52.4)),
+    (*b"UG", "🇺🇬", Africa, ["Uganda", "Uganda", "乌干达"], (1.3733, 32.2903), (29.55, -1.5, 35.05, 4.25)),
+    (*b"UM", "🇺🇲", Oceania, ["United States Minor Outlying Islands", "Islas Ultramarinas Menores de los Estados Unidos", "美国本土外小岛屿"], (19.2823, 166.647), (-180.0, -0.5, 180.0, 28.5)),
+    (*b"US", "🇺🇸", NorthAmerica, ["United States", "Estados Unidos", "美国"], (37.0902, -95.7129), (172.4, 18.9, -66.9, 71.4)),
+    (*b"UY", "🇺🇾", SouthAmerica, ["Uruguay", "Uruguay", "乌拉圭"], (-32.5228, -55.7658), (-58.45, -35.0, -53.05, -30.05)),
+    (*b"UZ", "🇺🇿", Asia, ["Uzbekistan", "Uzbekistán", "乌兹别克斯坦"], (41.3775, 64.5853), (55.95, 37.15, 73.15, 45.6)),
+    (*b"VA", "🇻🇦", Europe, ["Vatican City", "Ciudad del Vaticano", "梵蒂冈"], (41.9029, 12.4534), (12.44, 41.9, 12.46, 41.91)),
+    (*b"VC", "🇻🇨", NorthAmerica, ["Saint Vincent and the Grenadines", "San Vicente y las Granadinas", "圣文森特和格林纳丁斯"], (12.9843, -61.2872), (-61.5, 12.55, -61.1, 13.4)),
+    (*b"VE", "🇻🇪", SouthAmerica, ["Venezuela", "Venezuela", "委内瑞拉"], (6.4238, -66.5897), (-73.4, 0.6, -59.8, 15.7)),
+    (*b"VG", "🇻🇬", NorthAmerica, ["British Virgin Islands", "Islas Vírgenes Británicas", "英属维尔京群岛"], (18.4207, -64.64), (-64.85, 18.3, -64.25, 18.75)),
+    (*b"VI", "🇻🇮", NorthAmerica, ["U.S. Virgin Islands", "Islas Vírgenes de los Estados Unidos", "美属维尔京群岛"], (18.3358, -64.8963), (-65.1, 17.65, -64.55, 18.4)),
+    (*b"VN", "🇻🇳", Asia, ["Vietnam", "Vietnam", "越南"], (14.0583, 108.2772), (102.1, 8.4, 109.5, 23.4)),
+    (*b"VU", "🇻🇺", Oceania, ["Vanuatu", "Vanuatu", "瓦努阿图"], (-15.3767, 166.9592), (166.5, -20.25, 170.25, -13.05)),
+    (*b"WF", "🇼🇫", Oceania, ["Wallis and Futuna", "Wallis y Futuna", "瓦利斯和富图纳"], (-13.7688, -177.1561), (-178.2, -14.4, -176.1, -13.2)),
+    (*b"WS", "🇼🇸", Oceania, ["Samoa", "Samoa", "萨摩亚"], (-13.759, -172.1046), (-172.8, -14.1, -171.4, -13.4)),
+    (*b"XK", "🇽🇰", Europe, ["Kosovo", "Kosovo", "科索沃"], (42.6026, 20.903), (20.0, 41.85, 21.8, 43.3)),
+    (*b"YE", "🇾🇪", Asia, ["Yemen", "Yemen", "也门"], (15.5527, 48.5164), (41.8, 12.1, 54.55, 19.0)),
+    (*b"YT", "🇾🇹", Africa, ["Mayotte", "Mayotte", "马约特"], (-12.8275, 45.1662), (45.0, -13.0, 45.3, -12.6)),
+    (*b"ZA", "🇿🇦", Africa, ["South Africa", "Sudáfrica", "南非"], (-30.5595, 22.9375), (16.45, -47.0, 38.0, -22.1)),
+    (*b"ZM", "🇿🇲", Africa, ["Zambia", "Zambia", "赞比亚"], (-13.1339, 27.8493), (21.95, -18.1, 33.7, -8.2)),
+    (*b"ZW", "🇿🇼", Africa, ["Zimbabwe", "Zimbabue", "津巴布韦"], (-19.0154, 29.1549), (25.2, -22.45, 33.1, -15.6)),
+];
+
+impl CountryCode {
+    fn uppercase(&self) -> [u8; 2] {
+        self.0.to_ne_bytes().map(|b| b.to_ascii_uppercase())
+    }
+
+    fn entry(&self) -> Option<&'static CountryEntry> {
+        let code = self.uppercase();
+
+        COUNTRIES
+            .binary_search_by_key(&code, |(c, ..)| *c)
+            .ok()
+            .map(|i| &COUNTRIES[i])
+    }
+
+    /// Returns true if the code is an assigned ISO 3166-1 country, in either case.
+    pub fn is_valid(&self) -> bool {
+        self.entry().is_some()
+    }
+
+    /// The country's name, if the code is a known ISO 3166-1 country.
+    pub fn name(&self, lang: Language) -> Option<&'static str> {
+        self.entry().map(|(_, _, _, [en, es, zh], ..)| match lang {
+            Language::En => *en,
+            Language::Es => *es,
+            Language::Zh => *zh,
+        })
+    }
+
+    pub fn continent(&self) -> Option<Continent> {
+        self.entry().map(|(_, _, continent, ..)| *continent)
+    }
+
+    /// The country's flag emoji, e.g. 🇩🇪 for `DE`.
+    pub fn flag(&self) -> Option<&'static str> {
+        self.entry().map(|(_, flag, ..)| *flag)
+    }
+
+    /// The country's names, continent and flag, if the code is a known ISO 3166-1 country.
+    ///
+    /// Borrows from the built-in table, so it's cheap enough to resolve for every lookup.
+    pub fn country(&self) -> Option<Country> {
+        let (_, flag, continent, [en, es, zh], ..) = self.entry()?;
+
+        Some(Country {
+            names: CountryNames {
+                en: Cow::Borrowed(en),
+                es: Cow::Borrowed(es),
+                zh: Cow::Borrowed(zh),
+            },
+            continent: *continent,
+            flag: Cow::Borrowed(flag),
+        })
+    }
+
+    /// The approximate geographic center of the country, if the code is a known ISO 3166-1 country.
+    pub fn centroid(&self) -> Option<Coordinate> {
+        self.entry().map(|(_, _, _, _, (lat, lng), _)| Coordinate {
+            lat: *lat,
+            lng: *lng,
+        })
+    }
+
+    /// Returns whether a coordinate is within the country's bounding box, if the code is a known ISO 3166-1 country.
+    ///
+    /// The boxes are approximate, so this only tells apart coordinates that are clearly elsewhere.
+    pub fn contains(&self, crd: Coordinate) -> Option<bool> {
+        let (.., (west, south, east, north)) = *self.entry()?;
+
+        let west = west - BOUNDS_MARGIN_DEG;
+        let width = match east < west {
+            true => east + 360.0 + BOUNDS_MARGIN_DEG - west,
+            false => east + BOUNDS_MARGIN_DEG - west,
+        };
+
+        let lat = (south - BOUNDS_MARGIN_DEG..=north + BOUNDS_MARGIN_DEG).contains(&crd.lat);
+        // degrees east of the west edge, which wraps around the antimeridian
+        let lng = width >= 360.0 || (crd.lng - west).rem_euclid(360.0) <= width;
+
+        Some(lat && lng)
+    }
+
+    /// Returns true if the code is made of two ASCII letters, whether or not it's assigned.
+    pub(crate) fn is_well_formed(&self) -> bool {
+        self.0.to_ne_bytes().iter().all(u8::is_ascii_alphabetic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn countries_sorted() {
+        assert!(COUNTRIES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn centroid() {
+        let de = CountryCode::from("DE").centroid().unwrap();
+        assert!((de.lat - 51.17).abs() < 0.01 && (de.lng - 10.45).abs() < 0.01);
+
+        assert_eq!(CountryCode::from("de").centroid(), Some(de));
+        assert_eq!(CountryCode::from("ZZ").centroid(), None);
+        assert!(!CountryCode::from("13").is_well_formed());
+    }
+
+    #[test]
+    fn bounds() {
+        for (code, _, _, _, (lat, lng), _) in COUNTRIES {
+            assert_eq!(
+                CountryCode::from(code).contains(Coordinate {
+                    lat: *lat,
+                    lng: *lng
+                }),
+                Some(true),
+                "{code:?}"
+            );
+        }
+
+        let crd = |lat, lng| Coordinate { lat, lng };
+        let de = CountryCode::from("DE");
+        assert_eq!(de.contains(crd(52.52, 13.405)), Some(true));
+        assert_eq!(de.contains(crd(48.857, 2.352)), Some(false));
+
+        // boxes crossing the antimeridian, the Aleutians and Fiji's Lau islands
+        let us = CountryCode::from("US");
+        assert_eq!(us.contains(crd(52.9, 172.9)), Some(true));
+        assert_eq!(us.contains(crd(35.69, 139.69)), Some(false));
+        assert_eq!(
+            CountryCode::from("FJ").contains(crd(-17.5, -178.8)),
+            Some(true)
+        );
+        assert_eq!(
+            CountryCode::from("FJ").contains(crd(-17.5, 170.0)),
+            Some(false)
+        );
+
+        assert_eq!(
+            CountryCode::from("AQ").contains(crd(-80.0, 10.0)),
+            Some(true)
+        );
+        assert_eq!(CountryCode::from("ZZ").contains(crd(0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn countries() {
+        for (code, flag, ..) in COUNTRIES {
+            // regional indicator symbols A to Z are contiguous, like ASCII letters
+            let indicators = code
+                .iter()
+                .filter_map(|b| char::from_u32(0x1F1E6 + (b - b'A') as u32));
+            assert!(flag.chars().eq(indicators), "{code:?}");
+        }
+
+        let de = CountryCode::from("de");
+        assert!(de.is_valid());
+        assert_eq!(de.name(Language::Es), Some("Alemania"));
+        assert_eq!(de.continent(), Some(Continent::Europe));
+        assert_eq!(de.flag(), Some("🇩🇪"));
+
+        let country = Country::from_code("CN").unwrap();
+        assert_eq!(country.name(Language::Zh), "中国");
+        assert_eq!(country.continent.name(Language::En), "Asia");
+
+        for garbage in ["ZZ", "13", "\u{1}\u{2}", ""] {
+            assert!(!CountryCode::from(garbage).is_valid());
+            assert_eq!(Country::from_code(garbage), None);
+        }
+    }
+}
@@ -1,15 +1,15 @@
 use std::{
-    io::Read,
+    io::{Read, Write},
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
 };
 
 use treebitmap::IpLookupTable;
 
 use crate::{
-    Coordinate, Database, Error, GenericIp,
+    Coordinate, CsvSchema, Database, Error, GenericIp, ImportReport, Network,
     coordinate::PackedCoordinate,
     locations::{Location, LocationStore},
-    reader,
+    reader, translate, writer,
 };
 
 /// A database that stores IPv4 addresses.
@@ -34,27 +34,95 @@ pub struct SingleDatabase<Ip: GenericIp> {
 
 impl<Ip: GenericIp> SingleDatabase<Ip> {
     pub fn from_csv(read: impl Read, is_num: bool) -> Result<Self, Error> {
+        Self::from_csv_with_schema(read, is_num, &CsvSchema::SAPICS)
+    }
+
+    /// Like [`from_csv`](Self::from_csv), but skips malformed records and reports them instead of failing.
+    pub fn from_csv_lenient(read: impl Read, is_num: bool) -> Result<(Self, ImportReport), Error> {
+        Self::from_csv_lenient_with_schema(read, is_num, &CsvSchema::SAPICS)
+    }
+
+    /// Read a CSV file with its columns laid out in a different [`CsvSchema`].
+    pub fn from_csv_with_schema(
+        read: impl Read,
+        is_num: bool,
+        schema: &CsvSchema,
+    ) -> Result<Self, Error> {
         let mut ips = IpLookupTable::new();
         let mut locations = LocationStore::default();
 
-        reader::csv::read(read, is_num, &mut ips, &mut locations)?;
+        reader::csv::read(read, is_num, schema, None, &mut ips, &mut locations)?;
 
         Ok(Self { ips, locations })
     }
 
+    /// Combines [`from_csv_lenient`](Self::from_csv_lenient) and [`from_csv_with_schema`](Self::from_csv_with_schema).
+    pub fn from_csv_lenient_with_schema(
+        read: impl Read,
+        is_num: bool,
+        schema: &CsvSchema,
+    ) -> Result<(Self, ImportReport), Error> {
+        let mut ips = IpLookupTable::new();
+        let mut locations = LocationStore::default();
+        let mut report = ImportReport::default();
+
+        reader::csv::read(
+            read,
+            is_num,
+            schema,
+            Some(&mut report),
+            &mut ips,
+            &mut locations,
+        )?;
+
+        Ok((Self { ips, locations }, report))
+    }
+
     pub fn from_mmdb<S: AsRef<[u8]>>(reader: maxminddb::Reader<S>) -> Result<Self, Error> {
+        Self::from_mmdb_with_language(reader, reader::mmdb::DEFAULT_LANGUAGE)
+    }
+
+    /// Read a MaxMind DB, taking place names from the given language (e.g. `"de"`, `"zh-CN"`)
+    /// in GeoIP2/GeoLite2 databases, and falling back to English when it's missing.
+    pub fn from_mmdb_with_language<S: AsRef<[u8]>>(
+        reader: maxminddb::Reader<S>,
+        language: &str,
+    ) -> Result<Self, Error> {
         let mut ips = IpLookupTable::new();
         let mut locations = LocationStore::default();
 
-        reader::mmdb::read(reader, &mut ips, &mut locations)?;
+        reader::mmdb::read(&reader, language, &mut ips, &mut locations)?;
 
         Ok(Self { ips, locations })
     }
+
+    /// Write the database as a MaxMind DB file that can be read back with [`detect`](crate::detect).
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        writer::mmdb::write(
+            w,
+            Ip::IP_VERSION,
+            self.ips.iter().map(|(ip, len, c)| (ip.into(), len, *c)),
+            |c| self.locations.get(c),
+        )
+    }
+
+    /// Write the database as a sapics city CSV file, with string or numeric ([`is_num`](Self::from_csv)) IPs.
+    pub fn write_csv(&self, w: impl Write, is_num: bool, gzip: bool) -> Result<(), Error> {
+        writer::csv::write(
+            w,
+            is_num,
+            gzip,
+            self.ips.iter().map(|(ip, len, c)| (ip, len, *c)),
+            |c| self.locations.get(c),
+        )
+    }
 }
 
 impl<Ip: GenericIp> Database<Ip> for SingleDatabase<Ip> {
-    fn get_coordinate(&self, ip: Ip) -> Option<Coordinate> {
-        self.ips.longest_match(ip).map(|(_, _, c)| c.into())
+    fn get_network(&self, ip: Ip) -> Option<(Network, Coordinate)> {
+        self.ips
+            .longest_match(ip)
+            .map(|(ip, len, c)| (Network::from((ip, len)), c.into()))
     }
 
     fn get_location(&self, crd: Coordinate) -> Option<Location> {
@@ -76,8 +144,54 @@ impl CombinedDatabase {
         let mut ipv6 = IpLookupTable::new();
         let mut locations = LocationStore::default();
 
-        reader::csv::read(ipv4_csv, is_num, &mut ipv4, &mut locations)?;
-        reader::csv::read(ipv6_csv, is_num, &mut ipv6, &mut locations)?;
+        reader::csv::read(
+            ipv4_csv,
+            is_num,
+            &CsvSchema::SAPICS,
+            None,
+            &mut ipv4,
+            &mut locations,
+        )?;
+        reader::csv::read(
+            ipv6_csv,
+            is_num,
+            &CsvSchema::SAPICS,
+            None,
+            &mut ipv6,
+            &mut locations,
+        )?;
+
+        Ok(Self {
+            ipv4,
+            ipv6,
+            locations,
+        })
+    }
+
+    /// Read a dual-stack MaxMind DB, pulling the IPv4 networks out of its `::/96` subtree.
+    ///
+    /// IPv4-only databases leave the IPv6 table empty, and IPv6-only ones the IPv4 table.
+    pub fn from_mmdb<S: AsRef<[u8]>>(reader: maxminddb::Reader<S>) -> Result<Self, Error> {
+        Self::from_mmdb_with_language(reader, reader::mmdb::DEFAULT_LANGUAGE)
+    }
+
+    /// Like [`from_mmdb`](Self::from_mmdb), see [`SingleDatabase::from_mmdb_with_language`].
+    pub fn from_mmdb_with_language<S: AsRef<[u8]>>(
+        reader: maxminddb::Reader<S>,
+        language: &str,
+    ) -> Result<Self, Error> {
+        let mut ipv4 = IpLookupTable::new();
+        let mut ipv6 = IpLookupTable::new();
+        let mut locations = LocationStore::default();
+
+        match reader.metadata.ip_version {
+            4 => reader::mmdb::read(&reader, language, &mut ipv4, &mut locations)?,
+            6 => {
+                reader::mmdb::read(&reader, language, &mut ipv4, &mut locations)?;
+                reader::mmdb::read(&reader, language, &mut ipv6, &mut locations)?;
+            }
+            _ => return Err(Error::MalformedMaxMindDb),
+        }
 
         Ok(Self {
             ipv4,
@@ -85,24 +199,74 @@ impl CombinedDatabase {
             locations,
         })
     }
+
+    /// Write the database as an IPv6 MaxMind DB file, with the IPv4 networks stored in `::/96`.
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        writer::mmdb::write(
+            w,
+            Ipv6Addr::IP_VERSION,
+            self.ipv4
+                .iter()
+                .map(|(ip, len, c)| (ip.into(), len, *c))
+                .chain(self.ipv6.iter().map(|(ip, len, c)| (ip.into(), len, *c))),
+            |c| self.locations.get(c),
+        )
+    }
+
+    /// Write the database as a pair of sapics city CSV files, the inverse of [`Self::from_csv`].
+    pub fn write_csv(
+        &self,
+        ipv4_csv: impl Write,
+        ipv6_csv: impl Write,
+        is_num: bool,
+        gzip: bool,
+    ) -> Result<(), Error> {
+        writer::csv::write(
+            ipv4_csv,
+            is_num,
+            gzip,
+            self.ipv4.iter().map(|(ip, len, c)| (ip, len, *c)),
+            |c| self.locations.get(c),
+        )?;
+        writer::csv::write(
+            ipv6_csv,
+            is_num,
+            gzip,
+            self.ipv6.iter().map(|(ip, len, c)| (ip, len, *c)),
+            |c| self.locations.get(c),
+        )
+    }
 }
 
 impl Database<IpAddr> for CombinedDatabase {
-    fn get_coordinate(&self, ip: IpAddr) -> Option<Coordinate> {
+    fn get_network(&self, ip: IpAddr) -> Option<(Network, Coordinate)> {
         match ip {
-            IpAddr::V4(ip) => self.ipv4.longest_match(ip).map(|(_, _, c)| c.into()),
-            IpAddr::V6(ip) => self.ipv6.longest_match(ip).map(|(_, _, c)| c.into()),
+            IpAddr::V4(ip) => self
+                .ipv4
+                .longest_match(ip)
+                .map(|(ip, len, c)| (Network::from((ip, len)), c.into())),
+            IpAddr::V6(ip) => self
+                .ipv6
+                .longest_match(ip)
+                .map(|(ip, len, c)| (Network::from((ip, len)), c.into())),
         }
     }
 
     fn get_location(&self, crd: Coordinate) -> Option<Location> {
         self.locations.get(&(crd.into()))
     }
+
+    fn translate(&self, ip: &IpAddr) -> Option<IpAddr> {
+        translate::translate(ip)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Coordinate, Database, Ipv4Database, Ipv6Database, Location, LookupInfo};
+    use crate::{
+        Coordinate, Country, Database, Ipv4Database, Ipv6Database, Location, LookupInfo, Network,
+        locations::CountryCode, testing::archived,
+    };
     use std::{
         error,
         net::{Ipv4Addr, Ipv6Addr},
@@ -118,18 +282,27 @@ mod tests {
                 city: Some("Guangzhou".to_string()),
                 region: Some("Guangdong".to_string()),
                 country_code: "CN".to_string(),
+                subregion: Some("Tianhe".to_string()),
+                postcode: Some("510000".to_string()),
+                timezone: Some("Asia/Shanghai".to_string()),
+                country: Country::from_code("CN"),
             },
+            country_level: false,
+            translated: false,
         }
     }
 
     fn format_csv_line(lower: String, higher: String, info: &LookupInfo) -> String {
         format!(
-            "{lower},{higher},{},{},,{},,{},{},",
+            "{lower},{higher},{},{},{},{},{},{},{},{}",
             info.loc.country_code.clone(),
             info.loc.region.clone().unwrap_or_default(),
+            info.loc.subregion.clone().unwrap_or_default(),
             info.loc.city.clone().unwrap_or_default(),
+            info.loc.postcode.clone().unwrap_or_default(),
             info.crd.lat,
-            info.crd.lng
+            info.crd.lng,
+            info.loc.timezone.clone().unwrap_or_default(),
         )
     }
 
@@ -222,4 +395,77 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn archived_location() -> Result<(), Box<dyn error::Error>> {
+        let info = info();
+
+        let db = Ipv4Database::from_csv(
+            format_csv_line("1.0.8.0".into(), "1.0.15.255".into(), &info).as_bytes(),
+            false,
+        )?;
+
+        let network = Network::from((Ipv4Addr::new(1, 0, 8, 0), 21));
+        archived(&db, |archived| {
+            let result = archived.get(Ipv4Addr::new(1, 0, 9, 80));
+            assert!(
+                result.as_ref().is_some_and(|r| r.approx_eq(&info)),
+                "Archived DB: expected {info:?}, got {result:?}"
+            );
+
+            // the matched network is the range's prefix
+            assert_eq!(
+                archived
+                    .get_match(Ipv4Addr::new(1, 0, 9, 80))
+                    .map(|m| m.network),
+                Some(network)
+            );
+        })?;
+        assert_eq!(
+            db.get_match(Ipv4Addr::new(1, 0, 9, 80)).map(|m| m.network),
+            Some(network)
+        );
+
+        // files without the trailing timezone column are still accepted
+        let db = Ipv4Database::from_csv(
+            "1.0.8.0,1.0.15.255,CN,Guangdong,,Guangzhou,,23.1317,113.266".as_bytes(),
+            false,
+        )?;
+        let result = db.get(Ipv4Addr::new(1, 0, 9, 80)).map(|r| r.loc);
+        assert_eq!(
+            result,
+            Some(Location {
+                city: Some("Guangzhou".to_string()),
+                region: Some("Guangdong".to_string()),
+                country_code: "CN".to_string(),
+                country: Country::from_code("CN"),
+                ..Default::default()
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn country_level() -> Result<(), Box<dyn error::Error>> {
+        let db = Ipv4Database::from_csv(
+            "1.0.0.0,1.0.0.255,AU\n1.0.1.0,1.0.3.255,ZZ\n1.0.8.0,1.0.15.255,CN\n".as_bytes(),
+            false,
+        )?;
+
+        let result = db.get(Ipv4Addr::new(1, 0, 9, 80)).unwrap();
+        assert!(result.country_level);
+        assert_eq!(result.loc.country_code, "CN");
+        assert!(
+            result
+                .crd
+                .approx_eq(&CountryCode::from("CN").centroid().unwrap())
+        );
+
+        // unassigned codes are skipped, malformed ones are rejected
+        assert_eq!(None, db.get(Ipv4Addr::new(1, 0, 2, 1)));
+        assert!(Ipv4Database::from_csv("1.0.0.0,1.0.0.255,13335".as_bytes(), false).is_err());
+
+        Ok(())
+    }
 }
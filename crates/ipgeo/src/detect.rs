@@ -1,36 +1,234 @@
 use std::{
     fs::File,
-    io::{Read, Seek, SeekFrom},
+    io::{Read, Seek, SeekFrom, Write},
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
     path::Path,
 };
 
-use compact_str::CompactString;
 use flate2::read::GzDecoder;
 
-use crate::{Coordinate, Database, Error, Location, SingleDatabase};
+use treebitmap::IpLookupTable;
+
+use crate::{
+    AsnDatabase, Audit, AuditReport, CombinedDatabase, Coordinate, CsvSchema, Database,
+    DatabaseStats, Error, GenericIp, ImportReport, Location, LocationQuery, LocationSearch,
+    Network, Prefix, Prefixes, ReverseLookup, SearchIndex, SingleDatabase, SpatialIndex,
+    SpatialLookup, Statistics, coordinate::PackedCoordinate, locations::LocationStore, reader,
+    translate,
+};
 
 /// Automatically detect the format of the database and read it.
 ///
-/// Accepts ip-location "city" `*.mmdb`, `*-num.csv` and `*-num.csv.gz` files.
+/// Accepts ip-location "city" `*.mmdb`, `*-num.csv` and `*-num.csv.gz` files,
+/// as well as MaxMind's GeoIP2/GeoLite2 City and Country `*.mmdb` files.
+/// Only the IPv6 networks of a dual-stack `*.mmdb` are read, use [`detect_combined`] for both.
+/// CSV files with a header row have their columns found by name, see [`CsvSchema::from_headers`].
 pub fn detect(path: &Path) -> Result<GenericDatabase, Error> {
-    match DatabaseKind::detect(path)? {
+    detect_with_language(path, reader::mmdb::DEFAULT_LANGUAGE)
+}
+
+/// Like [`detect`], but takes GeoIP2/GeoLite2 place names in the given language.
+pub fn detect_with_language(path: &Path, language: &str) -> Result<GenericDatabase, Error> {
+    read_detected(DatabaseKind::detect(path, None)?, language)
+}
+
+/// Like [`detect`], but reads CSV files with the given [`CsvSchema`] instead of guessing it.
+pub fn detect_with_schema(path: &Path, schema: &CsvSchema) -> Result<GenericDatabase, Error> {
+    read_detected(
+        DatabaseKind::detect(path, Some(*schema))?,
+        reader::mmdb::DEFAULT_LANGUAGE,
+    )
+}
+
+fn read_detected(kind: DatabaseKind, language: &str) -> Result<GenericDatabase, Error> {
+    match kind {
         DatabaseKind::Csv {
             reader,
             is_num,
             is_ipv6,
+            schema,
+            ..
         } => match is_ipv6 {
-            true => SingleDatabase::from_csv(reader, is_num).map(GenericDatabase::Ipv6),
-            false => SingleDatabase::from_csv(reader, is_num).map(GenericDatabase::Ipv4),
-        },
-        DatabaseKind::Maxminddb { reader } => match reader.metadata.ip_version {
-            4 => SingleDatabase::from_mmdb(reader).map(GenericDatabase::Ipv4),
-            6 => SingleDatabase::from_mmdb(reader).map(GenericDatabase::Ipv6),
-            _ => Err(Error::MalformedMaxMindDb),
+            true => SingleDatabase::from_csv_with_schema(reader, is_num, &schema)
+                .map(GenericDatabase::Ipv6),
+            false => SingleDatabase::from_csv_with_schema(reader, is_num, &schema)
+                .map(GenericDatabase::Ipv4),
         },
+        DatabaseKind::Maxminddb { reader } => from_mmdb(reader, language),
+    }
+}
+
+/// Like [`detect`], but skips malformed CSV records and reports them instead of failing.
+///
+/// MaxMind DB files are read strictly, and always have a clean report.
+pub fn detect_lenient(path: &Path) -> Result<(GenericDatabase, ImportReport), Error> {
+    match DatabaseKind::detect(path, None)? {
+        DatabaseKind::Csv {
+            reader,
+            is_num,
+            is_ipv6,
+            schema,
+            ..
+        } => csv_lenient(reader, is_num, is_ipv6, &schema),
+        DatabaseKind::Maxminddb { reader } => from_mmdb(reader, reader::mmdb::DEFAULT_LANGUAGE)
+            .map(|db| (db, ImportReport::default())),
+    }
+}
+
+fn csv_lenient(
+    reader: Box<dyn Read>,
+    is_num: bool,
+    is_ipv6: bool,
+    schema: &CsvSchema,
+) -> Result<(GenericDatabase, ImportReport), Error> {
+    match is_ipv6 {
+        true => SingleDatabase::from_csv_lenient_with_schema(reader, is_num, schema)
+            .map(|(db, report)| (GenericDatabase::Ipv6(db), report)),
+        false => SingleDatabase::from_csv_lenient_with_schema(reader, is_num, schema)
+            .map(|(db, report)| (GenericDatabase::Ipv4(db), report)),
+    }
+}
+
+fn from_mmdb<S: AsRef<[u8]>>(
+    reader: maxminddb::Reader<S>,
+    language: &str,
+) -> Result<GenericDatabase, Error> {
+    match reader.metadata.ip_version {
+        4 => SingleDatabase::from_mmdb_with_language(reader, language).map(GenericDatabase::Ipv4),
+        6 => SingleDatabase::from_mmdb_with_language(reader, language).map(GenericDatabase::Ipv6),
+        _ => Err(Error::MalformedMaxMindDb),
+    }
+}
+
+/// Automatically detect an IPv4 and an IPv6 database and read them into one [`CombinedDatabase`],
+/// sharing their locations.
+///
+/// Each path may be a database file or a directory of them, which is searched (non-recursively)
+/// for files that can be detected. Exactly one IPv4 and one IPv6 database must be found,
+/// or a single dual-stack `*.mmdb` holding both (see [`CombinedDatabase::from_mmdb`]).
+pub fn detect_combined(paths: &[impl AsRef<Path>]) -> Result<CombinedDatabase, Error> {
+    let mut kinds = Vec::new();
+
+    for path in paths.iter().map(AsRef::as_ref) {
+        if !path.is_dir() {
+            kinds.push(DatabaseKind::detect(path, None)?);
+            continue;
+        }
+
+        let mut files = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        files.sort_unstable();
+
+        // anything that isn't a database (READMEs, licenses...) is skipped
+        kinds.extend(
+            files
+                .iter()
+                .filter(|file| file.is_file())
+                .filter_map(|file| DatabaseKind::detect(file, None).ok()),
+        );
+    }
+
+    if let [DatabaseKind::Maxminddb { reader }] = kinds.as_slice()
+        && reader::mmdb::has_ipv4(reader)
+        && let Some(DatabaseKind::Maxminddb { reader }) = kinds.pop()
+    {
+        return CombinedDatabase::from_mmdb(reader);
+    }
+
+    let (ipv6, ipv4): (Vec<_>, Vec<_>) = kinds.into_iter().partition(DatabaseKind::is_ipv6);
+    let (Ok([ipv4]), Ok([ipv6])) = (<[_; 1]>::try_from(ipv4), <[_; 1]>::try_from(ipv6)) else {
+        return Err(Error::NotADatabasePair);
+    };
+
+    let mut db = CombinedDatabase {
+        ipv4: IpLookupTable::new(),
+        ipv6: IpLookupTable::new(),
+        locations: LocationStore::default(),
+    };
+
+    ipv4.read_into(&mut db.ipv4, &mut db.locations)?;
+    ipv6.read_into(&mut db.ipv6, &mut db.locations)?;
+
+    Ok(db)
+}
+
+/// Automatically detect the format of an ASN database and read it.
+///
+/// Accepts ip-location "asn" `*.mmdb`, `*.csv` and `*.csv.gz` files.
+pub fn detect_asn(path: &Path) -> Result<AsnDatabase, Error> {
+    match DatabaseKind::detect(path, None)? {
+        DatabaseKind::Csv {
+            reader,
+            is_num,
+            is_ipv6,
+            ..
+        } => csv_asn(reader, is_num, is_ipv6),
+        DatabaseKind::Maxminddb { reader } => AsnDatabase::from_mmdb(reader),
     }
 }
 
+fn csv_asn(reader: Box<dyn Read>, is_num: bool, is_ipv6: bool) -> Result<AsnDatabase, Error> {
+    let mut db = AsnDatabase::default();
+
+    match is_ipv6 {
+        true => reader::csv::read_asn(reader, is_num, &mut db.ipv6, &mut db.systems)?,
+        false => reader::csv::read_asn(reader, is_num, &mut db.ipv4, &mut db.systems)?,
+    }
+
+    Ok(db)
+}
+
+/// A database of any kind, as read by [`detect_any`].
+#[allow(clippy::large_enum_variant)]
+pub enum DetectedDatabase {
+    Combined(CombinedDatabase),
+    Generic(GenericDatabase),
+    Asn(AsnDatabase),
+}
+
+/// Automatically detect the kind and format of a database file and read it, only sniffing it once.
+///
+/// Dual-stack city `*.mmdb` files are read into a [`CombinedDatabase`] with both tables,
+/// ASN databases like [`detect_asn`], and any other city database like [`detect_lenient`].
+/// CSV city databases without a single well-formed record are an [`Error::NoRecords`].
+pub fn detect_any(path: &Path) -> Result<(DetectedDatabase, ImportReport), Error> {
+    let db = match DatabaseKind::detect(path, None)? {
+        DatabaseKind::Csv {
+            reader,
+            is_num,
+            is_ipv6,
+            is_asn: true,
+            ..
+        } => DetectedDatabase::Asn(csv_asn(reader, is_num, is_ipv6)?),
+        DatabaseKind::Csv {
+            reader,
+            is_num,
+            is_ipv6,
+            schema,
+            ..
+        } => {
+            let (db, report) = csv_lenient(reader, is_num, is_ipv6, &schema)?;
+
+            return match report.imported {
+                0 => Err(Error::NoRecords),
+                _ => Ok((DetectedDatabase::Generic(db), report)),
+            };
+        }
+        DatabaseKind::Maxminddb { reader } if reader::mmdb::is_asn(&reader) => {
+            DetectedDatabase::Asn(AsnDatabase::from_mmdb(reader)?)
+        }
+        DatabaseKind::Maxminddb { reader } if reader::mmdb::has_ipv4(&reader) => {
+            DetectedDatabase::Combined(CombinedDatabase::from_mmdb(reader)?)
+        }
+        DatabaseKind::Maxminddb { reader } => {
+            DetectedDatabase::Generic(from_mmdb(reader, reader::mmdb::DEFAULT_LANGUAGE)?)
+        }
+    };
+
+    Ok((db, ImportReport::default()))
+}
+
 /// A generic [`SingleDatabase`].
 #[derive(PartialEq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
 pub enum GenericDatabase {
@@ -46,13 +244,29 @@ impl GenericDatabase {
     pub fn is_ipv6(&self) -> bool {
         matches!(self, Self::Ipv6(_))
     }
+
+    /// Write the database as a MaxMind DB file that can be read back with [`detect`].
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        match self {
+            Self::Ipv4(db) => db.write_mmdb(w),
+            Self::Ipv6(db) => db.write_mmdb(w),
+        }
+    }
+
+    /// Write the database as a sapics city CSV file that can be read back with [`detect`].
+    pub fn write_csv(&self, w: impl Write, is_num: bool, gzip: bool) -> Result<(), Error> {
+        match self {
+            Self::Ipv4(db) => db.write_csv(w, is_num, gzip),
+            Self::Ipv6(db) => db.write_csv(w, is_num, gzip),
+        }
+    }
 }
 
 impl Database<IpAddr> for GenericDatabase {
-    fn get_coordinate(&self, ip: IpAddr) -> Option<Coordinate> {
+    fn get_network(&self, ip: IpAddr) -> Option<(Network, Coordinate)> {
         match (ip, self) {
-            (IpAddr::V4(ip), GenericDatabase::Ipv4(db)) => db.get_coordinate(ip),
-            (IpAddr::V6(ip), GenericDatabase::Ipv6(db)) => db.get_coordinate(ip),
+            (IpAddr::V4(ip), GenericDatabase::Ipv4(db)) => db.get_network(ip),
+            (IpAddr::V6(ip), GenericDatabase::Ipv6(db)) => db.get_network(ip),
             _ => None,
         }
     }
@@ -63,6 +277,71 @@ impl Database<IpAddr> for GenericDatabase {
             GenericDatabase::Ipv6(db) => db.get_location(crd),
         }
     }
+
+    fn translate(&self, ip: &IpAddr) -> Option<IpAddr> {
+        translate::translate(ip)
+    }
+}
+
+impl ReverseLookup for GenericDatabase {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        match self {
+            GenericDatabase::Ipv4(db) => db.find_networks(query),
+            GenericDatabase::Ipv6(db) => db.find_networks(query),
+        }
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        match self {
+            GenericDatabase::Ipv4(db) => db.find_networks_at(coordinates),
+            GenericDatabase::Ipv6(db) => db.find_networks_at(coordinates),
+        }
+    }
+}
+
+impl SpatialLookup for GenericDatabase {
+    fn spatial_index(&self) -> SpatialIndex {
+        match self {
+            GenericDatabase::Ipv4(db) => db.spatial_index(),
+            GenericDatabase::Ipv6(db) => db.spatial_index(),
+        }
+    }
+}
+
+impl LocationSearch for GenericDatabase {
+    fn search_index(&self) -> SearchIndex {
+        match self {
+            GenericDatabase::Ipv4(db) => db.search_index(),
+            GenericDatabase::Ipv6(db) => db.search_index(),
+        }
+    }
+}
+
+impl Audit for GenericDatabase {
+    fn audit(&self) -> AuditReport {
+        match self {
+            GenericDatabase::Ipv4(db) => db.audit(),
+            GenericDatabase::Ipv6(db) => db.audit(),
+        }
+    }
+}
+
+impl Statistics for GenericDatabase {
+    fn stats(&self) -> DatabaseStats {
+        match self {
+            GenericDatabase::Ipv4(db) => db.stats(),
+            GenericDatabase::Ipv6(db) => db.stats(),
+        }
+    }
+}
+
+impl Prefixes for GenericDatabase {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        match self {
+            GenericDatabase::Ipv4(db) => db.prefixes(),
+            GenericDatabase::Ipv6(db) => db.prefixes(),
+        }
+    }
 }
 
 enum DatabaseKind {
@@ -70,6 +349,9 @@ enum DatabaseKind {
         reader: Box<dyn Read>,
         is_num: bool,
         is_ipv6: bool,
+        /// An ASN rather than a city database, only guessed when no schema is given.
+        is_asn: bool,
+        schema: CsvSchema,
     },
     Maxminddb {
         reader: maxminddb::Reader<Vec<u8>>,
@@ -77,10 +359,36 @@ enum DatabaseKind {
 }
 
 impl DatabaseKind {
-    fn detect(path: &Path) -> Result<Self, Error> {
+    fn detect(path: &Path, schema: Option<CsvSchema>) -> Result<Self, Error> {
         match path.extension().and_then(|s| s.to_str()) {
             Some("mmdb") => Self::parse_mmdb(path),
-            _ => Self::parse_csv(File::open(path)?),
+            _ => Self::parse_csv(File::open(path)?, schema),
+        }
+    }
+
+    fn is_ipv6(&self) -> bool {
+        match self {
+            Self::Csv { is_ipv6, .. } => *is_ipv6,
+            Self::Maxminddb { reader } => reader.metadata.ip_version == 6,
+        }
+    }
+
+    /// Read a city database into a table and a (possibly shared) location store.
+    fn read_into<Ip: GenericIp>(
+        self,
+        ips: &mut IpLookupTable<Ip, PackedCoordinate>,
+        locations: &mut LocationStore,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Csv {
+                reader,
+                is_num,
+                schema,
+                ..
+            } => reader::csv::read(reader, is_num, &schema, None, ips, locations),
+            Self::Maxminddb { reader } => {
+                reader::mmdb::read(&reader, reader::mmdb::DEFAULT_LANGUAGE, ips, locations)
+            }
         }
     }
 
@@ -91,50 +399,76 @@ impl DatabaseKind {
         }
     }
 
-    fn parse_csv(mut f: File) -> Result<Self, Error> {
+    fn parse_csv(mut f: File, schema: Option<CsvSchema>) -> Result<Self, Error> {
         const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+        /// Plenty for a header row and the first record.
+        const PEEK_LEN: u64 = 64 * 1024;
 
         let mut head = [0; 2];
         f.read_exact(&mut head)?;
         f.seek(SeekFrom::Start(0))?;
         let is_gzip = head == GZIP_MAGIC;
 
-        // fill scratch_buff and move to the beginning
-        let mut scratch_buff = [0u8; 50];
+        // peek at the first rows and move to the beginning
+        let mut peek = Vec::new();
         if is_gzip {
-            let mut decoder = GzDecoder::new(f);
-            decoder.read_exact(&mut scratch_buff)?;
-            f = decoder.into_inner();
+            GzDecoder::new(&mut f)
+                .take(PEEK_LEN)
+                .read_to_end(&mut peek)?;
         } else {
-            f.read_exact(&mut scratch_buff)?;
+            (&mut f).take(PEEK_LEN).read_to_end(&mut peek)?;
         }
         f.seek(SeekFrom::Start(0))?;
 
-        let Some(Ok(ip_str)) = scratch_buff
-            .split(|&b| b == b',')
-            .next()
-            .map(CompactString::from_utf8)
-        else {
-            return Err(Error::NoRecords);
+        let mut rows = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(peek.as_slice())
+            .into_byte_records();
+
+        let first = rows.next().ok_or(Error::NoRecords)??;
+        let is_guessed = schema.is_none();
+        let schema = schema
+            .or_else(|| CsvSchema::from_headers(&first))
+            .unwrap_or_default();
+        let sample = match schema.has_headers {
+            true => rows.next().ok_or(Error::NoRecords)??,
+            false => first,
         };
 
-        let parsed_ip = ip_str.parse::<IpAddr>();
+        let field = |idx| {
+            sample
+                .get(idx)
+                .and_then(|f| str::from_utf8(f).ok())
+                .ok_or(Error::NoRecords)
+        };
+        let (start, end) = (field(schema.ip_range_start)?, field(schema.ip_range_end)?);
+
+        let parsed_ip = start.parse::<IpAddr>();
 
         let is_num = parsed_ip.is_err();
-        let is_u32 = ip_str.parse::<u32>().is_ok();
-        let is_u128 = ip_str.parse::<u128>().is_ok();
+        let is_u32 = start.parse::<u32>().is_ok() && end.parse::<u32>().is_ok();
+        let is_u128 = start.parse::<u128>().is_ok() && end.parse::<u128>().is_ok();
 
         if is_num && !is_u32 && !is_u128 {
             return Err(Error::InvalidFormat);
         }
 
-        // *most* IPv6 addresses are not valid u32s (?), so we can use that to check for ipv6-num format??
+        // *most* IPv6 ranges don't fit in u32s, so we can use that to check for ipv6-num format.
         let is_ipv6 = if is_num {
             !is_u32
         } else {
             parsed_ip.is_ok_and(|ip| ip.is_ipv6())
         };
 
+        // headerless city databases have a country code where ASN databases have their number
+        let is_asn = is_guessed
+            && !schema.has_headers
+            && sample
+                .get(reader::csv::ASN_IDX)
+                .and_then(|f| str::from_utf8(f).ok())
+                .is_some_and(|asn| asn.parse::<u32>().is_ok());
+
         let reader: Box<dyn Read> = if is_gzip {
             Box::new(GzDecoder::new(f))
         } else {
@@ -145,6 +479,85 @@ impl DatabaseKind {
             reader,
             is_num,
             is_ipv6,
+            is_asn,
+            schema,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{error, fs, net::IpAddr};
+
+    use crate::{
+        AsnLookup, Database, DetectedDatabase, Error,
+        testing::{BRISBANE, csv},
+    };
+
+    #[test]
+    fn combined_directory() -> Result<(), Box<dyn error::Error>> {
+        let dir = std::env::temp_dir().join(format!("ipgeo-{}", fastrand::u64(..)));
+        fs::create_dir(&dir)?;
+
+        fs::write(dir.join("dbip-city-ipv4.csv"), csv(&[BRISBANE]))?;
+        fs::write(
+            dir.join("dbip-city-ipv6.csv"),
+            "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,AU,Queensland,,Brisbane,,-27.4679,153.028\n",
+        )?;
+        fs::write(dir.join("README.txt"), "City databases from DB-IP.\n")?;
+
+        let db = super::detect_combined(&[&dir]);
+        let pair = super::detect_combined(&[
+            dir.join("dbip-city-ipv6.csv"),
+            dir.join("dbip-city-ipv4.csv"),
+        ]);
+        let single = super::detect_combined(&[dir.join("dbip-city-ipv4.csv")]);
+        fs::remove_dir_all(&dir)?;
+
+        let db = db?;
+        assert!(db == pair?);
+        assert!(matches!(single, Err(Error::NotADatabasePair)));
+
+        let ipv4 = db.get("1.0.0.1".parse::<IpAddr>()?).expect("IPv4 record");
+        let ipv6 = db
+            .get("2001:200::1".parse::<IpAddr>()?)
+            .expect("IPv6 record");
+        assert_eq!(ipv4, ipv6);
+        assert_eq!(db.locations.locations.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn any_kind() -> Result<(), Box<dyn error::Error>> {
+        let dir = std::env::temp_dir().join(format!("ipgeo-{}", fastrand::u64(..)));
+        fs::create_dir(&dir)?;
+
+        let city = dir.join("city.csv");
+        let asn = dir.join("asn.csv");
+        let empty = dir.join("empty.csv");
+        fs::write(&city, csv(&[BRISBANE]))?;
+        fs::write(&asn, "1.0.0.0,1.0.0.255,13335,\"Cloudflare, Inc.\"\n")?;
+        fs::write(
+            &empty,
+            "1.0.0.0,1.0.0.255,AU,Queensland,,Brisbane,,north,east\n",
+        )?;
+
+        let results = [&city, &asn, &empty].map(|path| super::detect_any(path));
+        fs::remove_dir_all(&dir)?;
+        let [city, asn, empty] = results;
+
+        let ip = "1.0.0.1".parse::<IpAddr>()?;
+        match city?.0 {
+            DetectedDatabase::Generic(db) => assert!(db.get(ip).is_some()),
+            _ => panic!("expected a city database"),
+        }
+        match asn?.0 {
+            DetectedDatabase::Asn(db) => assert_eq!(db.get_asn(ip).map(|a| a.number), Some(13335)),
+            _ => panic!("expected an ASN database"),
+        }
+        assert!(matches!(empty, Err(Error::NoRecords)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! Compare two releases of a database prefix by prefix.
+
+use std::collections::HashMap;
+
+use rustc_hash::FxBuildHasher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use treebitmap::IpLookupTable;
+
+use crate::{
+    CombinedDatabase, Coordinate, GenericIp, Network, Prefixes, SingleDatabase,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationStore},
+};
+
+/// A network prefix in a database with where it's placed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Prefix {
+    pub network: Network,
+    pub crd: Coordinate,
+    pub country_code: CountryCode,
+}
+
+/// What changed between two releases of a database, see [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseDiff {
+    /// Prefixes only in the new database.
+    pub added: u64,
+    /// Prefixes only in the old database.
+    pub removed: u64,
+    /// Prefixes in both databases that were moved to another coordinate.
+    pub relocated: u64,
+    /// Prefixes in both databases at the same coordinate.
+    pub unchanged: u64,
+    /// Changes per country, sorted by country code.
+    pub countries: Vec<CountryDiff>,
+    /// The [`DatabaseDiff::MAX_MOVES`] relocated prefixes that moved the furthest, furthest first.
+    pub largest_moves: Vec<PrefixMove>,
+}
+
+/// Changed prefixes in a single country.
+///
+/// Added and relocated prefixes are counted in their new country, removed ones in their old.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryDiff {
+    pub country_code: String,
+    pub added: u64,
+    pub removed: u64,
+    pub relocated: u64,
+}
+
+/// A prefix that was moved to another coordinate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefixMove {
+    pub network: Network,
+    pub from: Coordinate,
+    pub to: Coordinate,
+    pub from_country: String,
+    pub to_country: String,
+    /// Great-circle distance between `from` and `to`.
+    pub distance_km: f32,
+}
+
+impl DatabaseDiff {
+    /// How many of the largest moves are kept.
+    pub const MAX_MOVES: usize = 100;
+
+    /// True if both databases have the same prefixes at the same coordinates.
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.relocated == 0
+    }
+}
+
+/// Compare an old release of a database with a new one.
+///
+/// Prefixes are compared exactly, so a network that was split into smaller ones
+/// counts as one removed and several added prefixes.
+pub fn diff(old: &dyn Prefixes, new: &dyn Prefixes) -> DatabaseDiff {
+    let mut old = old
+        .prefixes()
+        .map(|p| (p.network, p))
+        .collect::<HashMap<Network, Prefix, FxBuildHasher>>();
+
+    let mut report = DatabaseDiff::default();
+    let mut countries = HashMap::<CountryCode, CountryDiff, FxBuildHasher>::default();
+    let mut moves = Vec::new();
+
+    for new in new.prefixes() {
+        let Some(old) = old.remove(&new.network) else {
+            report.added += 1;
+            country(&mut countries, new.country_code).added += 1;
+            continue;
+        };
+
+        if old.crd == new.crd {
+            report.unchanged += 1;
+            continue;
+        }
+
+        report.relocated += 1;
+        country(&mut countries, new.country_code).relocated += 1;
+        moves.push(PrefixMove {
+            network: new.network,
+            from: old.crd,
+            to: new.crd,
+            from_country: old.country_code.to_string(),
+            to_country: new.country_code.to_string(),
+            distance_km: old.crd.distance_km(&new.crd),
+        });
+    }
+
+    for old in old.into_values() {
+        report.removed += 1;
+        country(&mut countries, old.country_code).removed += 1;
+    }
+
+    if moves.len() > DatabaseDiff::MAX_MOVES {
+        moves.select_nth_unstable_by(DatabaseDiff::MAX_MOVES, |a, b| {
+            b.distance_km.total_cmp(&a.distance_km)
+        });
+        moves.truncate(DatabaseDiff::MAX_MOVES);
+    }
+    moves.sort_unstable_by(|a, b| b.distance_km.total_cmp(&a.distance_km));
+    report.largest_moves = moves;
+
+    report.countries = countries.into_values().collect();
+    report
+        .countries
+        .sort_unstable_by(|a, b| a.country_code.cmp(&b.country_code));
+
+    report
+}
+
+fn country(
+    countries: &mut HashMap<CountryCode, CountryDiff, FxBuildHasher>,
+    code: CountryCode,
+) -> &mut CountryDiff {
+    countries.entry(code).or_insert_with(|| CountryDiff {
+        country_code: code.to_string(),
+        added: 0,
+        removed: 0,
+        relocated: 0,
+    })
+}
+
+/// Iterate over a table's prefixes, placing them with `locations`.
+///
+/// Takes iterators rather than an [`IpLookupTable`] so it can be shared with the archived table.
+pub(crate) fn table_prefixes<'a, Ip: GenericIp>(
+    table: impl Iterator<Item = (Ip, u32, PackedCoordinate)> + 'a,
+    country_code: impl Fn(&PackedCoordinate) -> Option<CountryCode> + 'a,
+) -> impl Iterator<Item = Prefix> + 'a {
+    table.filter_map(move |(ip, len, crd)| {
+        Some(Prefix {
+            network: Network::from((ip, len)),
+            country_code: country_code(&crd)?,
+            crd: (&crd).into(),
+        })
+    })
+}
+
+fn owned_prefixes<'a, Ip: GenericIp>(
+    table: &'a IpLookupTable<Ip, PackedCoordinate>,
+    locations: &'a LocationStore,
+) -> impl Iterator<Item = Prefix> + 'a {
+    table_prefixes(table.iter().map(|(ip, len, c)| (ip, len, *c)), |c| {
+        locations.country_code(c)
+    })
+}
+
+impl<Ip: GenericIp> Prefixes for SingleDatabase<Ip> {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        Box::new(owned_prefixes(&self.ips, &self.locations))
+    }
+}
+
+impl Prefixes for CombinedDatabase {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        Box::new(
+            owned_prefixes(&self.ipv4, &self.locations)
+                .chain(owned_prefixes(&self.ipv6, &self.locations)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::Ipv4Addr};
+
+    use crate::{
+        Ipv4Database, Network,
+        testing::{BRISBANE, FUZHOU, archived, csv},
+    };
+
+    use super::diff;
+
+    const OLD: &[&str] = &[
+        BRISBANE,
+        FUZHOU,
+        "1.0.2.0,1.0.2.255,CN,Fujian,,Fuzhou,,26.0614,119.306",
+    ];
+    const NEW: &[&str] = &[
+        BRISBANE,
+        "1.0.1.0,1.0.1.255,JP,Tokyo,,Tokyo,,35.6895,139.692",
+        "1.0.3.0,1.0.3.255,CN,Fujian,,Fuzhou,,26.0614,119.306",
+    ];
+
+    #[test]
+    fn changes() -> Result<(), Box<dyn error::Error>> {
+        let old = Ipv4Database::from_csv(csv(OLD).as_bytes(), false)?;
+        let new = Ipv4Database::from_csv(csv(NEW).as_bytes(), false)?;
+
+        let report = diff(&old, &new);
+        assert_eq!(
+            (
+                report.added,
+                report.removed,
+                report.relocated,
+                report.unchanged
+            ),
+            (1, 1, 1, 1)
+        );
+
+        let [moved] = report.largest_moves.as_slice() else {
+            panic!("expected one move");
+        };
+        assert_eq!(
+            moved.network,
+            Network::from(("1.0.1.0".parse::<Ipv4Addr>()?, 24))
+        );
+        assert_eq!(
+            (moved.from_country.as_str(), moved.to_country.as_str()),
+            ("CN", "JP")
+        );
+        // Fuzhou to Tokyo
+        assert!(
+            (moved.distance_km - 2214.0).abs() < 20.0,
+            "{}",
+            moved.distance_km
+        );
+
+        let countries = report
+            .countries
+            .iter()
+            .map(|c| (c.country_code.as_str(), c.added, c.removed, c.relocated))
+            .collect::<Vec<_>>();
+        assert_eq!(countries, [("CN", 1, 1, 0), ("JP", 0, 0, 1)]);
+
+        // archived databases compare the same as owned ones
+        archived(&old, |archived| {
+            assert_eq!(diff(archived, &new), report);
+            assert!(diff(archived, &old).is_empty());
+        })?;
+
+        Ok(())
+    }
+}
@@ -10,10 +10,12 @@ use std::{
 };
 
 use crate::{
-    CombinedDatabase, Error, GenericIp, SingleDatabase,
+    AsnDatabase, CombinedDatabase, CsvSchema, Error, GenericIp, ImportReport, SingleDatabase,
+    asn::{AsnKey, AsnStore},
     coordinate::PackedCoordinate,
     locations::{
         CountryCode, LocationIndices, LocationKey, LocationStore, StringDict, StringDictKey,
+        conflicting_cities,
     },
     reader::csv::*,
     treebitmap::IpLookupTable,
@@ -26,6 +28,7 @@ use csv_async::{AsyncReaderBuilder, ByteRecord};
 use dashmap::DashMap;
 use futures::StreamExt;
 use rustc_hash::FxBuildHasher;
+use tokio::io::AsyncRead;
 use tokio_util::io::StreamReader;
 use unix_time::Instant;
 
@@ -43,6 +46,28 @@ impl<Ip: GenericIp> SingleDatabase<Ip> {
         report_gap: Duration,
         progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
     ) -> anyhow::Result<Self> {
+        Self::download_inner(csv_url, is_num, false, report_gap, progress_report)
+            .await
+            .map(|(db, _)| db)
+    }
+
+    /// Like [`download`](Self::download), but skips malformed records and reports them instead of failing.
+    pub async fn download_lenient(
+        csv_url: impl AsRef<str>,
+        is_num: bool,
+        report_gap: Duration,
+        progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> anyhow::Result<(Self, ImportReport)> {
+        Self::download_inner(csv_url, is_num, true, report_gap, progress_report).await
+    }
+
+    async fn download_inner(
+        csv_url: impl AsRef<str>,
+        is_num: bool,
+        lenient: bool,
+        report_gap: Duration,
+        progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> anyhow::Result<(Self, ImportReport)> {
         let ip_parser = if is_num {
             Ip::from_num_bytes
         } else {
@@ -79,35 +104,22 @@ impl<Ip: GenericIp> SingleDatabase<Ip> {
             .buffer_capacity(64 * 1024) // 64KB internal buffer
             .create_reader(GzipDecoder::new(StreamReader::new(stream)));
 
-        let mut byte_records = reader.byte_records();
+        let mut record = ByteRecord::new();
+        let mut report = ImportReport::default();
         let mut size = 0;
 
         let mut ips = IpLookupTable::new();
         let mut locations = LocationStore::default();
 
-        while let Some(res) = byte_records.next().await {
-            let record = res?;
-
-            if record.len() < NUM_RECORDS {
-                return Err(anyhow::anyhow!("Not enough columns"));
-            }
-
-            let coord: PackedCoordinate = coord_from_record(&record)?.into();
-
-            locations.insert(coord, &|strings| {
-                Ok(LocationIndices {
-                    city: strings.insert_bytes(&record[CITY_IDX]),
-                    region: strings.insert_bytes(&record[REGION_IDX]),
-                    country_code: CountryCode::from(&record[COUNTRY_CODE_IDX]),
-                })
-            })?;
-
-            for (addr, len) in Ip::range_subnets(
-                ip_parser(&record[IP_RANGE_START_IDX])?,
-                ip_parser(&record[IP_RANGE_END_IDX])?,
-            ) {
-                ips.insert(addr, len, coord);
-            }
+        while next_record(&mut reader, &mut record, lenient, &mut report).await? {
+            let res = read_record(
+                &record,
+                &CsvSchema::SAPICS,
+                ip_parser,
+                &mut ips,
+                &mut locations,
+            );
+            handle_record(&record, res, lenient, &mut report)?;
 
             size += record.as_slice().len() as u64;
         }
@@ -122,7 +134,7 @@ impl<Ip: GenericIp> SingleDatabase<Ip> {
             elapsed.as_secs()
         );
 
-        Ok(Self { ips, locations })
+        Ok((Self { ips, locations }, report))
     }
 }
 
@@ -133,6 +145,27 @@ impl CombinedDatabase {
         report_gap: Duration,
         progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
     ) -> anyhow::Result<Self> {
+        Self::download_inner(source, false, report_gap, progress_report)
+            .await
+            .map(|(db, _)| db)
+    }
+
+    /// Like [`download`](Self::download), but skips malformed records and reports them instead of failing.
+    #[cfg(feature = "download")]
+    pub async fn download_lenient<'a>(
+        source: CombinedDatabaseSource<'a>,
+        report_gap: Duration,
+        progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> anyhow::Result<(Self, ImportReport)> {
+        Self::download_inner(source, true, report_gap, progress_report).await
+    }
+
+    async fn download_inner<'a>(
+        source: CombinedDatabaseSource<'a>,
+        lenient: bool,
+        report_gap: Duration,
+        progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> anyhow::Result<(Self, ImportReport)> {
         let start = std::time::Instant::now();
 
         let last = Arc::new((AtomicU64::new(0), AtomicU32::new(0)));
@@ -176,6 +209,7 @@ impl CombinedDatabase {
             tokio::spawn(concurrent_table_download(
                 source.ipv4_csv_url.to_string(),
                 source.is_num,
+                lenient,
                 locations.clone(),
                 add_total.clone(),
                 add_val.clone()
@@ -183,14 +217,16 @@ impl CombinedDatabase {
             tokio::spawn(concurrent_table_download(
                 source.ipv6_csv_url.to_string(),
                 source.is_num,
+                lenient,
                 locations.clone(),
                 add_total,
                 add_val,
             ))
         );
 
-        let (ipv4, ipv4_len) = ipv4??;
-        let (ipv6, ipv6_len) = ipv6??;
+        let (ipv4, ipv4_len, mut report) = ipv4??;
+        let (ipv6, ipv6_len, ipv6_report) = ipv6??;
+        report.merge(ipv6_report);
 
         let size_mb = ByteSize::b(ipv4_len + ipv6_len).as_mb();
         let elapsed = start.elapsed();
@@ -206,21 +242,133 @@ impl CombinedDatabase {
             .map_err(|_| anyhow::anyhow!("Failed to unwrap locations Arc"))?
             .into_store();
 
-        Ok(CombinedDatabase {
-            ipv4,
-            ipv6,
-            locations,
-        })
+        Ok((
+            CombinedDatabase {
+                ipv4,
+                ipv6,
+                locations,
+            },
+            report,
+        ))
+    }
+}
+
+impl AsnDatabase {
+    /// Download an ASN database from a pair of CSV files, which are decompressed if their url ends in `.gz`.
+    pub async fn download<'a>(
+        source: CombinedDatabaseSource<'a>,
+        report_gap: Duration,
+        progress_report: impl Fn(u64, u64) + Send + Sync + 'static,
+    ) -> anyhow::Result<Self> {
+        let start = std::time::Instant::now();
+
+        let ipv4_resp = reqwest::get(source.ipv4_csv_url.as_ref())
+            .await?
+            .error_for_status()?;
+        let ipv6_resp = reqwest::get(source.ipv6_csv_url.as_ref())
+            .await?
+            .error_for_status()?;
+
+        let content_length = ipv4_resp.content_length().unwrap_or_default()
+            + ipv6_resp.content_length().unwrap_or_default();
+
+        let mut last_reported = Instant::now();
+        let mut count = 0;
+
+        let mut cb = |v: u64| {
+            count += v;
+
+            if content_length > 0 && last_reported.elapsed() >= report_gap {
+                progress_report(count, content_length);
+                last_reported = Instant::now();
+            }
+        };
+
+        let mut db = AsnDatabase::default();
+
+        let ipv4_len = asn_table_download(
+            ipv4_resp,
+            source.ipv4_csv_url.ends_with(".gz"),
+            source.is_num,
+            &mut db.ipv4,
+            &mut db.systems,
+            &mut cb,
+        )
+        .await?;
+        let ipv6_len = asn_table_download(
+            ipv6_resp,
+            source.ipv6_csv_url.ends_with(".gz"),
+            source.is_num,
+            &mut db.ipv6,
+            &mut db.systems,
+            &mut cb,
+        )
+        .await?;
+
+        let size_mb = ByteSize::b(ipv4_len + ipv6_len).as_mb();
+        let elapsed = start.elapsed();
+
+        tracing::debug!(
+            "Downloaded {:.2} MB, decompressing/parsing at {:.2} MB/s in {} seconds",
+            ByteSize::b(content_length).as_mb(),
+            size_mb / elapsed.as_secs_f64(),
+            elapsed.as_secs()
+        );
+
+        Ok(db)
+    }
+}
+
+async fn asn_table_download<Ip: GenericIp>(
+    resp: reqwest::Response,
+    is_gzip: bool,
+    is_num: bool,
+    ips: &mut IpLookupTable<Ip, AsnKey>,
+    systems: &mut AsnStore,
+    chunk_report: &mut (impl FnMut(u64) + Send),
+) -> anyhow::Result<u64> {
+    let ip_parser = if is_num {
+        Ip::from_num_bytes
+    } else {
+        Ip::from_str_bytes
+    };
+
+    let stream = resp
+        .bytes_stream()
+        .map(|item| item.map_err(io::Error::other))
+        .map(|item| item.inspect(|i| chunk_report(i.len() as u64)));
+
+    let read: Box<dyn AsyncRead + Unpin + Send + '_> = if is_gzip {
+        Box::new(GzipDecoder::new(StreamReader::new(stream)))
+    } else {
+        Box::new(StreamReader::new(stream))
+    };
+
+    let mut reader = AsyncReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .buffer_capacity(64 * 1024) // 64KB internal buffer
+        .create_reader(read);
+
+    let mut record = ByteRecord::new();
+    let mut size = 0;
+
+    while reader.read_byte_record(&mut record).await? {
+        read_asn_record(&record, ip_parser, ips, systems)?;
+        size += record.as_slice().len() as u64;
     }
+
+    Ok(size)
 }
 
 async fn concurrent_table_download<Ip: GenericIp>(
     url: String,
     is_num: bool,
+    lenient: bool,
     locations: Arc<ConcurrentLocationStore>,
     len_report: impl Fn(u64) + Send + Sync,
     chunk_report: impl Fn(u64) + Send + Sync,
-) -> anyhow::Result<(IpLookupTable<Ip, PackedCoordinate>, u64)> {
+) -> anyhow::Result<(IpLookupTable<Ip, PackedCoordinate>, u64, ImportReport)> {
     let ip_parser = if is_num {
         Ip::from_num_bytes
     } else {
@@ -244,35 +392,54 @@ async fn concurrent_table_download<Ip: GenericIp>(
         .create_reader(GzipDecoder::new(StreamReader::new(stream)));
 
     let mut record = ByteRecord::new();
+    let mut report = ImportReport::default();
     let mut table = IpLookupTable::new();
     let mut size = 0;
 
-    while reader.read_byte_record(&mut record).await? {
-        if record.len() < NUM_RECORDS {
-            return Err(anyhow::anyhow!("Not enough columns"));
-        }
-
-        let coord: PackedCoordinate = coord_from_record(&record)?.into();
-
-        locations.insert(coord, &|strings| {
-            Ok(LocationIndices {
-                city: strings.insert_bytes(&record[CITY_IDX]),
-                region: strings.insert_bytes(&record[REGION_IDX]),
-                country_code: CountryCode::from(&record[COUNTRY_CODE_IDX]),
-            })
-        })?;
-
-        for (addr, len) in Ip::range_subnets(
-            ip_parser(&record[IP_RANGE_START_IDX])?,
-            ip_parser(&record[IP_RANGE_END_IDX])?,
-        ) {
-            table.insert(addr, len, coord);
-        }
+    while next_record(&mut reader, &mut record, lenient, &mut report).await? {
+        let res = read_record(
+            &record,
+            &CsvSchema::SAPICS,
+            ip_parser,
+            &mut table,
+            &mut locations.as_ref(),
+        );
+        handle_record(&record, res, lenient, &mut report)?;
 
         size += record.as_slice().len() as u64;
     }
 
-    Ok((table, size))
+    Ok((table, size, report))
+}
+
+/// Read the next record, skipping any that aren't valid CSV into `report` when `lenient`.
+async fn next_record<R: AsyncRead + Unpin + Send>(
+    reader: &mut csv_async::AsyncReader<R>,
+    record: &mut ByteRecord,
+    lenient: bool,
+    report: &mut ImportReport,
+) -> anyhow::Result<bool> {
+    loop {
+        match reader.read_byte_record(record).await {
+            Err(err) if lenient && !err.is_io_error() => {
+                report.skip(err.position().map(|p| p.line()).unwrap_or_default(), err)
+            }
+            res => return Ok(res?),
+        }
+    }
+}
+
+/// Pass on the outcome of reading a record, or skip it into `report` when `lenient`.
+fn handle_record(
+    record: &ByteRecord,
+    res: Result<(), Error>,
+    lenient: bool,
+    report: &mut ImportReport,
+) -> Result<(), Error> {
+    match lenient {
+        true => report.record(record.position().map(|p| p.line()).unwrap_or_default(), res),
+        false => res,
+    }
 }
 
 /// A concurrent, thread-safe builder for LocationStore.
@@ -283,28 +450,67 @@ struct ConcurrentLocationStore {
     loc_storage: DashMap<LocationKey, LocationIndices, FxBuildHasher>,
     loc_counter: AtomicUsize,
     strings: ConcurrentStringDict,
+    conflicts: DashMap<PackedCoordinate, Vec<LocationIndices>, FxBuildHasher>,
+}
+
+impl LocationSink for &ConcurrentLocationStore {
+    fn insert_names(
+        &mut self,
+        coord: PackedCoordinate,
+        country_code: CountryCode,
+        names: [&[u8]; 5],
+    ) -> Result<(), Error> {
+        self.insert(coord, Some(names[2]), &|strings| {
+            Ok(LocationIndices::from_names(country_code, names, |name| {
+                strings.insert_bytes(name)
+            }))
+        })
+    }
 }
 
 impl ConcurrentLocationStore {
+    /// Like [`LocationStore::insert`], keeping conflicting cities aside.
     fn insert(
         &self,
         coord: PackedCoordinate,
+        city: Option<&[u8]>,
         create_location: &dyn Fn(&ConcurrentStringDict) -> Result<LocationIndices, Error>,
     ) -> Result<(), Error> {
-        if self.coordinates.contains_key(&coord) {
-            return Ok(());
-        }
-
-        if let dashmap::Entry::Vacant(entry) = self.coordinates.entry(coord) {
-            let indices = create_location(&self.strings)?;
+        match self.coordinates.entry(coord) {
+            dashmap::Entry::Occupied(entry) => {
+                let key = *entry.get();
+                drop(entry);
+
+                // the string is released before creating the location, which may insert more
+                let conflicting = {
+                    let stored = self
+                        .loc_storage
+                        .get(&key)
+                        .and_then(|loc| loc.city)
+                        .and_then(|key| self.strings.storage.get(&key.get()));
+                    conflicting_cities(stored.as_deref().map(|s| s.as_str()), city)
+                };
+
+                if conflicting {
+                    let conflict = create_location(&self.strings)?;
+                    let mut conflicts = self.conflicts.entry(coord).or_default();
+
+                    if !conflicts.iter().any(|c| c.city == conflict.city) {
+                        conflicts.push(conflict);
+                    }
+                }
+            }
+            dashmap::Entry::Vacant(entry) => {
+                let indices = create_location(&self.strings)?;
 
-            let loc_key = *self.loc_lookup.entry(indices).or_insert_with(|| {
-                let id = self.loc_counter.fetch_add(1, Ordering::Relaxed);
-                self.loc_storage.insert(id, indices);
-                id
-            });
+                let loc_key = *self.loc_lookup.entry(indices).or_insert_with(|| {
+                    let id = self.loc_counter.fetch_add(1, Ordering::Relaxed);
+                    self.loc_storage.insert(id, indices);
+                    id
+                });
 
-            entry.insert(loc_key);
+                entry.insert(loc_key);
+            }
         }
 
         Ok(())
@@ -319,6 +525,7 @@ impl ConcurrentLocationStore {
             coordinates: self.coordinates.into_iter().collect(),
             locations: loc_vec.into_iter().map(|(_, v)| v).collect(),
             strings: self.strings.into_string_dict(),
+            conflicts: self.conflicts.into_iter().collect(),
         }
     }
 }
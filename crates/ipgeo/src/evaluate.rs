@@ -0,0 +1,233 @@
+//! Measuring how close a database places addresses whose real location is known.
+
+use std::{io::Read, net::IpAddr};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{Coordinate, Database, Error};
+
+/// An address whose real location is known, e.g. an office or a CDN point of presence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundTruth {
+    pub ip: IpAddr,
+    pub crd: Coordinate,
+    pub country_code: String,
+}
+
+/// How accurately a database placed a set of [`GroundTruth`] addresses, see [`evaluate`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Evaluation {
+    /// Addresses looked up.
+    pub total: u64,
+    /// Addresses that were found in the database.
+    pub found: u64,
+    /// Found addresses placed in their real country.
+    pub country_matches: u64,
+    /// `country_matches` out of `found`, from 0 to 1.
+    pub country_match_rate: f64,
+    /// Median distance between where found addresses were placed and where they are.
+    pub median_error_km: Option<f32>,
+    /// 90th percentile of the same distances, nine in ten found addresses are at least this close.
+    pub p90_error_km: Option<f32>,
+    /// Addresses that weren't found or were placed in another country, in the order given.
+    pub misses: Vec<EvaluationMiss>,
+}
+
+/// A [`GroundTruth`] address the database didn't find, or placed in another country.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationMiss {
+    pub ip: IpAddr,
+    /// The address's real country.
+    pub expected_country: String,
+    /// The country the database placed the address in, if it was found.
+    pub found_country: Option<String>,
+    /// Distance from the address's real coordinate, if it was found.
+    pub error_km: Option<f32>,
+}
+
+/// Read a ground truth CSV of `ip,lat,lng,country` rows, with an optional header and `#` comment lines.
+///
+/// Returns the addresses and how many other rows couldn't be read as one.
+///
+/// ```csv
+/// ip,lat,lng,country
+/// 192.0.2.10,52.52,13.405,DE
+/// ```
+pub fn read_ground_truth(read: impl Read) -> Result<(Vec<GroundTruth>, u64), Error> {
+    let mut truth = Vec::new();
+    let mut invalid = 0;
+
+    for (i, record) in csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_reader(read)
+        .into_records()
+        .enumerate()
+    {
+        let record = record?;
+
+        if i == 0 && record.get(0).is_some_and(|f| f.eq_ignore_ascii_case("ip")) {
+            continue;
+        }
+
+        let (Some(ip), Some(lat), Some(lng), Some(country)) =
+            (record.get(0), record.get(1), record.get(2), record.get(3))
+        else {
+            invalid += 1;
+            continue;
+        };
+
+        match (ip.parse(), lat.parse(), lng.parse()) {
+            (Ok(ip), Ok(lat), Ok(lng)) if country.len() == 2 => truth.push(GroundTruth {
+                ip,
+                crd: Coordinate { lat, lng },
+                country_code: country.to_ascii_uppercase(),
+            }),
+            _ => invalid += 1,
+        }
+    }
+
+    Ok((truth, invalid))
+}
+
+/// Look up every [`GroundTruth`] address in a database and measure how far off it was.
+///
+/// Errors are only measured over the addresses that were found, so compare
+/// `found` as well when ranking databases.
+pub fn evaluate<D>(db: &D, truth: &[GroundTruth]) -> Evaluation
+where
+    D: Database<IpAddr> + Sync + ?Sized,
+{
+    let ips = truth.iter().map(|t| t.ip).collect::<Vec<_>>();
+    let matches = db.get_batch(&ips, true);
+
+    let mut evaluation = Evaluation {
+        total: truth.len() as u64,
+        ..Default::default()
+    };
+    let mut errors = Vec::with_capacity(truth.len());
+
+    for (truth, matched) in truth.iter().zip(matches) {
+        let Some(matched) = matched else {
+            evaluation.misses.push(EvaluationMiss {
+                ip: truth.ip,
+                expected_country: truth.country_code.clone(),
+                found_country: None,
+                error_km: None,
+            });
+            continue;
+        };
+
+        let error_km = matched.info.crd.distance_km(&truth.crd);
+        evaluation.found += 1;
+        errors.push(error_km);
+
+        match matched
+            .info
+            .loc
+            .country_code
+            .eq_ignore_ascii_case(&truth.country_code)
+        {
+            true => evaluation.country_matches += 1,
+            false => evaluation.misses.push(EvaluationMiss {
+                ip: truth.ip,
+                expected_country: truth.country_code.clone(),
+                found_country: Some(matched.info.loc.country_code),
+                error_km: Some(error_km),
+            }),
+        }
+    }
+
+    errors.sort_unstable_by(f32::total_cmp);
+    evaluation.median_error_km = percentile(&errors, 0.5);
+    evaluation.p90_error_km = percentile(&errors, 0.9);
+    evaluation.country_match_rate = match evaluation.found {
+        0 => 0.0,
+        found => evaluation.country_matches as f64 / found as f64,
+    };
+
+    evaluation
+}
+
+/// The nearest-rank percentile `p` (from 0 to 1) of sorted values.
+fn percentile(sorted: &[f32], p: f64) -> Option<f32> {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+
+    use crate::{
+        CombinedDatabase,
+        testing::{BRISBANE, FUZHOU, TOKYO_IPV6, csv},
+    };
+
+    use super::{evaluate, percentile, read_ground_truth};
+
+    #[test]
+    fn evaluation() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(
+            csv(&[BRISBANE, FUZHOU, "1.0.2.0,1.0.2.255,US,,,,,37.751,-97.822"]).as_bytes(),
+            csv(&[TOKYO_IPV6]).as_bytes(),
+            false,
+        )?;
+
+        let (truth, invalid) = read_ground_truth(
+            "ip,lat,lng,country
+# offices
+1.0.0.1,-27.4679,153.028,au
+1.0.1.1,26.0614,119.306,CN
+1.0.2.1,40.7128,-74.006,CA
+2001:200::1,35.6895,139.692,JP
+9.9.9.9,37.751,-97.822,US
+not-an-ip,0,0,US
+1.0.0.2,north,153.028,AU
+"
+            .as_bytes(),
+        )?;
+        assert_eq!(invalid, 2);
+        assert_eq!(truth.len(), 5);
+        assert_eq!(truth[0].country_code, "AU");
+
+        let evaluation = evaluate(&db, &truth);
+        assert_eq!(evaluation.total, 5);
+        assert_eq!(evaluation.found, 4);
+        assert_eq!(evaluation.country_matches, 3);
+        assert_eq!(evaluation.country_match_rate, 0.75);
+
+        // three exact matches and New York placed in Kansas
+        assert!(evaluation.median_error_km.is_some_and(|km| km < 1.0));
+        assert!(evaluation.p90_error_km.is_some_and(|km| km > 1500.0));
+
+        let misses = evaluation
+            .misses
+            .iter()
+            .map(|m| (m.ip.to_string(), m.found_country.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            misses,
+            [
+                ("1.0.2.1".to_string(), Some("US")),
+                ("9.9.9.9".to_string(), None)
+            ]
+        );
+
+        let empty = evaluate(&db, &[]);
+        assert_eq!(empty.median_error_km, None);
+        assert_eq!(empty.country_match_rate, 0.0);
+
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 0.5), Some(2.0));
+        assert_eq!(percentile(&[1.0, 2.0, 3.0, 4.0], 0.9), Some(4.0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Reports for lenient CSV imports, which skip malformed records instead of failing on the first one.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::Error;
+
+/// What happened while leniently importing a database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ImportReport {
+    /// Records that were read without issue.
+    pub imported: u64,
+    /// Malformed records that were skipped.
+    pub skipped: u64,
+    /// The first [`ImportReport::MAX_ISSUES`] skipped records, in the order they were found.
+    pub issues: Vec<ImportIssue>,
+}
+
+/// A single malformed record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct ImportIssue {
+    /// One-based line number in the file, `0` if unknown.
+    pub line: u64,
+    /// Why the record was skipped.
+    pub reason: String,
+}
+
+impl ImportReport {
+    /// How many issues are kept, the rest are only counted in `skipped`.
+    pub const MAX_ISSUES: usize = 50;
+
+    /// True if no records were skipped.
+    pub fn is_clean(&self) -> bool {
+        self.skipped == 0
+    }
+
+    /// Combine the reports of two files imported into the same database.
+    pub fn merge(&mut self, other: ImportReport) {
+        self.imported += other.imported;
+        self.skipped += other.skipped;
+        self.issues.extend(other.issues);
+        self.issues.truncate(Self::MAX_ISSUES);
+    }
+
+    /// Count a malformed record.
+    pub(crate) fn skip(&mut self, line: u64, reason: impl ToString) {
+        self.skipped += 1;
+
+        if self.issues.len() < Self::MAX_ISSUES {
+            self.issues.push(ImportIssue {
+                line,
+                reason: reason.to_string(),
+            });
+        }
+    }
+
+    /// Count the result of reading a record, passing on errors that a skipped record can't recover from.
+    pub(crate) fn record(&mut self, line: u64, res: Result<(), Error>) -> Result<(), Error> {
+        match res {
+            Ok(()) => self.imported += 1,
+            Err(err) if is_record_error(&err) => self.skip(line, err),
+            Err(err) => return Err(err),
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether an error is caused by a single malformed record, rather than the file or the database as a whole.
+fn is_record_error(err: &Error) -> bool {
+    match err {
+        Error::ReadCsv(err) => !err.is_io_error(),
+        Error::Io(_)
+        | Error::DatabaseMetadataOverflow
+        | Error::NoRecords
+        | Error::MaxMindDb(_)
+        | Error::MalformedMaxMindDb
+        | Error::InvalidFormat => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+
+    use crate::{
+        Database, ImportReport, Ipv4Database,
+        testing::{BRISBANE, csv},
+    };
+
+    #[test]
+    fn lenient_csv() -> Result<(), Box<dyn error::Error>> {
+        let records = [
+            csv(&[BRISBANE]).as_bytes(),
+            b"1.0.1.0,1.0.1.255,CN,Fujian,,Fuzhou,,not-a-latitude,119.306
+1.0.2.0,1.0.1.255,CN,Fujian,,Fuzhou,,26.0614,119.306
+1.0.3.x,1.0.3.255,CN,Fujian,,Fuzhou,,26.0614,119.306
+1.0.4.0,1.0.4.255,CN,Fujian,,Fuzhou
+1.0.5.0,1.0.5.255,CN,Fujian,,Fu\xffzhou,,26.0614,119.306
+1.0.6.0,1.0.6.255,CN,Guangdong,,Guangzhou,,23.1317,113.266
+",
+        ]
+        .concat();
+
+        assert!(Ipv4Database::from_csv(records.as_slice(), false).is_err());
+
+        let (db, report) = Ipv4Database::from_csv_lenient(records.as_slice(), false)?;
+
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.skipped, 5);
+        assert_eq!(
+            report.issues.iter().map(|i| i.line).collect::<Vec<_>>(),
+            [2, 3, 4, 5, 6]
+        );
+        assert!(report.issues[1].reason.contains("range"));
+
+        assert!(db.get("1.0.0.1".parse()?).is_some());
+        assert!(db.get("1.0.1.1".parse()?).is_none());
+        assert!(db.get("1.0.6.1".parse()?).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn issue_limit() {
+        let mut report = ImportReport::default();
+
+        for line in 0..ImportReport::MAX_ISSUES as u64 * 2 {
+            report.skip(line, "bad");
+        }
+
+        assert_eq!(report.skipped, ImportReport::MAX_ISSUES as u64 * 2);
+        assert_eq!(report.issues.len(), ImportReport::MAX_ISSUES);
+        assert!(!report.is_clean());
+    }
+}
@@ -9,10 +9,25 @@ use std::{
 use ipnet::{Ipv4Subnets, Ipv6Subnets};
 use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
 
+mod asn;
+mod audit;
+mod batch;
+mod consensus;
 mod coordinate;
+mod country;
 mod database;
 mod detect;
+mod diff;
+mod evaluate;
+mod import;
+mod overlay;
 mod reader;
+mod reverse;
+mod search;
+mod spatial;
+mod stats;
+mod translate;
+mod writer;
 
 pub mod locations;
 pub(crate) mod rkyv_impl;
@@ -20,34 +35,156 @@ pub(crate) mod rkyv_impl;
 #[cfg(feature = "download")]
 pub mod download;
 
+#[cfg(test)]
+mod testing;
+
+pub use asn::{ArchivedAsnDatabase, AsnDatabase, AsnInfo};
+pub use audit::{AuditCheck, AuditFinding, AuditReport, CountryAudit};
+pub use batch::{read_ips, write_lookups_csv};
+pub use consensus::{Consensus, consensus};
 pub use coordinate::Coordinate;
+pub use country::{Continent, Country, CountryNames, Language};
 pub use database::{
     ArchivedCombinedDatabase, ArchivedSingleDatabase, CombinedDatabase, Ipv4Database, Ipv6Database,
     SingleDatabase,
 };
-pub use detect::{ArchivedGenericDatabase, GenericDatabase, detect};
-pub use locations::{Location, LookupInfo};
+pub use detect::{
+    ArchivedGenericDatabase, DetectedDatabase, GenericDatabase, detect, detect_any, detect_asn,
+    detect_combined, detect_lenient, detect_with_language, detect_with_schema,
+};
+pub use diff::{CountryDiff, DatabaseDiff, Prefix, PrefixMove, diff};
+pub use evaluate::{Evaluation, EvaluationMiss, GroundTruth, evaluate, read_ground_truth};
+pub use import::{ImportIssue, ImportReport};
+pub use locations::{Location, LookupInfo, LookupMatch};
+pub use overlay::Site;
+pub use reader::schema::CsvSchema;
+pub use reverse::{LocationQuery, Network};
+pub use search::{SearchHit, SearchIndex};
+pub use spatial::{NearbyLocation, SpatialIndex};
+pub use stats::{CountryStats, DatabaseStats, FamilyStats};
+pub use translate::{embedded_ipv4, translate};
 pub use treebitmap;
 
 /// A generic way of addressing a [`CombinedDatabase`], [`SingleDatabase`], or [`GenericDatabase`].
 pub trait Database<Ip> {
     /// Get a [`Coordinate`]/[`Location`] pair for a given ip address.
+    ///
+    /// Addresses with another address embedded in them (see [`Database::translate`]) are looked up
+    /// by the embedded one first, falling back to the original if it isn't found.
     fn get(&self, ip: Ip) -> Option<LookupInfo> {
-        let crd = self.get_coordinate(ip)?;
+        self.get_match(ip).map(|m| m.info)
+    }
+
+    /// Like [`Database::get`], with the network prefix that matched the address.
+    ///
+    /// For translated addresses this is the network of the embedded address.
+    fn get_match(&self, ip: Ip) -> Option<LookupMatch> {
+        let ((network, crd), translated) =
+            match self.translate(&ip).and_then(|ip| self.get_network(ip)) {
+                Some(matched) => (matched, true),
+                None => (self.get_network(ip)?, false),
+            };
         let loc = self.get_location(crd)?;
 
-        Some(LookupInfo { crd, loc })
+        Some(LookupMatch {
+            info: LookupInfo {
+                crd,
+                country_level: loc.is_country_level(),
+                translated,
+                loc,
+            },
+            network,
+        })
+    }
+
+    /// [`Database::get_match`] for many addresses at once, returned in the same order.
+    ///
+    /// The addresses are looked up in sorted order so neighbouring lookups share the same
+    /// parts of the table, and large batches are split across threads if `parallel` is set.
+    fn get_batch(&self, ips: &[Ip], parallel: bool) -> Vec<Option<LookupMatch>>
+    where
+        Self: Sync,
+        Ip: Copy + Ord + Send + Sync,
+    {
+        batch::get_batch(self, ips, parallel)
+    }
+
+    /// The address to look up in place of `ip`, e.g. the IPv4 address in an IPv4-mapped IPv6 address.
+    fn translate(&self, _ip: &Ip) -> Option<Ip> {
+        None
+    }
+
+    /// Get the most specific network containing the ip address, with its coordinate.
+    fn get_network(&self, ip: Ip) -> Option<(Network, Coordinate)>;
+
+    fn get_coordinate(&self, ip: Ip) -> Option<Coordinate> {
+        self.get_network(ip).map(|(_, crd)| crd)
     }
 
-    fn get_coordinate(&self, ip: Ip) -> Option<Coordinate>;
     fn get_location(&self, crd: Coordinate) -> Option<Location>;
 }
 
+/// Search a database for the networks that resolve to a given location.
+pub trait ReverseLookup {
+    /// Get every network prefix in the database whose location matches the [`LocationQuery`].
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network>;
+
+    /// Get every network prefix in the database that resolves to one of the coordinates,
+    /// e.g. the ones a [`SpatialIndex`] found near a point.
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network>;
+}
+
+/// Search a database for the locations close to a coordinate.
+pub trait SpatialLookup {
+    /// Build a [`SpatialIndex`] over the coordinate of every location in the database.
+    ///
+    /// This reads every location, so keep the index around for repeated searches.
+    fn spatial_index(&self) -> SpatialIndex;
+}
+
+/// Search a database's locations by their city and region names.
+pub trait LocationSearch {
+    /// Build a [`SearchIndex`] over the city and region names of every location in the database.
+    ///
+    /// This reads every location and network, so keep the index around for repeated searches.
+    fn search_index(&self) -> SearchIndex;
+}
+
+/// Count what a database covers.
+pub trait Statistics {
+    /// Count the prefixes and addresses per family and country, the locations and the table sizes.
+    ///
+    /// This reads every prefix, so it's best kept around rather than recomputed.
+    fn stats(&self) -> DatabaseStats;
+}
+
+/// Check the locations of a database for common data-quality problems.
+pub trait Audit {
+    /// Flag locations at null island, at their country's centroid, outside their country,
+    /// with conflicting names, or that look more precise than they are, see [`AuditCheck`].
+    ///
+    /// This reads every location and network.
+    fn audit(&self) -> AuditReport;
+}
+
+/// Iterate over every network prefix in a database.
+pub trait Prefixes {
+    /// Every network prefix in the database with its coordinate and country, in no particular order.
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_>;
+}
+
+/// Find the autonomous system that owns an address.
+pub trait AsnLookup {
+    /// Get the [`AsnInfo`] of the most specific network containing the ip address.
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo>;
+}
+
 /// A trait representing either an `Ipv4Addr` or `Ipv6Addr` for the needs in the database.
 #[doc(hidden)]
 pub trait GenericIp:
     FromStr<Err = AddrParseError>
     + From<Self::Bits>
+    + Into<IpAddr>
     + treebitmap::Address
     + std::fmt::Debug
     + Ord
@@ -57,6 +194,8 @@ pub trait GenericIp:
 {
     type Bits: FromStr<Err = ParseIntError>;
     const FULL_NETWORK: IpNetwork;
+    /// The MaxMind DB `ip_version` of a database of these addresses.
+    const IP_VERSION: u16;
 
     fn from_str_bytes(record: &[u8]) -> Result<Self, Error> {
         Ok(str::from_utf8(record)?.parse::<Self>()?)
@@ -74,6 +213,7 @@ impl GenericIp for Ipv4Addr {
     type Bits = u32;
     const FULL_NETWORK: IpNetwork =
         IpNetwork::V4(Ipv4Network::new_checked(Ipv4Addr::UNSPECIFIED, 0).unwrap());
+    const IP_VERSION: u16 = 4;
 
     fn from_generic(ip: IpAddr) -> Option<Self> {
         match ip {
@@ -91,6 +231,7 @@ impl GenericIp for Ipv6Addr {
     type Bits = u128;
     const FULL_NETWORK: IpNetwork =
         IpNetwork::V6(Ipv6Network::new_checked(Ipv6Addr::UNSPECIFIED, 0).unwrap());
+    const IP_VERSION: u16 = 6;
 
     fn from_generic(ip: IpAddr) -> Option<Self> {
         match ip {
@@ -113,8 +254,14 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("IP addresses must either be represented as numbers or literal string representations")]
     MalformedIp,
+    #[error("IP range starts after it ends")]
+    InvalidRange,
     #[error("CSV file doesn't have enough columns")]
     NotEnoughColumns,
+    #[error("Malformed country code, expected two ASCII letters")]
+    MalformedCountryCode,
+    #[error("Malformed autonomous system number")]
+    MalformedAsn,
     #[error("Malformed coordinate: {0}")]
     CoordinateParse(#[from] ParseFloatError),
     #[error("Non-utf8 text found: {0}")]
@@ -136,4 +283,14 @@ pub enum Error {
     MalformedMaxMindDb,
     #[error("Invalid Database Format")]
     InvalidFormat,
+    #[error("Expected exactly one IPv4 and one IPv6 database")]
+    NotADatabasePair,
+    #[error("Malformed network: {0}")]
+    MalformedNetwork(#[from] ipnetwork::IpNetworkError),
+    #[error("Overlay TOML error: {0}")]
+    OverlayToml(#[from] toml::de::Error),
+    /// Overlay sites at the same coordinate must describe the same place,
+    /// and a network can only be at one coordinate.
+    #[error("Overlay sites '{0}' and '{1}' are at the same coordinate or network but differ")]
+    ConflictingSites(String, String),
 }
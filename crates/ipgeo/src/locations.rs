@@ -10,7 +10,7 @@ use rustc_hash::FxBuildHasher;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::{Coordinate, Error, coordinate::PackedCoordinate};
+use crate::{Coordinate, Country, Error, Network, coordinate::PackedCoordinate, search::fold};
 
 /// A memory-efficient store of named locations by their coordinates.
 #[derive(
@@ -29,22 +29,47 @@ pub(crate) struct LocationStore {
     pub(crate) locations: IndexSet<LocationIndices, FxBuildHasher>,
     /// Deduplicated location name strings
     pub(crate) strings: StringDict,
+    /// Later locations at an occupied coordinate with another city, which [`Self::insert`] doesn't keep.
+    ///
+    /// Read by the [`Audit`](crate::Audit), vendors disagreeing with themselves is worth knowing about.
+    pub(crate) conflicts: HashMap<PackedCoordinate, Vec<LocationIndices>, FxBuildHasher>,
 }
 
 impl LocationStore {
     /// Insert a new location into the store, only allocating/parsing/inserting strings when necessary
+    ///
+    /// A coordinate keeps its first location, later ones with another `city` are only kept as [`Self::conflicts`].
     pub(crate) fn insert(
         &mut self,
         coord: PackedCoordinate,
+        city: Option<&[u8]>,
         create_location: &dyn Fn(&mut StringDict) -> Result<LocationIndices, Error>,
     ) -> Result<(), Error> {
-        // only allocating if the location is new saves millions of parses/allocations per database.
-        if let Entry::Vacant(entry) = self.coordinates.entry(coord) {
-            entry.insert(
-                self.locations
-                    .insert_full(create_location(&mut self.strings)?)
-                    .0,
-            );
+        match self.coordinates.entry(coord) {
+            // only allocating if the location is new saves millions of parses/allocations per database.
+            Entry::Vacant(entry) => {
+                entry.insert(
+                    self.locations
+                        .insert_full(create_location(&mut self.strings)?)
+                        .0,
+                );
+            }
+            Entry::Occupied(entry) => {
+                let stored = self
+                    .locations
+                    .get_index(*entry.get())
+                    .and_then(|loc| loc.city)
+                    .and_then(|key| self.strings.get_str(key));
+
+                if conflicting_cities(stored, city) {
+                    let conflict = create_location(&mut self.strings)?;
+                    let conflicts = self.conflicts.entry(coord).or_default();
+
+                    if !conflicts.iter().any(|c| c.city == conflict.city) {
+                        conflicts.push(conflict);
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -60,6 +85,14 @@ impl LocationStore {
                 .populate(&self.strings)
         })
     }
+
+    /// Get only the country of an associated coordinate, without reading its strings.
+    pub(crate) fn country_code(&self, coord: &PackedCoordinate) -> Option<CountryCode> {
+        self.coordinates
+            .get(coord)
+            .and_then(|i| self.locations.get_index(*i))
+            .map(|loc| loc.country_code)
+    }
 }
 
 #[doc(hidden)]
@@ -103,13 +136,28 @@ impl StringDict {
     }
 
     pub fn get(&self, idx: StringDictKey) -> Option<String> {
+        self.get_str(idx).map(|s| s.to_string())
+    }
+
+    pub fn get_str(&self, idx: StringDictKey) -> Option<&str> {
         self.0
             .get_index((idx.get() - 1) as usize)
-            .map(|s| s.to_string())
+            .map(|s| s.as_str())
     }
 }
 
-/// The city and region stored as indexes into a `StringDict` database.
+/// Returns true if a city at an occupied coordinate is another place than the `stored` one,
+/// rather than the same name in another case or without its accents.
+pub(crate) fn conflicting_cities(stored: Option<&str>, city: Option<&[u8]>) -> bool {
+    match (stored, city) {
+        (Some(stored), Some(city)) if !city.is_empty() && stored.as_bytes() != city => {
+            fold(stored) != fold(&String::from_utf8_lossy(city))
+        }
+        _ => false,
+    }
+}
+
+/// The location's names stored as indexes into a `StringDict` database.
 #[derive(
     Copy,
     Clone,
@@ -127,23 +175,78 @@ pub(crate) struct LocationIndices {
     pub(crate) city: Option<StringDictKey>,
     pub(crate) region: Option<StringDictKey>,
     pub(crate) country_code: CountryCode,
+    pub(crate) subregion: Option<StringDictKey>,
+    pub(crate) postcode: Option<StringDictKey>,
+    pub(crate) timezone: Option<StringDictKey>,
 }
 
 impl LocationIndices {
+    /// Intern a record's `[region, subregion, city, postcode, timezone]` names with any string store.
+    pub(crate) fn from_names(
+        country_code: CountryCode,
+        [region, subregion, city, postcode, timezone]: [&[u8]; 5],
+        mut intern: impl FnMut(&[u8]) -> Option<StringDictKey>,
+    ) -> Self {
+        Self {
+            city: intern(city),
+            region: intern(region),
+            country_code,
+            subregion: intern(subregion),
+            postcode: intern(postcode),
+            timezone: intern(timezone),
+        }
+    }
+
+    /// Like [`Location::is_country_level`], without reading the strings.
+    pub(crate) fn is_country_level(&self) -> bool {
+        self.city.is_none()
+            && self.region.is_none()
+            && self.subregion.is_none()
+            && self.postcode.is_none()
+    }
+
     fn populate(&self, strings: &StringDict) -> Location {
+        self.populate_with(|i| strings.get(i))
+    }
+
+    /// Build the [`Location`] with strings from any store, e.g. an archived one.
+    pub(crate) fn populate_with(
+        &self,
+        string: impl Fn(StringDictKey) -> Option<String>,
+    ) -> Location {
         Location {
-            city: self.city.and_then(|i| strings.get(i)),
-            region: self.region.and_then(|i| strings.get(i)),
+            city: self.city.and_then(&string),
+            region: self.region.and_then(&string),
             country_code: self.country_code.to_string(),
+            subregion: self.subregion.and_then(&string),
+            postcode: self.postcode.and_then(&string),
+            timezone: self.timezone.and_then(&string),
+            country: self.country_code.country(),
         }
     }
 }
 
 /// A [`Coordinate`]/[`Location`] pair.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
 pub struct LookupInfo {
     pub crd: Coordinate,
     pub loc: Location,
+    /// Nothing more specific than the country is known, see [`Location::is_country_level`].
+    ///
+    /// `crd` is then usually somewhere central in the country, but that's up to the database.
+    pub country_level: bool,
+    /// The address had an IPv4 address embedded in it (IPv4-mapped, 6to4, NAT64 or Teredo),
+    /// which was looked up instead.
+    pub translated: bool,
+}
+
+/// A [`LookupInfo`] with the network prefix that matched the address, see [`Database::get_match`](crate::Database::get_match).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct LookupMatch {
+    pub info: LookupInfo,
+    /// The most specific network in the database containing the address.
+    pub network: Network,
 }
 
 impl LookupInfo {
@@ -154,12 +257,30 @@ impl LookupInfo {
 }
 
 /// A [`Coordinate`]'s associated city, region, and country.
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Type, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Location {
     pub city: Option<String>,
     pub region: Option<String>,
     pub country_code: String,
+    /// Second-level region (`state2`), e.g. a county or district.
+    pub subregion: Option<String>,
+    pub postcode: Option<String>,
+    /// IANA time zone name, e.g. `Asia/Shanghai`.
+    pub timezone: Option<String>,
+    /// Name, continent and flag of `country_code`, if it's a known ISO 3166-1 country.
+    #[serde(default)]
+    pub country: Option<Country>,
+}
+
+impl Location {
+    /// Returns true if nothing more specific than the country is known.
+    pub fn is_country_level(&self) -> bool {
+        self.city.is_none()
+            && self.region.is_none()
+            && self.subregion.is_none()
+            && self.postcode.is_none()
+    }
 }
 
 /// An ISO 3166 2-digit ASCII country code.
@@ -191,12 +312,11 @@ impl<A: AsRef<[u8]>> From<A> for CountryCode {
 
 impl fmt::Display for CountryCode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.0.to_ne_bytes() {
-            [0, 0] => "??".fmt(f),
-            [a, b] => unsafe {
-                char::from_u32_unchecked(a as u32).fmt(f)?;
-                char::from_u32_unchecked(b as u32).fmt(f)
-            },
+        let bytes = self.0.to_ne_bytes();
+
+        match str::from_utf8(&bytes) {
+            Ok(code) if self.is_well_formed() => f.pad(code),
+            _ => f.pad("??"),
         }
     }
 }
@@ -210,6 +330,7 @@ mod tests {
         assert_eq!("us", CountryCode::from("us").to_string());
         assert_eq!("us", CountryCode::from("usa").to_string());
         assert_eq!("??", CountryCode::from("!").to_string());
+        assert_eq!("??", CountryCode::from([0xff, b'a']).to_string());
     }
 
     #[test]
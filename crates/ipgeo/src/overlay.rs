@@ -0,0 +1,310 @@
+//! Hand-written CIDR to site mappings for networks no public database covers, e.g. internal offices.
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs,
+    io::Read,
+    path::Path,
+};
+
+use ipnetwork::IpNetwork;
+use serde::Deserialize;
+use treebitmap::IpLookupTable;
+
+use crate::{
+    CombinedDatabase, Coordinate, Error,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationIndices, LocationStore},
+};
+
+/// A named site and the networks that belong to it.
+///
+/// The site's name is stored as the city of its [`Location`](crate::Location).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Site {
+    pub name: String,
+    /// CIDR networks, e.g. `10.20.0.0/16` or `fd00:20::/32`.
+    pub networks: Vec<String>,
+    /// ISO 3166 2-digit country code.
+    pub country: String,
+    pub lat: f32,
+    pub lng: f32,
+    #[serde(default)]
+    pub region: Option<String>,
+    /// IANA time zone name, e.g. `Europe/Berlin`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+}
+
+impl Site {
+    /// Fill in the region and time zone of another site at the same coordinate,
+    /// as long as they describe the same place.
+    fn merge(&mut self, other: Site) -> Result<(), Error> {
+        fn agree(a: &Option<String>, b: &Option<String>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+        }
+
+        if self.name != other.name
+            || !self.country.eq_ignore_ascii_case(&other.country)
+            || !agree(&self.region, &other.region)
+            || !agree(&self.timezone, &other.timezone)
+        {
+            return Err(Error::ConflictingSites(self.name.clone(), other.name));
+        }
+
+        self.region = self.region.take().or(other.region);
+        self.timezone = self.timezone.take().or(other.timezone);
+
+        Ok(())
+    }
+}
+
+/// The layout of an overlay TOML file, a list of `[[site]]` tables.
+#[derive(Deserialize)]
+struct OverlayFile {
+    #[serde(default)]
+    site: Vec<Site>,
+}
+
+/// A single row of an overlay CSV file.
+#[derive(Deserialize)]
+struct OverlayRecord {
+    network: String,
+    name: String,
+    country: String,
+    lat: f32,
+    lng: f32,
+    #[serde(default)]
+    region: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
+}
+
+impl CombinedDatabase {
+    /// Read an overlay file, as TOML if it has a `.toml` extension and CSV otherwise.
+    ///
+    /// See [`from_overlay_toml`](Self::from_overlay_toml) and [`from_overlay_csv`](Self::from_overlay_csv)
+    /// for the formats.
+    pub fn from_overlay(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => {
+                Self::from_overlay_toml(&fs::read_to_string(path)?)
+            }
+            _ => Self::from_overlay_csv(fs::File::open(path)?),
+        }
+    }
+
+    /// Read a TOML overlay of `[[site]]` tables, see [`Site`] for their fields.
+    ///
+    /// ```toml
+    /// [[site]]
+    /// name = "Berlin office"
+    /// networks = ["10.20.0.0/16", "fd00:20::/32"]
+    /// country = "DE"
+    /// lat = 52.52
+    /// lng = 13.405
+    /// timezone = "Europe/Berlin"
+    /// ```
+    pub fn from_overlay_toml(toml: &str) -> Result<Self, Error> {
+        Self::from_sites(toml::from_str::<OverlayFile>(toml)?.site)
+    }
+
+    /// Read a headerless CSV overlay of `network,name,country,lat,lng[,region[,timezone]]` rows,
+    /// with `#` comment lines.
+    ///
+    /// ```csv
+    /// 10.20.0.0/16,Berlin office,DE,52.52,13.405,Berlin,Europe/Berlin
+    /// ```
+    pub fn from_overlay_csv(read: impl Read) -> Result<Self, Error> {
+        let mut sites = Vec::new();
+
+        for record in csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_reader(read)
+            .deserialize::<OverlayRecord>()
+        {
+            let record = record?;
+
+            sites.push(Site {
+                name: record.name,
+                networks: vec![record.network],
+                country: record.country,
+                lat: record.lat,
+                lng: record.lng,
+                region: record.region,
+                timezone: record.timezone,
+            });
+        }
+
+        Self::from_sites(sites)
+    }
+
+    /// Build an overlay database from a list of sites.
+    ///
+    /// Sites at the same coordinate are the same place, e.g. one CSV row per network,
+    /// so they must agree on the name, country, and region and time zone if both give one.
+    /// Returns [`Error::ConflictingSites`] otherwise, or if sites at different coordinates
+    /// list the same network.
+    pub fn from_sites(sites: impl IntoIterator<Item = Site>) -> Result<Self, Error> {
+        let mut ipv4 = IpLookupTable::new();
+        let mut ipv6 = IpLookupTable::new();
+        let mut places = HashMap::<PackedCoordinate, Site>::new();
+
+        for site in sites {
+            if site.country.len() != 2 || !site.country.bytes().all(|b| b.is_ascii_alphabetic()) {
+                return Err(Error::MalformedCountryCode);
+            }
+
+            let coord = PackedCoordinate::from(Coordinate {
+                lat: site.lat,
+                lng: site.lng,
+            });
+
+            for network in &site.networks {
+                let previous = match network.parse::<IpNetwork>()? {
+                    IpNetwork::V4(net) => ipv4.insert(net.network(), net.prefix().into(), coord),
+                    IpNetwork::V6(net) => ipv6.insert(net.network(), net.prefix().into(), coord),
+                };
+
+                // sites at other coordinates were added to the places already
+                if let Some(previous) = previous.filter(|previous| *previous != coord) {
+                    return Err(Error::ConflictingSites(
+                        places[&previous].name.clone(),
+                        site.name,
+                    ));
+                }
+            }
+
+            match places.entry(coord) {
+                Entry::Vacant(entry) => {
+                    entry.insert(site);
+                }
+                Entry::Occupied(mut entry) => entry.get_mut().merge(site)?,
+            }
+        }
+
+        let mut locations = LocationStore::default();
+        for (coord, site) in places {
+            locations.insert(coord, Some(site.name.as_bytes()), &|strings| {
+                Ok(LocationIndices {
+                    city: strings.insert_bytes(site.name.as_bytes()),
+                    region: strings
+                        .insert_bytes(site.region.as_deref().unwrap_or_default().as_bytes()),
+                    country_code: CountryCode::from(site.country.to_ascii_uppercase()),
+                    subregion: None,
+                    postcode: None,
+                    timezone: strings
+                        .insert_bytes(site.timezone.as_deref().unwrap_or_default().as_bytes()),
+                })
+            })?;
+        }
+
+        if ipv4.is_empty() && ipv6.is_empty() {
+            return Err(Error::NoRecords);
+        }
+
+        Ok(Self {
+            ipv4,
+            ipv6,
+            locations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::IpAddr};
+
+    use crate::{CombinedDatabase, Database, Error};
+
+    const TOML: &str = r#"
+[[site]]
+name = "Berlin office"
+networks = ["10.20.0.0/16", "fd00:20::/32"]
+country = "DE"
+lat = 52.52
+lng = 13.405
+timezone = "Europe/Berlin"
+
+[[site]]
+name = "Berlin lab"
+networks = ["10.20.30.0/24"]
+country = "de"
+lat = 52.51
+lng = 13.39
+"#;
+
+    const CSV: &str = "# network,name,country,lat,lng,region,timezone
+10.20.0.0/16,Berlin office,DE,52.52,13.405,,Europe/Berlin
+fd00:20::/32,Berlin office,DE,52.52,13.405
+10.20.30.0/24, Berlin lab ,de,52.51,13.39
+";
+
+    #[test]
+    fn sites() -> Result<(), Box<dyn error::Error>> {
+        for db in [
+            CombinedDatabase::from_overlay_toml(TOML)?,
+            CombinedDatabase::from_overlay_csv(CSV.as_bytes())?,
+        ] {
+            let office = db.get("10.20.1.1".parse::<IpAddr>()?).expect("office");
+            assert_eq!(office.loc.city.as_deref(), Some("Berlin office"));
+            assert_eq!(office.loc.country_code, "DE");
+            assert_eq!(office.loc.timezone.as_deref(), Some("Europe/Berlin"));
+            assert!(!office.country_level);
+
+            // the most specific network wins
+            let lab = db.get("10.20.30.1".parse::<IpAddr>()?).expect("lab");
+            assert_eq!(lab.loc.city.as_deref(), Some("Berlin lab"));
+            assert_eq!(lab.loc.country_code, "DE");
+
+            let ipv6 = db.get("fd00:20::1".parse::<IpAddr>()?).expect("ipv6");
+            assert_eq!(ipv6.loc, office.loc);
+
+            assert!(db.get("10.21.0.1".parse::<IpAddr>()?).is_none());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(matches!(
+            CombinedDatabase::from_overlay_csv("10.20.0.0/33,Berlin,DE,52.52,13.405".as_bytes()),
+            Err(Error::MalformedNetwork(_))
+        ));
+        assert!(matches!(
+            CombinedDatabase::from_overlay_csv(
+                "10.20.0.0/16,Berlin,Germany,52.52,13.405".as_bytes()
+            ),
+            Err(Error::MalformedCountryCode)
+        ));
+        assert!(matches!(
+            CombinedDatabase::from_overlay_toml(""),
+            Err(Error::NoRecords)
+        ));
+        assert!(matches!(
+            CombinedDatabase::from_overlay_csv(
+                "10.20.0.0/16,Berlin office,DE,52.52,13.405
+10.30.0.0/16,Berlin lab,DE,52.52,13.405"
+                    .as_bytes()
+            ),
+            Err(Error::ConflictingSites(..))
+        ));
+        assert!(matches!(
+            CombinedDatabase::from_overlay_csv(
+                "10.20.0.0/16,Berlin office,DE,52.52,13.405
+10.20.0.0/16,Berlin lab,DE,52.51,13.39"
+                    .as_bytes()
+            ),
+            Err(Error::ConflictingSites(..))
+        ));
+    }
+}
@@ -1,32 +1,45 @@
-use std::{io::Read, ops::Index};
+use std::io::Read;
 
-use compact_str::CompactString;
-use csv::ByteRecord;
 use treebitmap::IpLookupTable;
 
 use crate::{
-    Coordinate, Error, GenericIp,
+    CsvSchema, Error, GenericIp, ImportReport,
+    asn::{AsnKey, AsnStore},
     coordinate::PackedCoordinate,
     locations::{CountryCode, LocationIndices, LocationStore},
+    reader::schema::CsvRecord,
 };
 
-/// CSV indexes for city-ipv[4/6][-num].csv format
+/// CSV indexes for city-ipv[4/6][-num].csv format, see [`CsvSchema::SAPICS`].
 /// https://github.com/sapics/ip-location-db?tab=readme-ov-file#city-csv-format
-pub const NUM_RECORDS: usize = 9;
-
 pub const IP_RANGE_START_IDX: usize = 0;
 pub const IP_RANGE_END_IDX: usize = 1;
 pub const COUNTRY_CODE_IDX: usize = 2;
 pub const REGION_IDX: usize = 3;
+pub const SUBREGION_IDX: usize = 4;
 pub const CITY_IDX: usize = 5;
+pub const POSTCODE_IDX: usize = 6;
 pub const LATITUDE_IDX: usize = 7;
 pub const LONGITUDE_IDX: usize = 8;
+/// Optional trailing column, not every file has it.
+pub const TIMEZONE_IDX: usize = 9;
+
+/// CSV indexes for asn-ipv[4/6][-num].csv format
+/// https://github.com/sapics/ip-location-db?tab=readme-ov-file#asn-csv-format
+pub const ASN_NUM_RECORDS: usize = 3;
+
+pub const ASN_IDX: usize = 2;
+/// Optional trailing column, empty or missing when the organization is unknown.
+pub const ASN_ORGANIZATION_IDX: usize = 3;
 
+/// Read a city CSV file, skipping malformed records into `report` if one is given.
 pub fn read<Ip: GenericIp>(
     read: impl Read,
     is_num: bool,
+    schema: &CsvSchema,
+    mut report: Option<&mut ImportReport>,
     ips: &mut IpLookupTable<Ip, PackedCoordinate>,
-    locations: &mut LocationStore,
+    locations: &mut impl LocationSink,
 ) -> Result<(), crate::Error> {
     let ip_parser = if is_num {
         Ip::from_num_bytes
@@ -35,50 +48,136 @@ pub fn read<Ip: GenericIp>(
     };
 
     for record in csv::ReaderBuilder::new()
-        .has_headers(false)
+        .has_headers(schema.has_headers)
         .from_reader(read)
         .byte_records()
     {
-        read_record(&record?, ip_parser, ips, locations)?;
+        let Some(report) = report.as_deref_mut() else {
+            read_record(&record?, schema, ip_parser, ips, locations)?;
+            continue;
+        };
+
+        let line = match &record {
+            Ok(record) => record.position(),
+            Err(err) => err.position(),
+        }
+        .map(|pos| pos.line())
+        .unwrap_or_default();
+
+        report.record(
+            line,
+            record
+                .map_err(Error::from)
+                .and_then(|record| read_record(&record, schema, ip_parser, ips, locations)),
+        )?;
     }
 
     Ok(())
 }
 
-pub fn coord_from_record(
-    record: &impl Index<usize, Output = [u8]>,
-) -> Result<Coordinate, crate::Error> {
-    Ok(Coordinate {
-        lat: CompactString::from_utf8(&record[LATITUDE_IDX])?.parse::<f32>()?,
-        lng: CompactString::from_utf8(&record[LONGITUDE_IDX])?.parse::<f32>()?,
-    })
+/// Where [`read_record`] puts the locations it reads, so the same parsing serves a [`LocationStore`]
+/// and the concurrent store databases are downloaded into.
+pub trait LocationSink {
+    /// Add the location of a coordinate from its country and
+    /// `[region, subregion, city, postcode, timezone]` names, unless it already has one.
+    fn insert_names(
+        &mut self,
+        coord: PackedCoordinate,
+        country_code: CountryCode,
+        names: [&[u8]; 5],
+    ) -> Result<(), Error>;
+}
+
+impl LocationSink for LocationStore {
+    fn insert_names(
+        &mut self,
+        coord: PackedCoordinate,
+        country_code: CountryCode,
+        names: [&[u8]; 5],
+    ) -> Result<(), Error> {
+        self.insert(coord, Some(names[2]), &|strings| {
+            Ok(LocationIndices::from_names(country_code, names, |name| {
+                strings.insert_bytes(name)
+            }))
+        })
+    }
 }
 
 pub fn read_record<Ip: GenericIp>(
-    record: &ByteRecord,
+    record: &impl CsvRecord,
+    schema: &CsvSchema,
     ip_parser: fn(&[u8]) -> Result<Ip, crate::Error>,
     ips: &mut IpLookupTable<Ip, PackedCoordinate>,
-    locations: &mut LocationStore,
+    locations: &mut impl LocationSink,
 ) -> Result<(), crate::Error> {
-    if record.len() < NUM_RECORDS {
-        return Err(Error::NotEnoughColumns);
+    let Some(coord) = schema.coordinate(record)? else {
+        return Ok(());
+    };
+    let coord = PackedCoordinate::from(coord);
+
+    let (start, end) = schema.range(record, ip_parser)?;
+    let country_code = schema.country_code(record)?;
+    locations.insert_names(coord, country_code, schema.names(record)?)?;
+
+    for (addr, len) in Ip::range_subnets(start, end) {
+        ips.insert(addr, len, coord);
     }
 
-    let coord: PackedCoordinate = coord_from_record(record)?.into();
+    Ok(())
+}
 
-    locations.insert(coord, &|strings| {
-        Ok(LocationIndices {
-            city: strings.insert_bytes(&record[CITY_IDX]),
-            region: strings.insert_bytes(&record[REGION_IDX]),
-            country_code: CountryCode::from(&record[COUNTRY_CODE_IDX]),
-        })
-    })?;
+pub fn read_asn<Ip: GenericIp>(
+    read: impl Read,
+    is_num: bool,
+    ips: &mut IpLookupTable<Ip, AsnKey>,
+    systems: &mut AsnStore,
+) -> Result<(), crate::Error> {
+    let ip_parser = if is_num {
+        Ip::from_num_bytes
+    } else {
+        Ip::from_str_bytes
+    };
+
+    for record in csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(read)
+        .byte_records()
+    {
+        read_asn_record(&record?, ip_parser, ips, systems)?;
+    }
+
+    Ok(())
+}
+
+pub fn asn_from_record(record: &impl CsvRecord) -> Result<u32, crate::Error> {
+    let asn = record.field(ASN_IDX).ok_or(Error::NotEnoughColumns)?;
+
+    str::from_utf8(asn)?
+        .parse::<u32>()
+        .map_err(|_| Error::MalformedAsn)
+}
+
+pub fn read_asn_record<Ip: GenericIp>(
+    record: &impl CsvRecord,
+    ip_parser: fn(&[u8]) -> Result<Ip, crate::Error>,
+    ips: &mut IpLookupTable<Ip, AsnKey>,
+    systems: &mut AsnStore,
+) -> Result<(), crate::Error> {
+    let field = |idx| record.field(idx).ok_or(Error::NotEnoughColumns);
+
+    field(ASN_NUM_RECORDS - 1)?;
+
+    let key = systems.insert(
+        asn_from_record(record)?,
+        record.field(ASN_ORGANIZATION_IDX).unwrap_or_default(),
+    );
 
     for (addr, len) in Ip::range_subnets(
-        ip_parser(&record[IP_RANGE_START_IDX])?,
-        ip_parser(&record[IP_RANGE_END_IDX])?,
+        ip_parser(field(IP_RANGE_START_IDX)?)?,
+        ip_parser(field(IP_RANGE_END_IDX)?)?,
     ) {
-        ips.insert(addr, len, coord);
+        ips.insert(addr, len, key);
     }
 
     Ok(())
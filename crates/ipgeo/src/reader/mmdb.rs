@@ -1,41 +1,241 @@
+use std::net::Ipv4Addr;
+
 use compact_str::CompactString;
+use ipnetwork::IpNetwork;
 use maxminddb::{LookupResult, PathElement, Reader, WithinOptions};
 use serde::Deserialize;
 use treebitmap::IpLookupTable;
 
 use crate::{
     Coordinate, Error, GenericIp,
+    asn::{AsnKey, AsnStore},
     coordinate::PackedCoordinate,
     locations::{CountryCode, LocationIndices, LocationStore},
 };
 
+/// The default language of the `names` maps in MaxMind's GeoIP2/GeoLite2 databases.
+pub(crate) const DEFAULT_LANGUAGE: &str = "en";
+
+/// The record layout of a MaxMind DB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Schema {
+    /// ip-location-db's flat top-level keys, e.g. `latitude`, `city`, `state1`.
+    Flat,
+    /// MaxMind's nested GeoIP2/GeoLite2 layout, e.g. `location.latitude`, `city.names.en`.
+    GeoIp2,
+}
+
+impl Schema {
+    /// Detect the layout from the metadata's `database_type`, e.g. `GeoLite2-City`.
+    pub(crate) fn detect(database_type: &str) -> Self {
+        // DB-IP's own mmdb releases follow MaxMind's layout too.
+        const GEOIP2_PREFIXES: [&str; 3] = ["GeoIP2-", "GeoLite2-", "DBIP-"];
+
+        if GEOIP2_PREFIXES.iter().any(|p| database_type.starts_with(p)) {
+            Self::GeoIp2
+        } else {
+            Self::Flat
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Field {
+    City,
+    Region,
+    Subregion,
+    Postcode,
+    Timezone,
+}
+
+/// Decodes the fields of a record in a given [`Schema`] and language.
+struct Fields<'a> {
+    schema: Schema,
+    language: &'a str,
+}
+
+impl Fields<'_> {
+    fn country_code<L: AsRef<[u8]>>(
+        &self,
+        lr: &LookupResult<'_, L>,
+    ) -> Result<Option<CompactString>, Error> {
+        match self.schema {
+            Schema::Flat => decode(lr, &[PathElement::Key("country_code")]).map(Some),
+            // anycast and satellite networks may only have a registered country, or just a continent.
+            Schema::GeoIp2 => Ok(
+                match decode_optional(
+                    lr,
+                    &[PathElement::Key("country"), PathElement::Key("iso_code")],
+                )? {
+                    Some(code) => Some(code),
+                    None => decode_optional(
+                        lr,
+                        &[
+                            PathElement::Key("registered_country"),
+                            PathElement::Key("iso_code"),
+                        ],
+                    )?,
+                },
+            ),
+        }
+    }
+
+    fn coordinate<L: AsRef<[u8]>>(
+        &self,
+        lr: &LookupResult<'_, L>,
+    ) -> Result<Option<Coordinate>, Error> {
+        let (lat, lng) = match self.schema {
+            Schema::Flat => (
+                decode_optional(lr, &[PathElement::Key("latitude")])?,
+                decode_optional(lr, &[PathElement::Key("longitude")])?,
+            ),
+            Schema::GeoIp2 => (
+                decode_optional(
+                    lr,
+                    &[PathElement::Key("location"), PathElement::Key("latitude")],
+                )?,
+                decode_optional(
+                    lr,
+                    &[PathElement::Key("location"), PathElement::Key("longitude")],
+                )?,
+            ),
+        };
+
+        Ok(lat.zip(lng).map(|(lat, lng)| Coordinate { lat, lng }))
+    }
+
+    fn string<L: AsRef<[u8]>>(
+        &self,
+        lr: &LookupResult<'_, L>,
+        field: Field,
+    ) -> Result<Option<CompactString>, Error> {
+        use PathElement::{Index, Key};
+
+        match (self.schema, field) {
+            (Schema::Flat, Field::City) => decode_optional(lr, &[Key("city")]),
+            (Schema::Flat, Field::Region) => decode_optional(lr, &[Key("state1")]),
+            (Schema::Flat, Field::Subregion) => decode_optional(lr, &[Key("state2")]),
+            (Schema::Flat, Field::Postcode) => decode_optional(lr, &[Key("postcode")]),
+            (Schema::Flat, Field::Timezone) => decode_optional(lr, &[Key("timezone")]),
+            (Schema::GeoIp2, Field::City) => self.name(lr, &[Key("city")]),
+            (Schema::GeoIp2, Field::Region) => self.name(lr, &[Key("subdivisions"), Index(0)]),
+            (Schema::GeoIp2, Field::Subregion) => self.name(lr, &[Key("subdivisions"), Index(1)]),
+            (Schema::GeoIp2, Field::Postcode) => decode_optional(lr, &[Key("postal"), Key("code")]),
+            (Schema::GeoIp2, Field::Timezone) => {
+                decode_optional(lr, &[Key("location"), Key("time_zone")])
+            }
+        }
+    }
+
+    /// Get the name of a GeoIP2 object in the chosen language, falling back to English.
+    fn name<L: AsRef<[u8]>>(
+        &self,
+        lr: &LookupResult<'_, L>,
+        object: &[PathElement<'_>],
+    ) -> Result<Option<CompactString>, Error> {
+        for language in [self.language, DEFAULT_LANGUAGE] {
+            let path = [
+                object,
+                &[PathElement::Key("names"), PathElement::Key(language)],
+            ]
+            .concat();
+
+            if let Some(name) = decode_optional(lr, &path)? {
+                return Ok(Some(name));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Whether an IPv6 database also holds IPv4 networks in its `::/96` subtree.
+///
+/// MaxMind's databases (and [`CombinedDatabase::write_mmdb`](crate::CombinedDatabase::write_mmdb))
+/// alias `::ffff:0:0/96` and other IPv4 translation prefixes to that same subtree.
+pub fn has_ipv4<S: AsRef<[u8]>>(reader: &Reader<S>) -> bool {
+    reader.metadata.ip_version == 6
+        && reader
+            .within(Ipv4Addr::FULL_NETWORK, WithinOptions::default())
+            .is_ok_and(|mut networks| networks.next().is_some())
+}
+
+/// Returns true if the database holds autonomous systems rather than locations,
+/// e.g. `GeoLite2-ASN` or ip-location-db's `asn` databases.
+pub fn is_asn<S: AsRef<[u8]>>(reader: &Reader<S>) -> bool {
+    reader
+        .metadata
+        .database_type
+        .to_ascii_lowercase()
+        .contains("asn")
+}
+
+/// IPv4 networks in an IPv6 database, which are read separately with an IPv4 table.
+///
+/// maxminddb returns networks in the `::/96` subtree as IPv4 networks.
+fn is_ipv4_subtree<Ip: GenericIp>(net: &IpNetwork) -> bool {
+    Ip::IP_VERSION == 6
+        && match net {
+            IpNetwork::V4(_) => true,
+            IpNetwork::V6(net) => net.prefix() >= 96 && net.ip().to_bits() >> 32 == 0,
+        }
+}
+
 pub fn read<Ip: GenericIp, S: AsRef<[u8]>>(
-    reader: Reader<S>,
+    reader: &Reader<S>,
+    language: &str,
     ips: &mut IpLookupTable<Ip, PackedCoordinate>,
     locations: &mut LocationStore,
 ) -> Result<(), Error> {
+    let fields = Fields {
+        schema: Schema::detect(&reader.metadata.database_type),
+        language,
+    };
+
     for res in reader
         .within(Ip::FULL_NETWORK, WithinOptions::default())
         .map_err(Error::MaxMindDb)?
     {
         let lookup = res.map_err(Error::MaxMindDb)?;
         let net = lookup.network().map_err(Error::MaxMindDb)?;
-        let ip = Ip::from_generic(net.ip()).ok_or(Error::MalformedMaxMindDb)?;
 
-        let coord: PackedCoordinate = Coordinate {
-            lat: decode::<_, f32>(&lookup, "latitude")?,
-            lng: decode::<_, f32>(&lookup, "longitude")?,
+        if is_ipv4_subtree::<Ip>(&net) {
+            continue;
         }
-        .into();
 
-        locations.insert(coord, &|strings| {
+        let ip = Ip::from_generic(net.ip()).ok_or(Error::MalformedMaxMindDb)?;
+
+        let Some(country_code) = fields.country_code(&lookup)?.map(CountryCode::from) else {
+            continue;
+        };
+
+        // country-only databases have no coordinates, so they're placed at the country's centroid.
+        let coord = match fields.coordinate(&lookup)? {
+            Some(crd) => crd,
+            None if !country_code.is_well_formed() => return Err(Error::MalformedCountryCode),
+            None => match country_code.centroid() {
+                Some(crd) => crd,
+                None => continue,
+            },
+        };
+        let coord = PackedCoordinate::from(coord);
+
+        let city = fields.string(&lookup, Field::City)?;
+
+        locations.insert(coord, city.as_ref().map(|c| c.as_bytes()), &|strings| {
+            let mut string = |field| -> Result<_, Error> {
+                Ok(fields
+                    .string(&lookup, field)?
+                    .and_then(|s| strings.insert_str(s)))
+            };
+
             Ok(LocationIndices {
-                city: strings.insert_str(decode::<_, CompactString>(&lookup, "city")?),
-                region: strings.insert_str(decode::<_, CompactString>(&lookup, "state1")?),
-                country_code: CountryCode::from(decode::<_, CompactString>(
-                    &lookup,
-                    "country_code",
-                )?),
+                city: string(Field::City)?,
+                region: string(Field::Region)?,
+                country_code,
+                subregion: string(Field::Subregion)?,
+                postcode: string(Field::Postcode)?,
+                timezone: string(Field::Timezone)?,
             })
         })?;
 
@@ -45,11 +245,195 @@ pub fn read<Ip: GenericIp, S: AsRef<[u8]>>(
     Ok(())
 }
 
+pub fn read_asn<Ip: GenericIp, S: AsRef<[u8]>>(
+    reader: &Reader<S>,
+    ips: &mut IpLookupTable<Ip, AsnKey>,
+    systems: &mut AsnStore,
+) -> Result<(), Error> {
+    for res in reader
+        .within(Ip::FULL_NETWORK, WithinOptions::default())
+        .map_err(Error::MaxMindDb)?
+    {
+        let lookup = res.map_err(Error::MaxMindDb)?;
+        let net = lookup.network().map_err(Error::MaxMindDb)?;
+
+        if is_ipv4_subtree::<Ip>(&net) {
+            continue;
+        }
+
+        let ip = Ip::from_generic(net.ip()).ok_or(Error::MalformedMaxMindDb)?;
+
+        let key = systems.insert(
+            decode::<_, u32>(&lookup, &[PathElement::Key("autonomous_system_number")])?,
+            decode_optional::<_, CompactString>(
+                &lookup,
+                &[PathElement::Key("autonomous_system_organization")],
+            )?
+            .unwrap_or_default()
+            .as_bytes(),
+        );
+
+        ips.insert(ip, net.prefix().into(), key);
+    }
+
+    Ok(())
+}
+
 fn decode<'a, L: AsRef<[u8]>, T: Deserialize<'a>>(
     lr: &LookupResult<'a, L>,
-    p: &'static str,
+    path: &[PathElement<'_>],
 ) -> Result<T, Error> {
-    lr.decode_path(&[PathElement::Key(p)])
+    lr.decode_path(path)
         .map_err(Error::MaxMindDb)
         .and_then(|r| r.ok_or(Error::MalformedMaxMindDb))
 }
+
+/// Like [`decode`], but for fields that aren't present in every record.
+fn decode_optional<'a, L: AsRef<[u8]>, T: Deserialize<'a>>(
+    lr: &LookupResult<'a, L>,
+    path: &[PathElement<'_>],
+) -> Result<Option<T>, Error> {
+    lr.decode_path(path).map_err(Error::MaxMindDb)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::Ipv4Addr};
+
+    use crate::{
+        Database, Ipv4Database,
+        writer::mmdb::{ARRAY, DataSection, MAP, write_records},
+    };
+
+    use super::Schema;
+
+    fn names(data: &mut DataSection, names: &[(&str, &str)]) {
+        data.string("names");
+        data.control(MAP, names.len());
+
+        for (language, name) in names {
+            data.string(language);
+            data.string(name);
+        }
+    }
+
+    /// A record in MaxMind's nested GeoLite2-City layout.
+    fn geolite2_city(data: &mut DataSection) {
+        data.control(MAP, 5);
+
+        data.string("city");
+        data.control(MAP, 1);
+        names(data, &[("en", "Munich"), ("de", "München")]);
+
+        data.string("country");
+        data.control(MAP, 1);
+        data.string("iso_code");
+        data.string("DE");
+
+        data.string("location");
+        data.control(MAP, 3);
+        data.string("latitude");
+        data.double(48.1374);
+        data.string("longitude");
+        data.double(11.5755);
+        data.string("time_zone");
+        data.string("Europe/Berlin");
+
+        data.string("postal");
+        data.control(MAP, 1);
+        data.string("code");
+        data.string("80331");
+
+        data.string("subdivisions");
+        data.control(ARRAY, 2);
+        data.control(MAP, 1);
+        names(data, &[("en", "Bavaria"), ("de", "Bayern")]);
+        data.control(MAP, 1);
+        names(data, &[("en", "Upper Bavaria")]);
+    }
+
+    /// A record with only a registered country, like an anycast network in GeoLite2-Country.
+    fn geolite2_country(data: &mut DataSection) {
+        data.control(MAP, 1);
+        data.string("registered_country");
+        data.control(MAP, 1);
+        data.string("iso_code");
+        data.string("FR");
+    }
+
+    fn geolite2_db(language: &str) -> Result<Ipv4Database, Box<dyn error::Error>> {
+        let networks = [
+            (Ipv4Addr::new(2, 0, 0, 0), 16, 0u8),
+            (Ipv4Addr::new(3, 0, 0, 0), 16, 1u8),
+            (Ipv4Addr::new(4, 0, 0, 0), 16, 2u8),
+        ];
+
+        let mut out = Vec::new();
+        write_records(
+            &mut out,
+            4,
+            "GeoLite2-City",
+            networks.into_iter().map(|(ip, len, k)| (ip.into(), len, k)),
+            |data, k| match k {
+                0 => geolite2_city(data),
+                1 => geolite2_country(data),
+                // continent only, nothing we can place
+                _ => {
+                    data.control(MAP, 1);
+                    data.string("continent");
+                    data.control(MAP, 1);
+                    data.string("code");
+                    data.string("EU");
+                }
+            },
+        )?;
+
+        let reader = maxminddb::Reader::from_source(out)?;
+        Ok(Ipv4Database::from_mmdb_with_language(reader, language)?)
+    }
+
+    #[test]
+    fn schema_detection() {
+        assert_eq!(Schema::detect("GeoLite2-City"), Schema::GeoIp2);
+        assert_eq!(Schema::detect("GeoIP2-Country"), Schema::GeoIp2);
+        assert_eq!(Schema::detect("DBIP-City-Lite"), Schema::GeoIp2);
+        assert_eq!(Schema::detect("ipgeo-city"), Schema::Flat);
+        assert_eq!(Schema::detect("ip-location-db"), Schema::Flat);
+    }
+
+    #[test]
+    fn geolite2_layout() -> Result<(), Box<dyn error::Error>> {
+        let db = geolite2_db("en")?;
+
+        let info = db.get(Ipv4Addr::new(2, 0, 1, 1)).expect("city record");
+        assert!((info.crd.lat - 48.1374).abs() < 0.01);
+        assert_eq!(info.loc.country_code.as_str(), "DE");
+        assert_eq!(info.loc.city.as_deref(), Some("Munich"));
+        assert_eq!(info.loc.region.as_deref(), Some("Bavaria"));
+        assert_eq!(info.loc.subregion.as_deref(), Some("Upper Bavaria"));
+        assert_eq!(info.loc.postcode.as_deref(), Some("80331"));
+        assert_eq!(info.loc.timezone.as_deref(), Some("Europe/Berlin"));
+
+        let info = db.get(Ipv4Addr::new(3, 0, 1, 1)).expect("country record");
+        assert_eq!(info.loc.country_code.as_str(), "FR");
+        assert!(info.country_level);
+
+        assert!(db.get(Ipv4Addr::new(4, 0, 1, 1)).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn geolite2_language() -> Result<(), Box<dyn error::Error>> {
+        let info = geolite2_db("de")?
+            .get(Ipv4Addr::new(2, 0, 1, 1))
+            .expect("city record");
+
+        assert_eq!(info.loc.city.as_deref(), Some("München"));
+        assert_eq!(info.loc.region.as_deref(), Some("Bayern"));
+        // falls back to English
+        assert_eq!(info.loc.subregion.as_deref(), Some("Upper Bavaria"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,394 @@
+//! Column layouts of city CSV databases.
+
+use crate::{Coordinate, Error, GenericIp, locations::CountryCode, reader::csv::*};
+
+/// Which column holds each field of a city CSV database.
+///
+/// Presets are included for common vendors, and [`CsvSchema::from_headers`] finds the columns
+/// from a header row. Anything else can be built up column by column:
+///
+/// ```
+/// use ipgeo::CsvSchema;
+///
+/// // ip_from,ip_to,country,city,lat,lng,asn
+/// let schema = CsvSchema::new(0, 1, 2)
+///     .city(3)
+///     .coordinates(4, 5)
+///     .with_headers(true);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CsvSchema {
+    pub(crate) has_headers: bool,
+    pub(crate) ip_range_start: usize,
+    pub(crate) ip_range_end: usize,
+    pub(crate) country_code: usize,
+    pub(crate) region: Option<usize>,
+    pub(crate) subregion: Option<usize>,
+    pub(crate) city: Option<usize>,
+    pub(crate) postcode: Option<usize>,
+    pub(crate) latitude: Option<usize>,
+    pub(crate) longitude: Option<usize>,
+    pub(crate) timezone: Option<usize>,
+}
+
+impl Default for CsvSchema {
+    fn default() -> Self {
+        Self::SAPICS
+    }
+}
+
+impl CsvSchema {
+    /// `sapics/ip-location-db` city (and country) CSVs, which [`detect`](crate::detect) assumes without a header.
+    ///
+    /// `ip_range_start,ip_range_end,country_code,state1,state2,city,postcode,latitude,longitude,timezone`
+    pub const SAPICS: Self = Self::new(IP_RANGE_START_IDX, IP_RANGE_END_IDX, COUNTRY_CODE_IDX)
+        .region(REGION_IDX)
+        .subregion(SUBREGION_IDX)
+        .city(CITY_IDX)
+        .postcode(POSTCODE_IDX)
+        .coordinates(LATITUDE_IDX, LONGITUDE_IDX)
+        .timezone(TIMEZONE_IDX);
+
+    /// IP2Location LITE DB5, with numeric IPs.
+    ///
+    /// `ip_from,ip_to,country_code,country_name,region_name,city_name,latitude,longitude`
+    pub const IP2LOCATION_DB5: Self = Self::new(0, 1, 2).region(4).city(5).coordinates(6, 7);
+
+    /// IP2Location LITE DB11, DB5 with postal codes and UTC offsets.
+    ///
+    /// The `time_zone` column is an offset like `+10:00` rather than an IANA name, so it isn't kept.
+    pub const IP2LOCATION_DB11: Self = Self::IP2LOCATION_DB5.postcode(8);
+
+    /// DB-IP's own "IP to City Lite" CSV.
+    ///
+    /// `ip_start,ip_end,continent,country,stateprov,city,latitude,longitude`
+    pub const DBIP_CITY_LITE: Self = Self::new(0, 1, 3).region(4).city(5).coordinates(6, 7);
+
+    /// A schema with only the IP range and the country, so every network is placed at its country's centroid.
+    pub const fn new(ip_range_start: usize, ip_range_end: usize, country_code: usize) -> Self {
+        Self {
+            has_headers: false,
+            ip_range_start,
+            ip_range_end,
+            country_code,
+            region: None,
+            subregion: None,
+            city: None,
+            postcode: None,
+            latitude: None,
+            longitude: None,
+            timezone: None,
+        }
+    }
+
+    /// Skip the first row of the file.
+    pub const fn with_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    pub const fn region(mut self, idx: usize) -> Self {
+        self.region = Some(idx);
+        self
+    }
+
+    pub const fn subregion(mut self, idx: usize) -> Self {
+        self.subregion = Some(idx);
+        self
+    }
+
+    pub const fn city(mut self, idx: usize) -> Self {
+        self.city = Some(idx);
+        self
+    }
+
+    pub const fn postcode(mut self, idx: usize) -> Self {
+        self.postcode = Some(idx);
+        self
+    }
+
+    pub const fn coordinates(mut self, latitude: usize, longitude: usize) -> Self {
+        self.latitude = Some(latitude);
+        self.longitude = Some(longitude);
+        self
+    }
+
+    /// An IANA time zone name like `Europe/Berlin`.
+    pub const fn timezone(mut self, idx: usize) -> Self {
+        self.timezone = Some(idx);
+        self
+    }
+
+    /// Find the columns from a header row by their (case, space and underscore insensitive) names,
+    /// e.g. `ip_from`, `Start IP`, `country_code`, `stateprov`, `lat`, `time_zone`.
+    ///
+    /// Returns `None` unless the IP range and country columns are all found.
+    pub fn from_headers<'a>(headers: impl IntoIterator<Item = &'a [u8]>) -> Option<Self> {
+        #[rustfmt::skip]
+        const NAMES: [(Column, &[&str]); 10] = [
+            (Column::IpRangeStart, &["iprangestart", "ipstart", "startip", "ipfrom", "rangestart", "firstip", "start", "from"]),
+            (Column::IpRangeEnd, &["iprangeend", "ipend", "endip", "ipto", "rangeend", "lastip", "end", "to"]),
+            (Column::CountryCode, &["countrycode", "country", "countryisocode", "isocode", "cc"]),
+            (Column::Region, &["region", "regionname", "state1", "stateprov", "state", "province", "subdivision1name"]),
+            (Column::Subregion, &["subregion", "state2", "district", "county", "subdivision2name"]),
+            (Column::City, &["city", "cityname"]),
+            (Column::Postcode, &["postcode", "postalcode", "zipcode", "zip", "postal"]),
+            (Column::Latitude, &["latitude", "lat"]),
+            (Column::Longitude, &["longitude", "lng", "lon", "long"]),
+            (Column::Timezone, &["timezone", "tz", "ianatimezone"]),
+        ];
+
+        let mut columns: [Option<usize>; NAMES.len()] = [None; NAMES.len()];
+
+        for (idx, header) in headers.into_iter().enumerate() {
+            let name = normalize(header);
+
+            if let Some(i) = NAMES
+                .iter()
+                .position(|(_, aliases)| aliases.contains(&name.as_str()))
+                && columns[i].is_none()
+            {
+                columns[i] = Some(idx);
+            }
+        }
+
+        let column = |c: Column| columns[c as usize];
+
+        let mut schema = Self::new(
+            column(Column::IpRangeStart)?,
+            column(Column::IpRangeEnd)?,
+            column(Column::CountryCode)?,
+        )
+        .with_headers(true);
+
+        schema.region = column(Column::Region);
+        schema.subregion = column(Column::Subregion);
+        schema.city = column(Column::City);
+        schema.postcode = column(Column::Postcode);
+        schema.timezone = column(Column::Timezone);
+
+        if let (Some(lat), Some(lng)) = (column(Column::Latitude), column(Column::Longitude)) {
+            schema = schema.coordinates(lat, lng);
+        }
+
+        Some(schema)
+    }
+
+    /// Get a column of a record, empty if the column isn't in the schema or the record.
+    pub(crate) fn get<'r>(&self, record: &'r impl CsvRecord, idx: Option<usize>) -> &'r [u8] {
+        idx.and_then(|idx| record.field(idx)).unwrap_or_default()
+    }
+
+    pub(crate) fn country_code(&self, record: &impl CsvRecord) -> Result<CountryCode, Error> {
+        match record.field(self.country_code) {
+            Some(code) => Ok(CountryCode::from(code)),
+            None => Err(Error::NotEnoughColumns),
+        }
+    }
+
+    /// Parse the record's coordinate, or its country's centroid if it doesn't have one.
+    ///
+    /// Returns `None` for well-formed codes that aren't in the built-in table (e.g. `ZZ` for unknown),
+    /// which are skipped rather than guessed.
+    pub(crate) fn coordinate(&self, record: &impl CsvRecord) -> Result<Option<Coordinate>, Error> {
+        let lat = self.get(record, self.latitude);
+        let lng = self.get(record, self.longitude);
+
+        if !lat.is_empty() || !lng.is_empty() {
+            return Ok(Some(Coordinate {
+                lat: str::from_utf8(lat)?.parse::<f32>()?,
+                lng: str::from_utf8(lng)?.parse::<f32>()?,
+            }));
+        }
+
+        let code = record
+            .field(self.country_code)
+            .ok_or(Error::NotEnoughColumns)?;
+
+        if code.len() != 2 || !CountryCode::from(code).is_well_formed() {
+            return Err(Error::MalformedCountryCode);
+        }
+
+        Ok(CountryCode::from(code).centroid())
+    }
+
+    /// Get the region, subregion, city, postcode and timezone, rejecting any that aren't UTF-8
+    /// rather than silently dropping them.
+    pub(crate) fn names<'r>(&self, record: &'r impl CsvRecord) -> Result<[&'r [u8]; 5], Error> {
+        let names = [
+            self.region,
+            self.subregion,
+            self.city,
+            self.postcode,
+            self.timezone,
+        ]
+        .map(|idx| self.get(record, idx));
+
+        for name in names {
+            str::from_utf8(name)?;
+        }
+
+        Ok(names)
+    }
+
+    /// Parse the first and last address of a record's range.
+    pub(crate) fn range<Ip: GenericIp>(
+        &self,
+        record: &impl CsvRecord,
+        ip_parser: fn(&[u8]) -> Result<Ip, Error>,
+    ) -> Result<(Ip, Ip), Error> {
+        let field = |idx| record.field(idx).ok_or(Error::NotEnoughColumns);
+
+        let start = ip_parser(field(self.ip_range_start)?)?;
+        let end = ip_parser(field(self.ip_range_end)?)?;
+
+        if start > end {
+            return Err(Error::InvalidRange);
+        }
+
+        Ok((start, end))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    IpRangeStart,
+    IpRangeEnd,
+    CountryCode,
+    Region,
+    Subregion,
+    City,
+    Postcode,
+    Latitude,
+    Longitude,
+    Timezone,
+}
+
+/// Lowercase a header, dropping everything but letters and numbers.
+fn normalize(header: &[u8]) -> String {
+    header
+        .iter()
+        .filter(|b| b.is_ascii_alphanumeric())
+        .map(|b| b.to_ascii_lowercase() as char)
+        .collect()
+}
+
+/// A record from either the `csv` or `csv_async` crates.
+pub trait CsvRecord {
+    fn field(&self, idx: usize) -> Option<&[u8]>;
+}
+
+impl CsvRecord for csv::ByteRecord {
+    fn field(&self, idx: usize) -> Option<&[u8]> {
+        self.get(idx)
+    }
+}
+
+#[cfg(feature = "download")]
+impl CsvRecord for csv_async::ByteRecord {
+    fn field(&self, idx: usize) -> Option<&[u8]> {
+        self.get(idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::Ipv4Addr};
+
+    use crate::{CsvSchema, Database, GenericDatabase, Ipv4Database};
+
+    fn headers(row: &str) -> Option<CsvSchema> {
+        CsvSchema::from_headers(row.split(',').map(str::as_bytes))
+    }
+
+    #[test]
+    fn header_detection() {
+        assert_eq!(
+            headers(
+                "ip_range_start,ip_range_end,country_code,state1,state2,city,postcode,latitude,longitude,timezone"
+            ),
+            Some(CsvSchema::SAPICS.with_headers(true))
+        );
+        assert_eq!(
+            headers("Start IP,End IP,Continent,Country,StateProv,City,Lat,Lng"),
+            Some(
+                CsvSchema::new(0, 1, 3)
+                    .region(4)
+                    .city(5)
+                    .coordinates(6, 7)
+                    .with_headers(true)
+            )
+        );
+        assert_eq!(
+            headers("network_id,ip_from,ip_to,cc"),
+            Some(CsvSchema::new(1, 2, 3).with_headers(true))
+        );
+        assert_eq!(headers("ip_from,ip_to,city"), None);
+        assert_eq!(headers("1.0.0.0,1.0.0.255,AU"), None);
+    }
+
+    #[test]
+    fn presets() -> Result<(), Box<dyn error::Error>> {
+        let db5 = r#""16777216","16777471","US","United States of America","California","Los Angeles","34.052230","-118.243680"
+"16777472","16778239","CN","China","Fujian","Fuzhou","26.061390","119.306110"
+"#;
+        let db =
+            Ipv4Database::from_csv_with_schema(db5.as_bytes(), true, &CsvSchema::IP2LOCATION_DB5)?;
+        let info = db.get(Ipv4Addr::new(1, 0, 0, 1)).expect("DB5 record");
+        assert_eq!(info.loc.country_code.as_str(), "US");
+        assert_eq!(info.loc.region.as_deref(), Some("California"));
+        assert_eq!(info.loc.city.as_deref(), Some("Los Angeles"));
+
+        let dbip = "1.0.0.0,1.0.0.255,OC,AU,Queensland,South Brisbane,-27.4767,153.017
+1.0.1.0,1.0.3.255,AS,CN,Fujian,Fuzhou,26.0614,119.306
+";
+        let db =
+            Ipv4Database::from_csv_with_schema(dbip.as_bytes(), false, &CsvSchema::DBIP_CITY_LITE)?;
+        let info = db.get(Ipv4Addr::new(1, 0, 2, 1)).expect("DB-IP record");
+        assert_eq!(info.loc.country_code.as_str(), "CN");
+        assert_eq!(info.loc.city.as_deref(), Some("Fuzhou"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn country_centroid_fallback() -> Result<(), Box<dyn error::Error>> {
+        let csv = "start,end,country,city,lat,lng
+1.0.0.0,1.0.0.255,AU,Brisbane,-27.4679,153.028
+1.0.1.0,1.0.1.255,JP,,,
+";
+        let schema = CsvSchema::new(0, 1, 2)
+            .city(3)
+            .coordinates(4, 5)
+            .with_headers(true);
+        let db = Ipv4Database::from_csv_with_schema(csv.as_bytes(), false, &schema)?;
+
+        let info = db.get(Ipv4Addr::new(1, 0, 1, 1)).expect("country record");
+        assert_eq!(info.loc.country_code.as_str(), "JP");
+        assert!(info.country_level);
+
+        Ok(())
+    }
+
+    #[test]
+    fn detect_headers() -> Result<(), Box<dyn error::Error>> {
+        // numeric IPv6 ranges starting with IPv4-mapped addresses, which fit in a u32 on their own.
+        let csv = "IP From,IP To,Country Code,City,Latitude,Longitude
+0,281470681743359,ZZ,,0,0
+281470698520576,281470698520831,AU,Brisbane,-27.4679,153.028
+";
+        let path = std::env::temp_dir().join(format!("ipgeo-{}.csv", fastrand::u64(..)));
+        std::fs::write(&path, csv)?;
+        let db = crate::detect(&path);
+        std::fs::remove_file(&path)?;
+
+        let Ok(GenericDatabase::Ipv6(db)) = db else {
+            panic!("expected an IPv6 database");
+        };
+
+        let info = db.get("::ffff:1.0.0.1".parse()?).expect("mapped record");
+        assert_eq!(info.loc.city.as_deref(), Some("Brisbane"));
+
+        Ok(())
+    }
+}
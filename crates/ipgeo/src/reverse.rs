@@ -0,0 +1,321 @@
+//! Reverse location-to-network searches over a database.
+
+use std::{collections::HashSet, fmt, net::IpAddr};
+
+use rustc_hash::FxBuildHasher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use treebitmap::IpLookupTable;
+
+use crate::{
+    CombinedDatabase, Coordinate, GenericIp, ReverseLookup, SingleDatabase,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationIndices, LocationKey, LocationStore, StringDictKey},
+};
+
+/// A location to search for the networks that resolve to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum LocationQuery {
+    /// An exact coordinate, within the precision of the database.
+    Coordinate(Coordinate),
+    /// A city name, matched case-insensitively.
+    City(String),
+    /// A region/state name, matched case-insensitively.
+    Region(String),
+    /// An ISO 3166 2-digit country code.
+    Country(String),
+    /// Every coordinate within a distance of another, e.g. a point clicked on a map.
+    ///
+    /// This checks every coordinate in the database, search a [`SpatialIndex`](crate::SpatialIndex)
+    /// and use [`ReverseLookup::find_networks_at`] instead to repeat it.
+    #[serde(rename_all = "camelCase")]
+    Near {
+        coordinate: Coordinate,
+        radius_km: f32,
+    },
+}
+
+impl From<Coordinate> for LocationQuery {
+    fn from(value: Coordinate) -> Self {
+        Self::Coordinate(value)
+    }
+}
+
+impl From<CountryCode> for LocationQuery {
+    fn from(value: CountryCode) -> Self {
+        Self::Country(value.to_string())
+    }
+}
+
+/// An IP network prefix found in a database.
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Type,
+)]
+pub struct Network {
+    pub ip: IpAddr,
+    pub prefix: u8,
+}
+
+impl<Ip: GenericIp> From<(Ip, u32)> for Network {
+    fn from((ip, prefix): (Ip, u32)) -> Self {
+        Self {
+            ip: ip.into(),
+            prefix: prefix as u8,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.ip, self.prefix)
+    }
+}
+
+/// The set of coordinates in a [`LocationStore`] that match a [`LocationQuery`].
+pub(crate) type CoordinateSet = HashSet<PackedCoordinate, FxBuildHasher>;
+
+/// Find every coordinate whose location matches the query.
+///
+/// Takes iterators rather than a [`LocationStore`] so it can be shared with the archived store.
+pub(crate) fn matching_coordinates<'a>(
+    query: &LocationQuery,
+    strings: impl Iterator<Item = &'a str>,
+    locations: impl Iterator<Item = LocationIndices>,
+    coordinates: impl Iterator<Item = (PackedCoordinate, LocationKey)>,
+) -> CoordinateSet {
+    let matches_location: Box<dyn Fn(&LocationIndices) -> bool> = match query {
+        LocationQuery::Coordinate(crd) => {
+            let crd = PackedCoordinate::from(*crd);
+
+            return coordinates.map(|(c, _)| c).filter(|c| *c == crd).collect();
+        }
+        LocationQuery::Near {
+            coordinate,
+            radius_km,
+        } => {
+            return coordinates
+                .map(|(c, _)| c)
+                .filter(|c| coordinate.within_km(&Coordinate::from(c), *radius_km))
+                .collect();
+        }
+        LocationQuery::Country(code) => {
+            let code = CountryCode::from(code.to_ascii_uppercase());
+            Box::new(move |loc| loc.country_code == code)
+        }
+        LocationQuery::City(name) => {
+            let keys = matching_strings(name, strings);
+            Box::new(move |loc| loc.city.is_some_and(|k| keys.contains(&k)))
+        }
+        LocationQuery::Region(name) => {
+            let keys = matching_strings(name, strings);
+            Box::new(move |loc| loc.region.is_some_and(|k| keys.contains(&k)))
+        }
+    };
+
+    let keys = locations
+        .enumerate()
+        .filter(|(_, loc)| matches_location(loc))
+        .map(|(key, _)| key)
+        .collect::<HashSet<LocationKey, FxBuildHasher>>();
+
+    coordinates
+        .filter(|(_, key)| keys.contains(key))
+        .map(|(c, _)| c)
+        .collect()
+}
+
+fn matching_strings<'a>(
+    name: &str,
+    strings: impl Iterator<Item = &'a str>,
+) -> HashSet<StringDictKey, FxBuildHasher> {
+    strings
+        .enumerate()
+        .filter(|(_, s)| eq_ignore_case(s, name))
+        .filter_map(|(i, _)| StringDictKey::new((i + 1) as u32))
+        .collect()
+}
+
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// The coordinates of a [`ReverseLookup::find_networks_at`] search, as stored in the database.
+pub(crate) fn coordinate_set(coordinates: &[Coordinate]) -> CoordinateSet {
+    coordinates
+        .iter()
+        .map(|crd| PackedCoordinate::from(*crd))
+        .collect()
+}
+
+/// Collect the networks in a table whose coordinate is in `coords`.
+pub(crate) fn matching_networks<Ip: GenericIp>(
+    table: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+    coords: &CoordinateSet,
+) -> impl Iterator<Item = Network> {
+    table
+        .filter(|(_, _, crd)| coords.contains(crd))
+        .map(|(ip, len, _)| Network::from((ip, len)))
+}
+
+impl LocationStore {
+    pub(crate) fn matching_coordinates(&self, query: &LocationQuery) -> CoordinateSet {
+        matching_coordinates(
+            query,
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().copied(),
+            self.coordinates.iter().map(|(c, k)| (*c, *k)),
+        )
+    }
+}
+
+fn owned_networks<Ip: GenericIp>(
+    table: &IpLookupTable<Ip, PackedCoordinate>,
+    coords: &CoordinateSet,
+) -> impl Iterator<Item = Network> {
+    matching_networks(table.iter().map(|(ip, len, c)| (ip, len, *c)), coords)
+}
+
+impl<Ip: GenericIp> SingleDatabase<Ip> {
+    fn networks_in(&self, coords: &CoordinateSet) -> Vec<Network> {
+        if coords.is_empty() {
+            return Vec::new();
+        }
+
+        owned_networks(&self.ips, coords).collect()
+    }
+}
+
+impl<Ip: GenericIp> ReverseLookup for SingleDatabase<Ip> {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        self.networks_in(&self.locations.matching_coordinates(query))
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        self.networks_in(&coordinate_set(coordinates))
+    }
+}
+
+impl CombinedDatabase {
+    fn networks_in(&self, coords: &CoordinateSet) -> Vec<Network> {
+        if coords.is_empty() {
+            return Vec::new();
+        }
+
+        owned_networks(&self.ipv4, coords)
+            .chain(owned_networks(&self.ipv6, coords))
+            .collect()
+    }
+}
+
+impl ReverseLookup for CombinedDatabase {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        self.networks_in(&self.locations.matching_coordinates(query))
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        self.networks_in(&coordinate_set(coordinates))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::Ipv4Addr};
+
+    use crate::{
+        Coordinate, Ipv4Database, LocationQuery, Network, ReverseLookup, SpatialLookup,
+        locations::CountryCode,
+        testing::{BRISBANE, archived, csv},
+    };
+
+    const RECORDS: &[&str] = &[
+        BRISBANE,
+        "1.0.1.0,1.0.3.255,CN,Fujian,,Fuzhou,,26.0614,119.306",
+        "1.0.4.0,1.0.7.255,AU,Victoria,,Melbourne,,-37.814,144.963",
+        "1.0.8.0,1.0.15.255,CN,Guangdong,,Guangzhou,,23.1317,113.266",
+    ];
+
+    fn net(a: u8, b: u8, c: u8, d: u8, prefix: u8) -> Network {
+        Network {
+            ip: Ipv4Addr::new(a, b, c, d).into(),
+            prefix,
+        }
+    }
+
+    fn sorted(mut nets: Vec<Network>) -> Vec<Network> {
+        nets.sort();
+        nets
+    }
+
+    #[test]
+    fn reverse_lookup() -> Result<(), Box<dyn error::Error>> {
+        let db = Ipv4Database::from_csv(csv(RECORDS).as_bytes(), false)?;
+
+        let cases = [
+            (
+                LocationQuery::City("fuzhou".into()),
+                vec![net(1, 0, 1, 0, 24), net(1, 0, 2, 0, 23)],
+            ),
+            (
+                LocationQuery::Region("Victoria".into()),
+                vec![net(1, 0, 4, 0, 22)],
+            ),
+            (
+                CountryCode::from("AU").into(),
+                vec![net(1, 0, 0, 0, 24), net(1, 0, 4, 0, 22)],
+            ),
+            (
+                Coordinate {
+                    lat: 23.1317,
+                    lng: 113.266,
+                }
+                .into(),
+                vec![net(1, 0, 8, 0, 21)],
+            ),
+            (
+                LocationQuery::Near {
+                    coordinate: Coordinate {
+                        lat: -30.0,
+                        lng: 150.0,
+                    },
+                    radius_km: 800.0,
+                },
+                vec![net(1, 0, 0, 0, 24)],
+            ),
+            (LocationQuery::City("Frankfurt".into()), vec![]),
+        ];
+
+        // the same as a near query, through a spatial index
+        let near = db
+            .spatial_index()
+            .within_km(
+                Coordinate {
+                    lat: -30.0,
+                    lng: 150.0,
+                },
+                800.0,
+            )
+            .into_iter()
+            .map(|(crd, _)| crd)
+            .collect::<Vec<_>>();
+        assert_eq!(db.find_networks_at(&near), [net(1, 0, 0, 0, 24)]);
+        assert!(db.find_networks_at(&[]).is_empty());
+
+        archived(&db, |archived| {
+            for (query, expected) in cases {
+                assert_eq!(sorted(db.find_networks(&query)), expected, "{query:?}");
+                assert_eq!(
+                    sorted(archived.find_networks(&query)),
+                    expected,
+                    "{query:?}"
+                );
+            }
+
+            assert_eq!(archived.find_networks_at(&near), [net(1, 0, 0, 0, 24)]);
+        })?;
+
+        Ok(())
+    }
+}
@@ -1,18 +1,30 @@
-use std::net::IpAddr;
+use std::{
+    io::Write,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 
 use rkyv::{
     option::ArchivedOption,
     rend::{NonZeroU32_le, u16_le},
 };
 
+use treebitmap::ArchivedIpLookupTable;
+
 use crate::{
-    Coordinate, Database, GenericIp, Location,
+    ArchivedAsnDatabase, ArchivedGenericDatabase, AsnInfo, AsnLookup, Audit, AuditReport,
+    Coordinate, Database, DatabaseStats, Error, GenericIp, Location, LocationQuery, LocationSearch,
+    Network, Prefix, Prefixes, ReverseLookup, SearchIndex, SpatialIndex, SpatialLookup, Statistics,
+    asn::{ArchivedAsnStore, AsnKey},
     coordinate::{ArchivedPackedCoordinate, PackedCoordinate},
     database::{ArchivedCombinedDatabase, ArchivedSingleDatabase},
+    diff,
     locations::{
         ArchivedCountryCode, ArchivedLocationIndices, ArchivedLocationStore, ArchivedStringDict,
-        CountryCode,
+        CountryCode, LocationIndices,
     },
+    reverse::{self, CoordinateSet},
+    stats::StatsBuilder,
+    translate, writer,
 };
 
 impl From<&ArchivedCountryCode> for CountryCode {
@@ -21,13 +33,18 @@ impl From<&ArchivedCountryCode> for CountryCode {
     }
 }
 
-impl From<&ArchivedPackedCoordinate> for Coordinate {
+impl From<&ArchivedPackedCoordinate> for PackedCoordinate {
     fn from(value: &ArchivedPackedCoordinate) -> Self {
-        (&PackedCoordinate {
+        PackedCoordinate {
             lat_u: value.lat_u.to_native(),
             lng_u: value.lng_u.to_native(),
-        })
-            .into()
+        }
+    }
+}
+
+impl From<&ArchivedPackedCoordinate> for Coordinate {
+    fn from(value: &ArchivedPackedCoordinate) -> Self {
+        (&PackedCoordinate::from(value)).into()
     }
 }
 
@@ -60,6 +77,32 @@ impl ArchivedLocationIndices {
                 ArchivedOption::None => None,
             },
             country_code: CountryCode::from(&self.country_code).to_string(),
+            subregion: match self.subregion {
+                ArchivedOption::Some(idx) => strings.get(idx),
+                ArchivedOption::None => None,
+            },
+            postcode: match self.postcode {
+                ArchivedOption::Some(idx) => strings.get(idx),
+                ArchivedOption::None => None,
+            },
+            timezone: match self.timezone {
+                ArchivedOption::Some(idx) => strings.get(idx),
+                ArchivedOption::None => None,
+            },
+            country: CountryCode::from(&self.country_code).country(),
+        }
+    }
+}
+
+impl From<&ArchivedLocationIndices> for LocationIndices {
+    fn from(value: &ArchivedLocationIndices) -> Self {
+        Self {
+            city: value.city.as_ref().map(|i| i.to_native()),
+            region: value.region.as_ref().map(|i| i.to_native()),
+            country_code: CountryCode::from(&value.country_code),
+            subregion: value.subregion.as_ref().map(|i| i.to_native()),
+            postcode: value.postcode.as_ref().map(|i| i.to_native()),
+            timezone: value.timezone.as_ref().map(|i| i.to_native()),
         }
     }
 }
@@ -75,11 +118,83 @@ impl ArchivedLocationStore {
                 .populate(&self.strings)
         })
     }
+
+    /// Get only the country of an associated coordinate, without reading its strings.
+    pub(crate) fn country_code(&self, coord: ArchivedPackedCoordinate) -> Option<CountryCode> {
+        self.coordinates
+            .get(&coord)
+            .and_then(|i| self.locations.get_index(i.to_native() as usize))
+            .map(|loc| CountryCode::from(&loc.country_code))
+    }
+
+    fn spatial_index(&self) -> SpatialIndex {
+        SpatialIndex::new(self.coordinates.keys().map(PackedCoordinate::from))
+    }
+
+    fn search_index(&self, prefixes: impl Iterator<Item = PackedCoordinate>) -> SearchIndex {
+        SearchIndex::new(
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().map(LocationIndices::from),
+            self.coordinates
+                .iter()
+                .map(|(c, k)| (c.into(), k.to_native() as usize)),
+            prefixes,
+        )
+    }
+
+    fn stats(&self) -> StatsBuilder<impl Fn(&PackedCoordinate) -> Option<(CountryCode, bool)>> {
+        StatsBuilder::new(
+            self.locations.iter().map(LocationIndices::from),
+            self.strings.0.len(),
+            |crd| {
+                self.coordinates
+                    .get(&ArchivedPackedCoordinate::from(*crd))
+                    .and_then(|i| self.locations.get_index(i.to_native() as usize))
+                    .map(LocationIndices::from)
+                    .map(|loc| (loc.country_code, loc.is_country_level()))
+            },
+        )
+    }
+
+    fn audit(&self, prefixes: impl Iterator<Item = PackedCoordinate>) -> AuditReport {
+        AuditReport::new(
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().map(LocationIndices::from),
+            self.coordinates
+                .iter()
+                .map(|(c, k)| (c.into(), k.to_native() as usize)),
+            self.conflicts.iter().flat_map(|(c, locs)| {
+                locs.iter()
+                    .map(move |loc| (PackedCoordinate::from(c), LocationIndices::from(loc)))
+            }),
+            prefixes,
+        )
+    }
+
+    fn matching_coordinates(&self, query: &LocationQuery) -> CoordinateSet {
+        reverse::matching_coordinates(
+            query,
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().map(LocationIndices::from),
+            self.coordinates
+                .iter()
+                .map(|(c, k)| (c.into(), k.to_native() as usize)),
+        )
+    }
+}
+
+fn archived_networks<Ip: GenericIp>(
+    table: &ArchivedIpLookupTable<Ip, PackedCoordinate>,
+    coords: &CoordinateSet,
+) -> impl Iterator<Item = Network> {
+    reverse::matching_networks(table.iter().map(|(ip, len, c)| (ip, len, c.into())), coords)
 }
 
 impl<Ip: GenericIp> Database<Ip> for ArchivedSingleDatabase<Ip> {
-    fn get_coordinate(&self, ip: Ip) -> Option<Coordinate> {
-        self.ips.longest_match(ip).map(|(_, _, c)| c.into())
+    fn get_network(&self, ip: Ip) -> Option<(Network, Coordinate)> {
+        self.ips
+            .longest_match(ip)
+            .map(|(ip, len, c)| (Network::from((ip, len)), c.into()))
     }
 
     fn get_location(&self, crd: Coordinate) -> Option<Location> {
@@ -89,10 +204,16 @@ impl<Ip: GenericIp> Database<Ip> for ArchivedSingleDatabase<Ip> {
 }
 
 impl Database<IpAddr> for ArchivedCombinedDatabase {
-    fn get_coordinate(&self, ip: IpAddr) -> Option<Coordinate> {
+    fn get_network(&self, ip: IpAddr) -> Option<(Network, Coordinate)> {
         match ip {
-            IpAddr::V4(ip) => self.ipv4.longest_match(ip).map(|(_, _, c)| c.into()),
-            IpAddr::V6(ip) => self.ipv6.longest_match(ip).map(|(_, _, c)| c.into()),
+            IpAddr::V4(ip) => self
+                .ipv4
+                .longest_match(ip)
+                .map(|(ip, len, c)| (Network::from((ip, len)), c.into())),
+            IpAddr::V6(ip) => self
+                .ipv6
+                .longest_match(ip)
+                .map(|(ip, len, c)| (Network::from((ip, len)), c.into())),
         }
     }
 
@@ -100,4 +221,320 @@ impl Database<IpAddr> for ArchivedCombinedDatabase {
         let crd: PackedCoordinate = crd.into();
         self.locations.get(crd.into())
     }
+
+    fn translate(&self, ip: &IpAddr) -> Option<IpAddr> {
+        translate::translate(ip)
+    }
+}
+
+impl<Ip: GenericIp> ArchivedSingleDatabase<Ip> {
+    fn networks_in(&self, coords: &CoordinateSet) -> Vec<Network> {
+        if coords.is_empty() {
+            return Vec::new();
+        }
+
+        archived_networks(&self.ips, coords).collect()
+    }
+}
+
+impl<Ip: GenericIp> ReverseLookup for ArchivedSingleDatabase<Ip> {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        self.networks_in(&self.locations.matching_coordinates(query))
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        self.networks_in(&reverse::coordinate_set(coordinates))
+    }
+}
+
+impl ArchivedCombinedDatabase {
+    fn networks_in(&self, coords: &CoordinateSet) -> Vec<Network> {
+        if coords.is_empty() {
+            return Vec::new();
+        }
+
+        archived_networks(&self.ipv4, coords)
+            .chain(archived_networks(&self.ipv6, coords))
+            .collect()
+    }
+}
+
+impl ReverseLookup for ArchivedCombinedDatabase {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        self.networks_in(&self.locations.matching_coordinates(query))
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        self.networks_in(&reverse::coordinate_set(coordinates))
+    }
+}
+
+impl ReverseLookup for ArchivedGenericDatabase {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.find_networks(query),
+            ArchivedGenericDatabase::Ipv6(db) => db.find_networks(query),
+        }
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.find_networks_at(coordinates),
+            ArchivedGenericDatabase::Ipv6(db) => db.find_networks_at(coordinates),
+        }
+    }
+}
+
+impl<Ip: GenericIp> SpatialLookup for ArchivedSingleDatabase<Ip> {
+    fn spatial_index(&self) -> SpatialIndex {
+        self.locations.spatial_index()
+    }
+}
+
+impl SpatialLookup for ArchivedCombinedDatabase {
+    fn spatial_index(&self) -> SpatialIndex {
+        self.locations.spatial_index()
+    }
+}
+
+impl SpatialLookup for ArchivedGenericDatabase {
+    fn spatial_index(&self) -> SpatialIndex {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.spatial_index(),
+            ArchivedGenericDatabase::Ipv6(db) => db.spatial_index(),
+        }
+    }
+}
+
+impl<Ip: GenericIp> LocationSearch for ArchivedSingleDatabase<Ip> {
+    fn search_index(&self) -> SearchIndex {
+        self.locations
+            .search_index(self.ips.iter().map(|(_, _, c)| c.into()))
+    }
+}
+
+impl LocationSearch for ArchivedCombinedDatabase {
+    fn search_index(&self) -> SearchIndex {
+        self.locations.search_index(
+            self.ipv4
+                .iter()
+                .map(|(_, _, c)| c.into())
+                .chain(self.ipv6.iter().map(|(_, _, c)| c.into())),
+        )
+    }
+}
+
+impl LocationSearch for ArchivedGenericDatabase {
+    fn search_index(&self) -> SearchIndex {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.search_index(),
+            ArchivedGenericDatabase::Ipv6(db) => db.search_index(),
+        }
+    }
+}
+
+impl<Ip: GenericIp> Audit for ArchivedSingleDatabase<Ip> {
+    fn audit(&self) -> AuditReport {
+        self.locations
+            .audit(self.ips.iter().map(|(_, _, c)| c.into()))
+    }
+}
+
+impl Audit for ArchivedCombinedDatabase {
+    fn audit(&self) -> AuditReport {
+        self.locations.audit(
+            self.ipv4
+                .iter()
+                .map(|(_, _, c)| c.into())
+                .chain(self.ipv6.iter().map(|(_, _, c)| c.into())),
+        )
+    }
+}
+
+impl Audit for ArchivedGenericDatabase {
+    fn audit(&self) -> AuditReport {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.audit(),
+            ArchivedGenericDatabase::Ipv6(db) => db.audit(),
+        }
+    }
+}
+
+impl<Ip: GenericIp> Statistics for ArchivedSingleDatabase<Ip> {
+    fn stats(&self) -> DatabaseStats {
+        self.locations
+            .stats()
+            .table::<Ip>(
+                self.ips.iter().map(|(ip, len, c)| (ip, len, c.into())),
+                self.ips.mem_usage(),
+            )
+            .finish()
+    }
+}
+
+impl Statistics for ArchivedCombinedDatabase {
+    fn stats(&self) -> DatabaseStats {
+        self.locations
+            .stats()
+            .table::<Ipv4Addr>(
+                self.ipv4.iter().map(|(ip, len, c)| (ip, len, c.into())),
+                self.ipv4.mem_usage(),
+            )
+            .table::<Ipv6Addr>(
+                self.ipv6.iter().map(|(ip, len, c)| (ip, len, c.into())),
+                self.ipv6.mem_usage(),
+            )
+            .finish()
+    }
+}
+
+impl Statistics for ArchivedGenericDatabase {
+    fn stats(&self) -> DatabaseStats {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.stats(),
+            ArchivedGenericDatabase::Ipv6(db) => db.stats(),
+        }
+    }
+}
+
+fn archived_prefixes<'a, Ip: GenericIp>(
+    table: &'a ArchivedIpLookupTable<Ip, PackedCoordinate>,
+    locations: &'a ArchivedLocationStore,
+) -> impl Iterator<Item = Prefix> + 'a {
+    diff::table_prefixes(table.iter().map(|(ip, len, c)| (ip, len, c.into())), |c| {
+        locations.country_code((*c).into())
+    })
+}
+
+impl<Ip: GenericIp> Prefixes for ArchivedSingleDatabase<Ip> {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        Box::new(archived_prefixes(&self.ips, &self.locations))
+    }
+}
+
+impl Prefixes for ArchivedCombinedDatabase {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        Box::new(
+            archived_prefixes(&self.ipv4, &self.locations)
+                .chain(archived_prefixes(&self.ipv6, &self.locations)),
+        )
+    }
+}
+
+impl Prefixes for ArchivedGenericDatabase {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        match self {
+            ArchivedGenericDatabase::Ipv4(db) => db.prefixes(),
+            ArchivedGenericDatabase::Ipv6(db) => db.prefixes(),
+        }
+    }
+}
+
+impl<Ip: GenericIp> ArchivedSingleDatabase<Ip> {
+    /// Write the database as a MaxMind DB file that can be read back with [`detect`](crate::detect).
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        writer::mmdb::write(
+            w,
+            Ip::IP_VERSION,
+            self.ips
+                .iter()
+                .map(|(ip, len, c)| (ip.into(), len, c.into())),
+            |c| self.locations.get((*c).into()),
+        )
+    }
+
+    /// Write the database as a sapics city CSV file, with string or numeric IPs.
+    pub fn write_csv(&self, w: impl Write, is_num: bool, gzip: bool) -> Result<(), Error> {
+        writer::csv::write(
+            w,
+            is_num,
+            gzip,
+            self.ips.iter().map(|(ip, len, c)| (ip, len, c.into())),
+            |c| self.locations.get((*c).into()),
+        )
+    }
+}
+
+impl ArchivedCombinedDatabase {
+    /// Write the database as an IPv6 MaxMind DB file, with the IPv4 networks stored in `::/96`.
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        writer::mmdb::write(
+            w,
+            Ipv6Addr::IP_VERSION,
+            self.ipv4
+                .iter()
+                .map(|(ip, len, c)| (ip.into(), len, c.into()))
+                .chain(
+                    self.ipv6
+                        .iter()
+                        .map(|(ip, len, c)| (ip.into(), len, c.into())),
+                ),
+            |c| self.locations.get((*c).into()),
+        )
+    }
+
+    /// Write the database as a pair of sapics city CSV files.
+    pub fn write_csv(
+        &self,
+        ipv4_csv: impl Write,
+        ipv6_csv: impl Write,
+        is_num: bool,
+        gzip: bool,
+    ) -> Result<(), Error> {
+        writer::csv::write(
+            ipv4_csv,
+            is_num,
+            gzip,
+            self.ipv4.iter().map(|(ip, len, c)| (ip, len, c.into())),
+            |c| self.locations.get((*c).into()),
+        )?;
+        writer::csv::write(
+            ipv6_csv,
+            is_num,
+            gzip,
+            self.ipv6.iter().map(|(ip, len, c)| (ip, len, c.into())),
+            |c| self.locations.get((*c).into()),
+        )
+    }
+}
+
+impl ArchivedGenericDatabase {
+    /// Write the database as a MaxMind DB file that can be read back with [`detect`](crate::detect).
+    pub fn write_mmdb(&self, w: impl Write) -> Result<(), Error> {
+        match self {
+            Self::Ipv4(db) => db.write_mmdb(w),
+            Self::Ipv6(db) => db.write_mmdb(w),
+        }
+    }
+
+    /// Write the database as a sapics city CSV file that can be read back with [`detect`](crate::detect).
+    pub fn write_csv(&self, w: impl Write, is_num: bool, gzip: bool) -> Result<(), Error> {
+        match self {
+            Self::Ipv4(db) => db.write_csv(w, is_num, gzip),
+            Self::Ipv6(db) => db.write_csv(w, is_num, gzip),
+        }
+    }
+}
+
+impl ArchivedAsnStore {
+    fn get(&self, key: AsnKey) -> Option<AsnInfo> {
+        self.systems.get_index(key as usize).map(|sys| AsnInfo {
+            number: sys.number.to_native(),
+            organization: match sys.organization {
+                ArchivedOption::Some(idx) => self.strings.get(idx),
+                ArchivedOption::None => None,
+            },
+        })
+    }
+}
+
+impl AsnLookup for ArchivedAsnDatabase {
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo> {
+        let key = match ip {
+            IpAddr::V4(ip) => self.ipv4.longest_match(ip).map(|(_, _, k)| k.to_native()),
+            IpAddr::V6(ip) => self.ipv6.longest_match(ip).map(|(_, _, k)| k.to_native()),
+        }?;
+
+        self.systems.get(key)
+    }
 }
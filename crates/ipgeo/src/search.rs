@@ -0,0 +1,445 @@
+//! Accent-insensitive prefix and fuzzy searches of city and region names.
+
+use std::{cmp::Reverse, collections::HashMap, iter};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{
+    CombinedDatabase, Coordinate, GenericIp, LocationSearch, SingleDatabase,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationIndices, LocationKey, LocationStore, StringDictKey},
+};
+
+/// Queries at least this many characters long also match names with a typo.
+const FUZZY_MIN_CHARS: usize = 4;
+/// Queries at least this many characters long are allowed a second typo.
+const FUZZY_TWO_TYPOS_CHARS: usize = 8;
+
+/// A location whose name matched a [`SearchIndex::search`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchHit {
+    /// The location's coordinate with the most network prefixes.
+    pub crd: Coordinate,
+    /// Network prefixes across every coordinate of the location.
+    pub networks: u32,
+    /// Edits between the query and the start of the matched name, `0` for a prefix match.
+    pub typos: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Field {
+    City,
+    Region,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Name {
+    folded: String,
+    field: Field,
+    places: Vec<u32>,
+}
+
+/// Every location with the same city, region, and country, e.g. one per postcode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Place {
+    crd: PackedCoordinate,
+    networks: u32,
+}
+
+/// An index of the city and region names of a database, see [`LocationSearch`].
+///
+/// Every word of a name is indexed, so `paulo` finds São Paulo as well as `sao pa`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SearchIndex {
+    names: Vec<Name>,
+    /// The name and byte offset of every word, sorted by the rest of the name from there.
+    words: Vec<(u32, u32)>,
+    places: Vec<Place>,
+}
+
+/// Rank of a search hit, lower is better.
+type Rank = (u32, bool, Field);
+
+impl SearchIndex {
+    /// Build an index from the parts of a [`LocationStore`] and the coordinate of every network prefix.
+    ///
+    /// Takes iterators rather than a [`LocationStore`] so it can be shared with the archived store.
+    pub(crate) fn new<'a>(
+        strings: impl Iterator<Item = &'a str>,
+        locations: impl Iterator<Item = LocationIndices>,
+        coordinates: impl Iterator<Item = (PackedCoordinate, LocationKey)>,
+        prefixes: impl Iterator<Item = PackedCoordinate>,
+    ) -> Self {
+        let mut counts = HashMap::<PackedCoordinate, u32, FxBuildHasher>::default();
+        for crd in prefixes {
+            *counts.entry(crd).or_default() += 1;
+        }
+
+        let strings = strings.collect::<Vec<_>>();
+        let locations = locations.collect::<Vec<_>>();
+
+        // the coordinate with the most prefixes, its count, and the location's total
+        let mut best = vec![None::<(PackedCoordinate, u32, u32)>; locations.len()];
+        for (crd, key) in coordinates {
+            let count = counts.get(&crd).copied().unwrap_or_default();
+
+            match best.get_mut(key) {
+                Some(Some((best_crd, best_count, total))) => {
+                    *total += count;
+
+                    if (count, Reverse(crd)) > (*best_count, Reverse(*best_crd)) {
+                        (*best_crd, *best_count) = (crd, count);
+                    }
+                }
+                Some(slot) => *slot = Some((crd, count, count)),
+                None => (),
+            }
+        }
+
+        let mut names = Vec::<Name>::new();
+        let mut name_keys = HashMap::<(StringDictKey, Field), usize, FxBuildHasher>::default();
+        let mut places = Vec::<Place>::new();
+        let mut place_keys = HashMap::<
+            (Option<StringDictKey>, Option<StringDictKey>, CountryCode),
+            usize,
+            FxBuildHasher,
+        >::default();
+
+        for (loc, best) in locations.iter().zip(best) {
+            let Some((crd, count, total)) = best else {
+                continue;
+            };
+
+            let key = (loc.city, loc.region, loc.country_code);
+            let (place, new) = match place_keys.get(&key) {
+                Some(place) => (*place, false),
+                None => {
+                    places.push(Place { crd, networks: 0 });
+                    place_keys.insert(key, places.len() - 1);
+                    (places.len() - 1, true)
+                }
+            };
+
+            let best_count = counts.get(&places[place].crd).copied().unwrap_or_default();
+            if (count, Reverse(crd)) > (best_count, Reverse(places[place].crd)) {
+                places[place].crd = crd;
+            }
+            places[place].networks += total;
+
+            // only the first location of a place adds it to its names
+            if !new {
+                continue;
+            }
+
+            for (field, key) in [(Field::City, loc.city), (Field::Region, loc.region)] {
+                let Some(key) = key else {
+                    continue;
+                };
+
+                let name = *name_keys.entry((key, field)).or_insert_with(|| {
+                    let string = strings.get(key.get() as usize - 1).copied();
+
+                    names.push(Name {
+                        folded: fold(string.unwrap_or_default()),
+                        field,
+                        places: Vec::new(),
+                    });
+                    names.len() - 1
+                });
+
+                names[name].places.push(place as u32);
+            }
+        }
+
+        let mut words = names
+            .iter()
+            .enumerate()
+            .flat_map(|(i, name)| {
+                iter::once(0)
+                    .chain(name.folded.match_indices(' ').map(|(offset, _)| offset + 1))
+                    .filter(|offset| *offset < name.folded.len())
+                    .map(move |offset| (i as u32, offset as u32))
+            })
+            .collect::<Vec<_>>();
+        words.sort_unstable_by(|a, b| word(&names, a).cmp(word(&names, b)).then(a.cmp(b)));
+
+        Self {
+            names,
+            words,
+            places,
+        }
+    }
+
+    /// The number of distinct names in the index.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Find up to `limit` locations with a city or region name starting with `query`,
+    /// or with any word of it starting with `query`, ignoring case and accents.
+    ///
+    /// Queries of at least four characters also match with a typo (two from eight characters),
+    /// though not in the first letter. Hits are ranked by typos, then names matching from their
+    /// first word, cities before regions, and finally by how many network prefixes map to them.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let query = fold(query);
+
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+
+        let mut ranks = HashMap::<u32, Rank, FxBuildHasher>::default();
+        let mut hit = |(name, offset): &(u32, u32), typos: u32| {
+            let name = &self.names[*name as usize];
+            let rank = (typos, *offset != 0, name.field);
+
+            for place in &name.places {
+                ranks
+                    .entry(*place)
+                    .and_modify(|r| *r = rank.min(*r))
+                    .or_insert(rank);
+            }
+        };
+
+        let start = self
+            .words
+            .partition_point(|w| word(&self.names, w) < query.as_str());
+        for w in self.words[start..]
+            .iter()
+            .take_while(|w| word(&self.names, w).starts_with(&query))
+        {
+            hit(w, 0);
+        }
+
+        let chars = query.chars().count();
+        if chars >= FUZZY_MIN_CHARS {
+            let max_typos = if chars >= FUZZY_TWO_TYPOS_CHARS { 2 } else { 1 };
+            let first = query.chars().next();
+
+            for w in &self.words {
+                let word = word(&self.names, w);
+
+                if word.chars().next() != first {
+                    continue;
+                }
+
+                // the query may be a letter short or long of the same part of the name
+                let typos = (chars - 1..=chars + 1)
+                    .map(|len| {
+                        let end = word.char_indices().nth(len).map_or(word.len(), |(i, _)| i);
+                        strsim::levenshtein(&query, &word[..end])
+                    })
+                    .min()
+                    .unwrap_or(usize::MAX) as u32;
+
+                if typos > 0 && typos <= max_typos {
+                    hit(w, typos);
+                }
+            }
+        }
+
+        let mut hits = ranks.into_iter().collect::<Vec<_>>();
+        hits.sort_unstable_by_key(|(place, rank)| {
+            (
+                *rank,
+                Reverse(self.places[*place as usize].networks),
+                *place,
+            )
+        });
+
+        hits.into_iter()
+            .take(limit)
+            .map(|(place, (typos, ..))| {
+                let place = self.places[place as usize];
+
+                SearchHit {
+                    crd: Coordinate::from(&place.crd),
+                    networks: place.networks,
+                    typos,
+                }
+            })
+            .collect()
+    }
+}
+
+fn word<'a>(names: &'a [Name], (name, offset): &(u32, u32)) -> &'a str {
+    &names[*name as usize].folded[*offset as usize..]
+}
+
+/// Lowercase a name, strip the accents from its Latin letters, and turn anything else
+/// that isn't a letter or digit into single spaces, so `São-Paulo` is the same as `sao paulo`.
+pub(crate) fn fold(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    let mut space = false;
+
+    for c in name.chars().flat_map(char::to_lowercase) {
+        // combining diacritical marks, from already decomposed names
+        if ('\u{300}'..='\u{36f}').contains(&c) {
+            continue;
+        }
+
+        if !c.is_alphanumeric() {
+            space = !folded.is_empty();
+            continue;
+        }
+
+        if space {
+            folded.push(' ');
+            space = false;
+        }
+
+        match unaccent(c) {
+            Some(letters) => folded.push_str(letters),
+            None => folded.push(c),
+        }
+    }
+
+    folded
+}
+
+/// The unaccented letters of a lowercase Latin-1 or Latin Extended-A letter.
+fn unaccent(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' | 'ǎ' => "a",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' | 'ǐ' => "i",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' | 'ǒ' => "o",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ș' => "s",
+        'ţ' | 'ť' | 'ŧ' | 'ț' => "t",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' | 'ǔ' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        'þ' => "th",
+        _ => return None,
+    })
+}
+
+impl LocationStore {
+    pub(crate) fn search_index(
+        &self,
+        prefixes: impl Iterator<Item = PackedCoordinate>,
+    ) -> SearchIndex {
+        SearchIndex::new(
+            self.strings.0.iter().map(|s| s.as_str()),
+            self.locations.iter().copied(),
+            self.coordinates.iter().map(|(c, k)| (*c, *k)),
+            prefixes,
+        )
+    }
+}
+
+impl<Ip: GenericIp> LocationSearch for SingleDatabase<Ip> {
+    fn search_index(&self) -> SearchIndex {
+        self.locations
+            .search_index(self.ips.iter().map(|(_, _, c)| *c))
+    }
+}
+
+impl LocationSearch for CombinedDatabase {
+    fn search_index(&self) -> SearchIndex {
+        self.locations.search_index(
+            self.ipv4
+                .iter()
+                .map(|(_, _, c)| *c)
+                .chain(self.ipv6.iter().map(|(_, _, c)| *c)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::IpAddr};
+
+    use crate::{CombinedDatabase, Database, LocationSearch, testing::archived};
+
+    use super::{SearchIndex, fold};
+
+    const CSV: &str = "1.0.0.0,1.0.0.255,BR,São Paulo,,São Paulo,,-23.5475,-46.6361
+1.0.1.0,1.0.1.255,BR,São Paulo,,São Paulo,,-23.5475,-46.6361
+1.0.2.0,1.0.2.255,BR,São Paulo,,Campinas,,-22.9056,-47.0608
+1.0.3.0,1.0.3.255,BR,Pará,,São Paulo de Olivença,,-3.3783,-68.8725
+1.0.4.0,1.0.4.255,AU,Queensland,,Brisbane,,-27.4679,153.028
+1.0.5.0,1.0.5.255,US,Illinois,,Springfield,,39.8017,-89.6437
+1.0.6.0,1.0.6.255,US,Missouri,,Springfield,,37.2153,-93.2982
+1.0.7.0,1.0.7.255,US,Missouri,,Springfield,,37.2153,-93.2982
+";
+
+    #[test]
+    fn folding() {
+        assert_eq!(fold("São Paulo"), "sao paulo");
+        assert_eq!(fold("  Saint-Étienne "), "saint etienne");
+        assert_eq!(fold("Val-d'Or"), "val d or");
+        assert_eq!(fold("Sa\u{303}o Paulo"), "sao paulo");
+        assert_eq!(fold("Łódź"), "lodz");
+        assert_eq!(fold("Großenhain"), "grossenhain");
+        assert_eq!(fold("東京"), "東京");
+    }
+
+    #[test]
+    fn search() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(CSV.as_bytes(), "".as_bytes(), false)?;
+
+        let index = db.search_index();
+        assert_eq!(index, archived(&db, |db| db.search_index())?);
+
+        let search = |query: &str| {
+            index
+                .search(query, 10)
+                .into_iter()
+                .map(|hit| {
+                    let loc = Database::<IpAddr>::get_location(&db, hit.crd).expect("location");
+                    (loc.city.unwrap_or_default(), loc.region.unwrap_or_default())
+                })
+                .collect::<Vec<_>>()
+        };
+        let place = |city: &str, region: &str| (city.to_string(), region.to_string());
+
+        // cities before regions, the bigger city first
+        assert_eq!(
+            search("Sao Pa"),
+            [
+                place("São Paulo", "São Paulo"),
+                place("São Paulo de Olivença", "Pará"),
+                place("Campinas", "São Paulo"),
+            ]
+        );
+        assert_eq!(search("olivenca"), [place("São Paulo de Olivença", "Pará")]);
+
+        // the Missouri one has more networks
+        assert_eq!(
+            search("springfield"),
+            [
+                place("Springfield", "Missouri"),
+                place("Springfield", "Illinois")
+            ]
+        );
+
+        let hits = index.search("brisbame", 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].typos, 1);
+        assert_eq!(hits[0].networks, 1);
+
+        assert!(search("risbane").is_empty());
+        assert!(search("").is_empty());
+        assert!(SearchIndex::default().search("sao", 10).is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+//! Nearest-location and radius searches over the coordinates of a database.
+
+use std::{f64::consts::FRAC_PI_2, ops::RangeInclusive};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    CombinedDatabase, Coordinate, GenericIp, Location, SingleDatabase, SpatialLookup,
+    coordinate::{EARTH_RADIUS_KM, PackedCoordinate},
+    locations::LocationStore,
+};
+
+/// Packed latitudes are grouped into rows of `2^ROW_SHIFT` steps, about 0.7° or 78 km.
+const ROW_SHIFT: u32 = 8;
+/// How far the first [`SpatialIndex::nearest`] search reaches, doubling until something is found.
+const NEAREST_START_KM: f32 = 25.0;
+/// Half the earth's circumference, no two coordinates are further apart.
+const MAX_DISTANCE_KM: f32 = 20_016.0;
+
+/// A location close to a searched coordinate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct NearbyLocation {
+    pub crd: Coordinate,
+    pub loc: Location,
+    /// Great-circle distance from the searched coordinate.
+    pub distance_km: f32,
+}
+
+/// An index of the coordinates of every location in a database, see [`SpatialLookup`].
+///
+/// Coordinates are sorted into latitude rows and then by longitude,
+/// so a search only reads the part of each row inside its bounding box.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SpatialIndex {
+    coordinates: Vec<PackedCoordinate>,
+}
+
+impl SpatialIndex {
+    pub(crate) fn new(coordinates: impl IntoIterator<Item = PackedCoordinate>) -> Self {
+        let mut coordinates = coordinates.into_iter().collect::<Vec<_>>();
+        coordinates.sort_unstable_by_key(|crd| (cell(crd), crd.lat_u));
+
+        Self { coordinates }
+    }
+
+    /// The number of coordinates in the index.
+    pub fn len(&self) -> usize {
+        self.coordinates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.coordinates.is_empty()
+    }
+
+    /// Every coordinate within `radius_km` of `center` with its distance, closest first.
+    pub fn within_km(&self, center: Coordinate, radius_km: f32) -> Vec<(Coordinate, f32)> {
+        let mut found = Vec::new();
+        self.visit(center, radius_km, |crd, distance| {
+            found.push((crd, distance))
+        });
+
+        found.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        found
+    }
+
+    /// The closest coordinate to `center` with its distance.
+    pub fn nearest(&self, center: Coordinate) -> Option<(Coordinate, f32)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut radius_km = NEAREST_START_KM;
+
+        loop {
+            let mut nearest: Option<(Coordinate, f32)> = None;
+            self.visit(center, radius_km, |crd, distance| {
+                if nearest.is_none_or(|(_, d)| distance < d) {
+                    nearest = Some((crd, distance));
+                }
+            });
+
+            // everything within the radius was seen, so nothing outside of it can be closer
+            if nearest.is_some() || radius_km >= MAX_DISTANCE_KM {
+                return nearest;
+            }
+
+            radius_km = (radius_km * 2.0).min(MAX_DISTANCE_KM);
+        }
+    }
+
+    /// Call `f` with every coordinate within `radius_km` of `center`, in no particular order.
+    fn visit(&self, center: Coordinate, radius_km: f32, mut f: impl FnMut(Coordinate, f32)) {
+        let (rows, lngs) = bounding_box(center, radius_km);
+
+        for row in rows {
+            for lng in &lngs {
+                let start = self
+                    .coordinates
+                    .partition_point(|crd| cell(crd) < (row, *lng.start()));
+                let end = self
+                    .coordinates
+                    .partition_point(|crd| cell(crd) <= (row, *lng.end()));
+
+                for crd in &self.coordinates[start..end] {
+                    let crd = Coordinate::from(crd);
+                    let distance = center.distance_km(&crd);
+
+                    if distance <= radius_km {
+                        f(crd, distance);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn cell(crd: &PackedCoordinate) -> (u16, u16) {
+    (crd.lat_u >> ROW_SHIFT, crd.lng_u)
+}
+
+fn pack(lat: f64, lng: f64) -> PackedCoordinate {
+    PackedCoordinate::from(Coordinate {
+        lat: lat as f32,
+        lng: lng as f32,
+    })
+}
+
+/// The packed rows and longitude ranges of a box around every coordinate within `radius_km`.
+///
+/// Bounds are widened by a step to cover the rounding of packed coordinates,
+/// and the longitudes are split in two where the box crosses the antimeridian.
+fn bounding_box(
+    center: Coordinate,
+    radius_km: f32,
+) -> (RangeInclusive<u16>, Vec<RangeInclusive<u16>>) {
+    let (lat, lng) = (center.lat as f64, center.lng as f64);
+    let angle = radius_km.max(0.0) as f64 / EARTH_RADIUS_KM;
+    let dlat = angle.to_degrees();
+
+    let rows = cell(&pack(lat - dlat, lng)).0.saturating_sub(1)
+        ..=cell(&pack(lat + dlat, lng)).0.saturating_add(1);
+
+    // a circle over a pole, or wider than its parallel, covers every longitude
+    let ratio = angle.sin() / lat.to_radians().cos();
+    if lat - dlat <= -90.0 || lat + dlat >= 90.0 || angle >= FRAC_PI_2 || ratio >= 1.0 {
+        return (rows, vec![0..=u16::MAX]);
+    }
+
+    let dlng = ratio.asin().to_degrees();
+    let (lo, hi) = (lng - dlng, lng + dlng);
+    let widen = |lo: f64, hi: f64| {
+        pack(lat, lo).lng_u.saturating_sub(1)..=pack(lat, hi).lng_u.saturating_add(1)
+    };
+
+    let (west, east) = match (lo < -180.0, hi > 180.0) {
+        (true, _) => (widen(-180.0, hi), widen(lo + 360.0, 180.0)),
+        (_, true) => (widen(-180.0, hi - 360.0), widen(lo, 180.0)),
+        _ => return (rows, vec![widen(lo, hi)]),
+    };
+
+    match east.start() <= west.end() {
+        true => (rows, vec![0..=u16::MAX]),
+        false => (rows, vec![west, east]),
+    }
+}
+
+impl LocationStore {
+    pub(crate) fn spatial_index(&self) -> SpatialIndex {
+        SpatialIndex::new(self.coordinates.keys().copied())
+    }
+}
+
+impl<Ip: GenericIp> SpatialLookup for SingleDatabase<Ip> {
+    fn spatial_index(&self) -> SpatialIndex {
+        self.locations.spatial_index()
+    }
+}
+
+impl SpatialLookup for CombinedDatabase {
+    fn spatial_index(&self) -> SpatialIndex {
+        self.locations.spatial_index()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::IpAddr};
+
+    use crate::{
+        CombinedDatabase, Coordinate, Database, SpatialLookup,
+        testing::{BRISBANE, archived, csv},
+    };
+
+    use super::{PackedCoordinate, SpatialIndex};
+
+    const RECORDS: &[&str] = &[
+        BRISBANE,
+        "1.0.1.0,1.0.1.255,AU,Queensland,,Gold Coast,,-28.0167,153.4",
+        "1.0.2.0,1.0.2.255,AU,Victoria,,Melbourne,,-37.814,144.963",
+        "1.0.3.0,1.0.3.255,FJ,Northern,,Labasa,,-16.4167,179.3833",
+        "1.0.4.0,1.0.4.255,FJ,Northern,,Naqara,,-16.7,-179.95",
+        "1.0.5.0,1.0.5.255,AQ,,,,,-89.5,10.0",
+    ];
+
+    fn crd(lat: f32, lng: f32) -> Coordinate {
+        Coordinate { lat, lng }
+    }
+
+    #[test]
+    fn nearby() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(csv(RECORDS).as_bytes(), "".as_bytes(), false)?;
+
+        let index = db.spatial_index();
+        assert_eq!(index, archived(&db, |db| db.spatial_index())?);
+        assert_eq!(index.len(), 6);
+
+        let city = |found: &[(Coordinate, f32)]| {
+            found
+                .iter()
+                .map(|(crd, _)| Database::<IpAddr>::get_location(&db, *crd).and_then(|l| l.city))
+                .collect::<Vec<_>>()
+        };
+
+        // Brisbane and the Gold Coast are ~70 km apart
+        let brisbane = crd(-27.4679, 153.028);
+        assert_eq!(
+            city(&index.within_km(brisbane, 50.0)),
+            [Some("Brisbane".into())]
+        );
+        assert_eq!(
+            city(&index.within_km(brisbane, 100.0)),
+            [Some("Brisbane".into()), Some("Gold Coast".into())]
+        );
+
+        let (nearest, distance) = index.nearest(crd(-37.0, 145.0)).expect("nearest");
+        assert_eq!(city(&[(nearest, distance)]), [Some("Melbourne".into())]);
+        assert!((distance - 90.6).abs() < 1.0, "{distance}");
+
+        // across the antimeridian and the south pole
+        assert_eq!(
+            city(&index.within_km(crd(-16.5, 180.0), 100.0)),
+            [Some("Naqara".into()), Some("Labasa".into())]
+        );
+        assert_eq!(index.within_km(crd(-89.9, -170.0), 100.0).len(), 1);
+
+        assert!(SpatialIndex::default().nearest(brisbane).is_none());
+        assert!(index.within_km(brisbane, -1.0).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = fastrand::Rng::with_seed(0x5ca1ab1e);
+        let coords = (0..20_000)
+            .map(|_| crd(rng.f32() * 180.0 - 90.0, rng.f32() * 360.0 - 180.0))
+            .map(|c| Coordinate::from(&PackedCoordinate::from(c)))
+            .collect::<Vec<_>>();
+        let index = SpatialIndex::new(coords.iter().map(|c| (*c).into()));
+
+        for _ in 0..200 {
+            let center = crd(rng.f32() * 180.0 - 90.0, rng.f32() * 360.0 - 180.0);
+            let radius_km = rng.f32() * 2000.0;
+
+            let mut expected = coords
+                .iter()
+                .filter(|c| center.within_km(c, radius_km))
+                .map(|c| PackedCoordinate::from(*c))
+                .collect::<Vec<_>>();
+            expected.sort();
+
+            let found = index.within_km(center, radius_km);
+            assert!(found.is_sorted_by(|(_, a), (_, b)| a <= b));
+
+            let mut found = found
+                .into_iter()
+                .map(|(c, _)| PackedCoordinate::from(c))
+                .collect::<Vec<_>>();
+            found.sort();
+            assert_eq!(found, expected, "{center:?} {radius_km}");
+
+            let nearest = index.nearest(center).map(|(_, d)| d);
+            let expected = coords
+                .iter()
+                .map(|c| center.distance_km(c))
+                .min_by(f32::total_cmp);
+            assert_eq!(nearest, expected, "{center:?}");
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Prefix, address and location counts that show what a database covers.
+
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use rustc_hash::FxBuildHasher;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{
+    CombinedDatabase, GenericIp, SingleDatabase, Statistics,
+    coordinate::PackedCoordinate,
+    locations::{CountryCode, LocationIndices, LocationStore},
+    writer,
+};
+
+/// What a database covers, see [`Statistics`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStats {
+    pub ipv4: FamilyStats,
+    pub ipv6: FamilyStats,
+    /// Coverage of every country with at least one prefix, sorted by country code.
+    pub countries: Vec<CountryStats>,
+    /// Distinct locations, each at its own coordinate.
+    pub locations: u64,
+    /// Locations with nothing more specific than their country.
+    pub country_level_locations: u64,
+    /// Distinct city, region and other location name strings.
+    pub strings: u64,
+}
+
+/// Coverage of a single address family.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct FamilyStats {
+    pub prefixes: u64,
+    /// Addresses covered by the prefixes, each counted once, a float since IPv6 counts overflow any integer.
+    pub addresses: f64,
+    /// Prefixes placed at a location with nothing more specific than its country.
+    pub country_level_prefixes: u64,
+    /// Bytes used by the lookup table's trie nodes and results.
+    pub table_bytes: u64,
+}
+
+/// Prefixes and addresses placed in a single country.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CountryStats {
+    pub country_code: String,
+    pub ipv4_prefixes: u64,
+    pub ipv4_addresses: f64,
+    pub ipv6_prefixes: u64,
+    pub ipv6_addresses: f64,
+}
+
+impl DatabaseStats {
+    /// Bytes used by both lookup tables, not counting the locations.
+    pub fn table_bytes(&self) -> u64 {
+        self.ipv4.table_bytes + self.ipv6.table_bytes
+    }
+}
+
+/// Counts the prefixes of a database's lookup tables against its locations.
+///
+/// `location` gives the country of a coordinate and whether it's country-level,
+/// so the same counting serves both owned and archived stores.
+pub(crate) struct StatsBuilder<F> {
+    stats: DatabaseStats,
+    countries: HashMap<CountryCode, CountryStats, FxBuildHasher>,
+    location: F,
+}
+
+impl<F: Fn(&PackedCoordinate) -> Option<(CountryCode, bool)>> StatsBuilder<F> {
+    pub(crate) fn new(
+        locations: impl Iterator<Item = LocationIndices>,
+        strings: usize,
+        location: F,
+    ) -> Self {
+        let mut stats = DatabaseStats {
+            strings: strings as u64,
+            ..Default::default()
+        };
+
+        for loc in locations {
+            stats.locations += 1;
+            stats.country_level_locations += loc.is_country_level() as u64;
+        }
+
+        Self {
+            stats,
+            countries: HashMap::default(),
+            location,
+        }
+    }
+
+    /// Count every prefix of a table, with the `(nodes, results)` bytes of its `mem_usage`.
+    ///
+    /// Prefixes can be nested, e.g. in overlays, so addresses are only counted towards
+    /// the most specific prefix containing them, where a lookup would place them.
+    pub(crate) fn table<Ip: GenericIp>(
+        mut self,
+        prefixes: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+        (nodes, results): (usize, usize),
+    ) -> Self {
+        let is_ipv4 = Ip::IP_VERSION == 4;
+        let (family, bits) = match is_ipv4 {
+            true => (&mut self.stats.ipv4, 32),
+            false => (&mut self.stats.ipv6, 128),
+        };
+        family.table_bytes = (nodes + results) as u64;
+
+        for (crd, addresses) in own_addresses(prefixes, bits) {
+            family.prefixes += 1;
+            family.addresses += addresses;
+
+            let Some((country_code, country_level)) = (self.location)(&crd) else {
+                continue;
+            };
+            family.country_level_prefixes += country_level as u64;
+
+            let country = self
+                .countries
+                .entry(country_code)
+                .or_insert_with(|| CountryStats {
+                    country_code: country_code.to_string(),
+                    ..Default::default()
+                });
+
+            match is_ipv4 {
+                true => {
+                    country.ipv4_prefixes += 1;
+                    country.ipv4_addresses += addresses;
+                }
+                false => {
+                    country.ipv6_prefixes += 1;
+                    country.ipv6_addresses += addresses;
+                }
+            }
+        }
+
+        self
+    }
+
+    pub(crate) fn finish(mut self) -> DatabaseStats {
+        self.stats.countries = self.countries.into_values().collect();
+        self.stats
+            .countries
+            .sort_unstable_by(|a, b| a.country_code.cmp(&b.country_code));
+
+        self.stats
+    }
+}
+
+/// The coordinate of each prefix with its addresses that aren't in a prefix nested in it.
+fn own_addresses<Ip: GenericIp>(
+    prefixes: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+    bits: i32,
+) -> Vec<(PackedCoordinate, f64)> {
+    let host_mask = |len: u32| (u128::MAX >> (128 - bits)).checked_shr(len).unwrap_or(0);
+
+    // outer networks sort before the ones nested in them
+    let mut prefixes = prefixes
+        .map(|(ip, len, crd)| {
+            let start = writer::csv::from_ip(ip.into()) & !host_mask(len);
+            (start, len, crd, 2f64.powi(bits - len as i32))
+        })
+        .collect::<Vec<_>>();
+    prefixes.sort_unstable_by_key(|(start, len, ..)| (*start, *len));
+
+    // the ends and indices of the enclosing networks of the current prefix, innermost last
+    let mut stack: Vec<(u128, usize)> = Vec::new();
+
+    for i in 0..prefixes.len() {
+        let (start, len, _, addresses) = prefixes[i];
+
+        while let Some(&(end, _)) = stack.last()
+            && end < start
+        {
+            stack.pop();
+        }
+
+        if let Some(&(_, outer)) = stack.last() {
+            prefixes[outer].3 -= addresses;
+        }
+
+        stack.push((start | host_mask(len), i));
+    }
+
+    prefixes
+        .into_iter()
+        .map(|(_, _, crd, addresses)| (crd, addresses))
+        .collect()
+}
+
+impl LocationStore {
+    fn stats(&self) -> StatsBuilder<impl Fn(&PackedCoordinate) -> Option<(CountryCode, bool)>> {
+        StatsBuilder::new(
+            self.locations.iter().copied(),
+            self.strings.0.len(),
+            |crd| {
+                self.coordinates
+                    .get(crd)
+                    .and_then(|i| self.locations.get_index(*i))
+                    .map(|loc| (loc.country_code, loc.is_country_level()))
+            },
+        )
+    }
+}
+
+impl<Ip: GenericIp> Statistics for SingleDatabase<Ip> {
+    fn stats(&self) -> DatabaseStats {
+        self.locations
+            .stats()
+            .table::<Ip>(
+                self.ips.iter().map(|(ip, len, c)| (ip, len, *c)),
+                self.ips.mem_usage(),
+            )
+            .finish()
+    }
+}
+
+impl Statistics for CombinedDatabase {
+    fn stats(&self) -> DatabaseStats {
+        self.locations
+            .stats()
+            .table::<Ipv4Addr>(
+                self.ipv4.iter().map(|(ip, len, c)| (ip, len, *c)),
+                self.ipv4.mem_usage(),
+            )
+            .table::<Ipv6Addr>(
+                self.ipv6.iter().map(|(ip, len, c)| (ip, len, *c)),
+                self.ipv6.mem_usage(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error;
+
+    use crate::{
+        CombinedDatabase, Statistics,
+        testing::{BRISBANE, TOKYO_IPV6, archived, csv},
+    };
+
+    #[test]
+    fn stats() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(
+            csv(&[
+                BRISBANE,
+                "1.0.1.0,1.0.1.255,AU,Queensland,,Brisbane,,-27.4679,153.028",
+                "1.0.2.0,1.0.3.255,CN,,,,,35.8617,104.1954",
+            ])
+            .as_bytes(),
+            csv(&[TOKYO_IPV6]).as_bytes(),
+            false,
+        )?;
+
+        let stats = db.stats();
+        assert_eq!(stats.locations, 3);
+        assert_eq!(stats.country_level_locations, 1);
+        assert_eq!(stats.strings, 3);
+
+        assert_eq!(stats.ipv4.prefixes, 3);
+        assert_eq!(stats.ipv4.addresses, 1024.0);
+        assert_eq!(stats.ipv4.country_level_prefixes, 1);
+        assert_eq!(stats.ipv6.prefixes, 1);
+        assert_eq!(stats.ipv6.addresses, 2f64.powi(96));
+        assert!(stats.ipv4.table_bytes > 0 && stats.ipv6.table_bytes > 0);
+
+        let countries = stats
+            .countries
+            .iter()
+            .map(|c| (c.country_code.as_str(), c.ipv4_addresses, c.ipv6_prefixes))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            countries,
+            [("AU", 512.0, 0), ("CN", 512.0, 0), ("JP", 0.0, 1)]
+        );
+
+        // archives only count what they store, not the owned tables' spare capacity
+        let mut archived = archived(&db, |db| db.stats())?;
+        assert!(archived.table_bytes() > 0 && archived.table_bytes() <= stats.table_bytes());
+
+        archived.ipv4.table_bytes = stats.ipv4.table_bytes;
+        archived.ipv6.table_bytes = stats.ipv6.table_bytes;
+        assert_eq!(archived, stats);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nested_prefixes() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_overlay_csv(
+            "10.20.0.0/16,Berlin office,DE,52.52,13.405
+10.20.30.0/24,Vienna lab,AT,48.2082,16.3738
+10.20.30.128/25,Vienna rack,AT,48.21,16.37
+"
+            .as_bytes(),
+        )?;
+
+        let stats = db.stats();
+        assert_eq!(stats.ipv4.prefixes, 3);
+        assert_eq!(stats.ipv4.addresses, 65536.0);
+
+        let countries = stats
+            .countries
+            .iter()
+            .map(|c| (c.country_code.as_str(), c.ipv4_prefixes, c.ipv4_addresses))
+            .collect::<Vec<_>>();
+        assert_eq!(countries, [("AT", 2, 256.0), ("DE", 1, 65280.0)]);
+
+        Ok(())
+    }
+}
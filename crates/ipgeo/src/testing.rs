@@ -0,0 +1,32 @@
+//! Fixtures shared by the tests of several modules.
+
+use rkyv::{
+    Archive, Serialize, api::high::HighSerializer, rancor, ser::allocator::ArenaHandle,
+    util::AlignedVec,
+};
+
+/// A city-level IPv4 record in the sapics CSV layout.
+pub(crate) const BRISBANE: &str = "1.0.0.0,1.0.0.255,AU,Queensland,,Brisbane,,-27.4679,153.028";
+pub(crate) const FUZHOU: &str = "1.0.1.0,1.0.1.255,CN,Fujian,,Fuzhou,,26.0614,119.306";
+pub(crate) const TOKYO_IPV6: &str =
+    "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP,Tokyo,,Tokyo,,35.6895,139.692";
+
+/// A CSV file of records, one per line.
+pub(crate) fn csv(records: &[&str]) -> String {
+    records.iter().map(|record| format!("{record}\n")).collect()
+}
+
+/// Serialize a database and read its archived form, as if it had been loaded from disk.
+pub(crate) fn archived<T, R>(
+    db: &T,
+    read: impl FnOnce(&T::Archived) -> R,
+) -> Result<R, rancor::Error>
+where
+    T: Archive + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, rancor::Error>>,
+{
+    let bytes = rkyv::to_bytes::<rancor::Error>(db)?;
+
+    Ok(read(unsafe {
+        rkyv::access_unchecked::<T::Archived>(&bytes)
+    }))
+}
@@ -0,0 +1,100 @@
+//! IPv4 addresses embedded in IPv6 transition addresses, which are looked up in the IPv4 table.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// 6to4 relay addresses, `2002:AABB:CCDD::/48` carries `A.B.C.D` (RFC 3056).
+const SIX_TO_FOUR: u16 = 0x2002;
+/// The well-known NAT64 prefix `64:ff9b::/96` (RFC 6052).
+const NAT64: u128 = 0x0064_ff9b_0000_0000_0000_0000_0000_0000;
+/// Teredo addresses, `2001:0::/32` with the client's address inverted in the last 32 bits (RFC 4380).
+const TEREDO: u128 = 0x2001_0000_0000_0000_0000_0000_0000_0000;
+
+/// Get the IPv4 address embedded in an IPv4-mapped, 6to4, NAT64 or Teredo address.
+pub fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let bits = ip.to_bits();
+
+    if let Some(ip) = ip.to_ipv4_mapped() {
+        Some(ip)
+    } else if ip.segments()[0] == SIX_TO_FOUR {
+        Some(Ipv4Addr::from_bits((bits >> 80) as u32))
+    } else if bits >> 32 == NAT64 >> 32 {
+        Some(Ipv4Addr::from_bits(bits as u32))
+    } else if bits >> 96 == TEREDO >> 96 {
+        Some(Ipv4Addr::from_bits(!(bits as u32)))
+    } else {
+        None
+    }
+}
+
+/// [`embedded_ipv4`] for [`Database::translate`](crate::Database::translate) implementations,
+/// including those of types wrapping the databases outside this crate.
+pub fn translate(ip: &IpAddr) -> Option<IpAddr> {
+    match ip {
+        IpAddr::V4(_) => None,
+        IpAddr::V6(ip) => embedded_ipv4(*ip).map(IpAddr::V4),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error, net::IpAddr};
+
+    use crate::{
+        CombinedDatabase, Database,
+        testing::{BRISBANE, csv},
+    };
+
+    use super::embedded_ipv4;
+
+    #[test]
+    fn embedded() -> Result<(), Box<dyn error::Error>> {
+        for (ip, embedded) in [
+            ("::ffff:1.0.0.1", Some("1.0.0.1")),
+            ("2002:100:1::1", Some("1.0.0.1")),
+            ("64:ff9b::1.0.0.1", Some("1.0.0.1")),
+            ("2001:0:4136:e378:8000:63bf:feff:fffe", Some("1.0.0.1")),
+            ("2001:200::1", None),
+            ("::1", None),
+        ] {
+            assert_eq!(
+                embedded_ipv4(ip.parse()?),
+                embedded.map(str::parse).transpose()?,
+                "{ip}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn translated_lookup() -> Result<(), Box<dyn error::Error>> {
+        let db = CombinedDatabase::from_csv(
+            csv(&[BRISBANE]).as_bytes(),
+            b"2002::,2002:ffff:ffff:ffff:ffff:ffff:ffff:ffff,JP,Tokyo,,Tokyo,,35.6895,139.692\n"
+                .as_slice(),
+            false,
+        )?;
+
+        let ipv4 = db.get("1.0.0.1".parse::<IpAddr>()?).expect("IPv4 record");
+        assert!(!ipv4.translated);
+
+        let mapped = db.get("::ffff:1.0.0.1".parse::<IpAddr>()?).expect("mapped");
+        assert!(mapped.translated);
+        assert_eq!(mapped.loc, ipv4.loc);
+
+        // the network is the embedded address's
+        let network = db
+            .get_match("::ffff:1.0.0.1".parse::<IpAddr>()?)
+            .expect("mapped");
+        assert_eq!(network.network.to_string(), "1.0.0.0/24");
+
+        // falls back to the IPv6 table when the embedded address isn't in the IPv4 table
+        let six_to_four = db.get("2002:200::1".parse::<IpAddr>()?).expect("6to4");
+        assert!(!six_to_four.translated);
+        assert_eq!(six_to_four.loc.city.as_deref(), Some("Tokyo"));
+
+        assert!(db.get("64:ff9b::2.0.0.1".parse::<IpAddr>()?).is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+//! Writes databases back out in the sapics city CSV format that `reader::csv` reads.
+
+use std::{io::Write, net::IpAddr};
+
+use flate2::{Compression, write::GzEncoder};
+
+use crate::{Coordinate, Error, GenericIp, Location, coordinate::PackedCoordinate};
+
+/// Write every network as `ip_range_start,ip_range_end,...` rows, optionally gzip-compressed.
+///
+/// Nested prefixes are flattened by longest match, and adjacent ranges that share a coordinate
+/// are merged back together.
+pub fn write<Ip: GenericIp>(
+    w: impl Write,
+    is_num: bool,
+    gzip: bool,
+    networks: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+    location: impl Fn(&PackedCoordinate) -> Option<Location>,
+) -> Result<(), Error> {
+    if gzip {
+        let mut encoder = GzEncoder::new(w, Compression::default());
+        write_rows(&mut encoder, is_num, networks, location)?;
+        encoder.finish()?;
+    } else {
+        write_rows(w, is_num, networks, location)?;
+    }
+
+    Ok(())
+}
+
+fn write_rows<Ip: GenericIp>(
+    w: impl Write,
+    is_num: bool,
+    networks: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+    location: impl Fn(&PackedCoordinate) -> Option<Location>,
+) -> Result<(), Error> {
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(w);
+
+    for (start, end, crd) in ranges(networks) {
+        let (start, end) = (to_ip::<Ip>(start), to_ip::<Ip>(end));
+        let loc = location(&crd).unwrap_or_default();
+        let Coordinate { lat, lng } = Coordinate::from(&crd);
+
+        let (start, end) = if is_num {
+            (ip_num(start), ip_num(end))
+        } else {
+            (start.to_string(), end.to_string())
+        };
+
+        writer.write_record([
+            start.as_str(),
+            end.as_str(),
+            loc.country_code.as_str(),
+            loc.region.as_deref().unwrap_or_default(),
+            loc.subregion.as_deref().unwrap_or_default(),
+            loc.city.as_deref().unwrap_or_default(),
+            loc.postcode.as_deref().unwrap_or_default(),
+            lat.to_string().as_str(),
+            lng.to_string().as_str(),
+            loc.timezone.as_deref().unwrap_or_default(),
+        ])?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Flatten (possibly nested) prefixes into sorted, non-overlapping, merged ranges.
+fn ranges<Ip: GenericIp>(
+    networks: impl Iterator<Item = (Ip, u32, PackedCoordinate)>,
+) -> Vec<(u128, u128, PackedCoordinate)> {
+    let max = u128::MAX >> (128 - if Ip::IP_VERSION == 4 { 32 } else { 128 });
+    let host_mask = |len: u32| max.checked_shr(len).unwrap_or(0);
+
+    // outer networks sort before the ones nested in them
+    let mut prefixes = networks
+        .map(|(ip, len, crd)| (from_ip(ip.into()) & !host_mask(len), len, crd))
+        .collect::<Vec<_>>();
+    prefixes.sort_unstable_by_key(|(start, len, _)| (*start, *len));
+
+    let mut ranges = Ranges::default();
+    // the enclosing networks of the current prefix, innermost last
+    let mut stack: Vec<(u128, PackedCoordinate)> = Vec::new();
+
+    for (start, len, crd) in prefixes {
+        while let Some(&(end, outer)) = stack.last()
+            && end < start
+        {
+            ranges.fill(end, outer);
+            stack.pop();
+        }
+
+        // the part of the enclosing network before this one
+        if let Some(&(_, outer)) = stack.last()
+            && let Some(before) = start.checked_sub(1)
+        {
+            ranges.fill(before, outer);
+        }
+
+        ranges.cursor = Some(start);
+        stack.push((start | host_mask(len), crd));
+    }
+
+    while let Some((end, outer)) = stack.pop() {
+        ranges.fill(end, outer);
+    }
+
+    ranges.out
+}
+
+struct Ranges {
+    out: Vec<(u128, u128, PackedCoordinate)>,
+    /// The next address that hasn't been written, `None` once the whole address space is.
+    cursor: Option<u128>,
+}
+
+impl Default for Ranges {
+    fn default() -> Self {
+        Self {
+            out: Vec::new(),
+            cursor: Some(0),
+        }
+    }
+}
+
+impl Ranges {
+    /// Cover everything from the cursor up to and including `end` with a coordinate.
+    fn fill(&mut self, end: u128, crd: PackedCoordinate) {
+        let Some(start) = self.cursor.filter(|start| *start <= end) else {
+            return;
+        };
+
+        match self.out.last_mut() {
+            Some(last) if last.2 == crd && last.1.checked_add(1) == Some(start) => last.1 = end,
+            _ => self.out.push((start, end, crd)),
+        }
+
+        self.cursor = end.checked_add(1);
+    }
+}
+
+pub(crate) fn from_ip(ip: IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => ip.to_bits().into(),
+        IpAddr::V6(ip) => ip.to_bits(),
+    }
+}
+
+fn to_ip<Ip: GenericIp>(bits: u128) -> IpAddr {
+    match Ip::IP_VERSION {
+        4 => IpAddr::V4((bits as u32).into()),
+        _ => IpAddr::V6(bits.into()),
+    }
+}
+
+fn ip_num(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => ip.to_bits().to_string(),
+        IpAddr::V6(ip) => ip.to_bits().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        fs::File,
+        net::{Ipv4Addr, Ipv6Addr},
+    };
+
+    use crate::{Database, GenericDatabase, Ipv4Database, Ipv6Database};
+
+    const CSV: &str = "1.0.0.0,1.0.255.255,AU,Queensland,,Brisbane,,-27.4679,153.028,
+1.0.1.0,1.0.3.255,CN,Fujian,,Fuzhou,,26.0614,119.306,
+1.0.4.0,1.0.7.255,CN,Fujian,,Fuzhou,,26.0614,119.306,
+1.0.16.0,1.0.16.255,CN,Guangdong,,Guangzhou,510000,23.1317,113.266,Asia/Shanghai
+";
+
+    fn ranges(csv: &[u8]) -> Vec<(String, String, String)> {
+        csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(csv)
+            .records()
+            .map(|r| r.unwrap())
+            .map(|r| (r[0].to_string(), r[1].to_string(), r[5].to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn merged_ranges() -> Result<(), Box<dyn error::Error>> {
+        let db = Ipv4Database::from_csv(CSV.as_bytes(), false)?;
+
+        let mut out = Vec::new();
+        db.write_csv(&mut out, false, false)?;
+
+        let expected = [
+            ("1.0.0.0", "1.0.0.255", "Brisbane"),
+            ("1.0.1.0", "1.0.7.255", "Fuzhou"),
+            ("1.0.8.0", "1.0.15.255", "Brisbane"),
+            ("1.0.16.0", "1.0.16.255", "Guangzhou"),
+            ("1.0.17.0", "1.0.255.255", "Brisbane"),
+        ]
+        .map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string()));
+
+        assert_eq!(ranges(&out), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), Box<dyn error::Error>> {
+        let db = Ipv4Database::from_csv(CSV.as_bytes(), false)?;
+
+        let mut num = Vec::new();
+        db.write_csv(&mut num, true, false)?;
+        let read = Ipv4Database::from_csv(num.as_slice(), true)?;
+
+        let path = std::env::temp_dir().join(format!("ipgeo-{}.csv.gz", fastrand::u64(..)));
+        db.write_csv(File::create(&path)?, false, true)?;
+        let gz = crate::detect(&path);
+        std::fs::remove_file(&path)?;
+
+        let Ok(GenericDatabase::Ipv4(gz)) = gz else {
+            panic!("expected an IPv4 database");
+        };
+
+        for ip in (0..10_000).map(|_| Ipv4Addr::from_bits(fastrand::u32(0x0100_0000..0x0102_0000)))
+        {
+            assert_eq!(db.get(ip), read.get(ip), "{ip}");
+            assert_eq!(db.get(ip), gz.get(ip), "{ip}");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn full_ipv6_range() -> Result<(), Box<dyn error::Error>> {
+        let csv = "::,ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff,DE,,,,,51.1657,10.4515,
+2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP,Tokyo,,Tokyo,,35.6895,139.692,
+";
+        let db = Ipv6Database::from_csv(csv.as_bytes(), false)?;
+
+        let mut out = Vec::new();
+        db.write_csv(&mut out, false, false)?;
+
+        let expected = [
+            ("::", "2001:1ff:ffff:ffff:ffff:ffff:ffff:ffff", ""),
+            (
+                "2001:200::",
+                "2001:200:ffff:ffff:ffff:ffff:ffff:ffff",
+                "Tokyo",
+            ),
+            ("2001:201::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff", ""),
+        ]
+        .map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string()));
+
+        assert_eq!(ranges(&out), expected);
+
+        let read = Ipv6Database::from_csv(out.as_slice(), false)?;
+        let ip: Ipv6Addr = "2001:200::1".parse()?;
+        assert_eq!(db.get(ip), read.get(ip));
+
+        Ok(())
+    }
+}
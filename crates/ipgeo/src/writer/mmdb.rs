@@ -0,0 +1,471 @@
+//! A minimal MaxMind DB writer producing the same flat layout that `reader::mmdb` reads.
+//!
+//! https://maxmind.github.io/MaxMind-DB/
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    io::Write,
+    net::IpAddr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{Coordinate, Error, Location, coordinate::PackedCoordinate};
+
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+const DATA_SECTION_SEPARATOR: usize = 16;
+
+pub const DATABASE_TYPE: &str = "ipgeo-city";
+
+// data section field types
+pub(crate) const POINTER: u8 = 1;
+pub(crate) const STRING: u8 = 2;
+pub(crate) const DOUBLE: u8 = 3;
+pub(crate) const UINT16: u8 = 5;
+pub(crate) const UINT32: u8 = 6;
+pub(crate) const MAP: u8 = 7;
+pub(crate) const UINT64: u8 = 9;
+pub(crate) const ARRAY: u8 = 11;
+
+/// Write every network and its location as a MaxMind DB.
+///
+/// A database with `ip_version` 6 stores any IPv4 networks in `::/96`, as MaxMind's own databases do.
+pub fn write(
+    w: impl Write,
+    ip_version: u16,
+    networks: impl Iterator<Item = (IpAddr, u32, PackedCoordinate)>,
+    location: impl Fn(&PackedCoordinate) -> Option<Location>,
+) -> Result<(), Error> {
+    write_records(w, ip_version, DATABASE_TYPE, networks, |data, crd| {
+        data.location(crd, location(crd).unwrap_or_default());
+    })
+}
+
+/// Write a MaxMind DB, encoding each distinct record value once with `encode`.
+pub(crate) fn write_records<K: Copy + Eq + Hash>(
+    mut w: impl Write,
+    ip_version: u16,
+    database_type: &str,
+    networks: impl Iterator<Item = (IpAddr, u32, K)>,
+    encode: impl Fn(&mut DataSection, &K),
+) -> Result<(), Error> {
+    let mut networks = networks
+        .map(|(ip, len, key)| match (ip, ip_version) {
+            (IpAddr::V4(ip), 4) => Ok(((ip.to_bits() as u128) << 96, len, key)),
+            (IpAddr::V4(ip), _) => Ok((ip.to_bits() as u128, len + 96, key)),
+            (IpAddr::V6(ip), 6) => Ok((ip.to_bits(), len, key)),
+            (IpAddr::V6(_), _) => Err(Error::InvalidFormat),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // shorter prefixes first, so more specific ones are carved out of them rather than overwritten.
+    networks.sort_unstable_by_key(|(bits, len, _)| (*len, *bits));
+
+    let mut tree = SearchTree::default();
+    let mut data = DataSection::default();
+    let mut offsets: HashMap<K, u32, FxBuildHasher> = HashMap::default();
+
+    for (bits, len, key) in networks {
+        let offset = match offsets.get(&key) {
+            Some(offset) => *offset,
+            None => {
+                let offset =
+                    u32::try_from(data.buf.len()).map_err(|_| Error::DatabaseMetadataOverflow)?;
+                encode(&mut data, &key);
+                offsets.insert(key, offset);
+                offset
+            }
+        };
+
+        tree.insert(bits, len, offset);
+    }
+
+    let node_count = tree.nodes.len() as u64;
+    let max_record = node_count + DATA_SECTION_SEPARATOR as u64 + data.buf.len() as u64;
+    let record_size: u16 = match max_record {
+        0..0x100_0000 => 24,
+        0x100_0000..0x1000_0000 => 28,
+        0x1000_0000..0x1_0000_0000 => 32,
+        _ => return Err(Error::DatabaseMetadataOverflow),
+    };
+
+    tree.write(&mut w, record_size)?;
+    w.write_all(&[0; DATA_SECTION_SEPARATOR])?;
+    w.write_all(&data.buf)?;
+
+    w.write_all(METADATA_MARKER)?;
+    w.write_all(&metadata(node_count as u32, record_size, ip_version, database_type).buf)?;
+
+    Ok(())
+}
+
+fn metadata(
+    node_count: u32,
+    record_size: u16,
+    ip_version: u16,
+    database_type: &str,
+) -> DataSection {
+    let build_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    // pointers in the metadata would be relative to its own start, so strings are always inlined.
+    let mut meta = DataSection {
+        inline_strings: true,
+        ..Default::default()
+    };
+
+    meta.control(MAP, 9);
+    meta.string("node_count");
+    meta.uint(UINT32, node_count.into());
+    meta.string("record_size");
+    meta.uint(UINT16, record_size.into());
+    meta.string("ip_version");
+    meta.uint(UINT16, ip_version.into());
+    meta.string("database_type");
+    meta.string(database_type);
+    meta.string("languages");
+    meta.control(ARRAY, 1);
+    meta.string("en");
+    meta.string("binary_format_major_version");
+    meta.uint(UINT16, 2);
+    meta.string("binary_format_minor_version");
+    meta.uint(UINT16, 0);
+    meta.string("build_epoch");
+    meta.uint(UINT64, build_epoch);
+    meta.string("description");
+    meta.control(MAP, 1);
+    meta.string("en");
+    meta.string("IP geolocation database exported by ipgeo");
+
+    meta
+}
+
+#[derive(Copy, Clone)]
+enum Record {
+    Empty,
+    Node(u32),
+    Data(u32),
+}
+
+/// The binary search tree section, one bit of the address per level.
+struct SearchTree {
+    nodes: Vec<[Record; 2]>,
+}
+
+impl Default for SearchTree {
+    fn default() -> Self {
+        Self {
+            nodes: vec![[Record::Empty; 2]],
+        }
+    }
+}
+
+impl SearchTree {
+    /// Point the `len` most significant bits of `bits` at a data section offset.
+    fn insert(&mut self, bits: u128, len: u32, offset: u32) {
+        // the root can't hold data itself, so a default route fills both of its halves.
+        if len == 0 {
+            self.insert(0, 1, offset);
+            self.insert(1 << 127, 1, offset);
+            return;
+        }
+
+        let mut node = 0;
+
+        for depth in 0..len {
+            let bit = ((bits >> (127 - depth)) & 1) as usize;
+
+            if depth == len - 1 {
+                self.nodes[node][bit] = Record::Data(offset);
+                return;
+            }
+
+            node = match self.nodes[node][bit] {
+                Record::Node(next) => next as usize,
+                // an existing, less specific network keeps covering the other half of the split.
+                fill @ (Record::Empty | Record::Data(_)) => {
+                    let next = self.nodes.len();
+                    self.nodes.push([fill; 2]);
+                    self.nodes[node][bit] = Record::Node(next as u32);
+                    next
+                }
+            };
+        }
+    }
+
+    fn write(&self, w: &mut impl Write, record_size: u16) -> Result<(), Error> {
+        let node_count = self.nodes.len() as u32;
+
+        let value = |record: Record| match record {
+            Record::Empty => node_count,
+            Record::Node(node) => node,
+            Record::Data(offset) => node_count + DATA_SECTION_SEPARATOR as u32 + offset,
+        };
+
+        for [left, right] in &self.nodes {
+            let (left, right) = (value(*left), value(*right));
+
+            match record_size {
+                24 => {
+                    w.write_all(&left.to_be_bytes()[1..])?;
+                    w.write_all(&right.to_be_bytes()[1..])?;
+                }
+                28 => {
+                    w.write_all(&left.to_be_bytes()[1..])?;
+                    w.write_all(&[((left >> 20) & 0xF0) as u8 | ((right >> 24) & 0x0F) as u8])?;
+                    w.write_all(&right.to_be_bytes()[1..])?;
+                }
+                _ => {
+                    w.write_all(&left.to_be_bytes())?;
+                    w.write_all(&right.to_be_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Encoder for the MaxMind DB data section format, deduplicating strings with pointers.
+#[derive(Default)]
+pub(crate) struct DataSection {
+    buf: Vec<u8>,
+    strings: HashMap<String, u32, FxBuildHasher>,
+    inline_strings: bool,
+}
+
+impl DataSection {
+    /// Encode a location in the flat ip-location-db layout.
+    fn location(&mut self, crd: &PackedCoordinate, loc: Location) {
+        let crd = Coordinate::from(crd);
+
+        let optional = [
+            ("city", loc.city),
+            ("state1", loc.region),
+            ("state2", loc.subregion),
+            ("postcode", loc.postcode),
+            ("timezone", loc.timezone),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k, v)))
+        .collect::<Vec<_>>();
+
+        self.control(MAP, 3 + optional.len());
+        self.string("latitude");
+        self.double(crd.lat.into());
+        self.string("longitude");
+        self.double(crd.lng.into());
+        self.string("country_code");
+        self.string(&loc.country_code);
+
+        for (key, value) in optional {
+            self.string(key);
+            self.string(&value);
+        }
+    }
+
+    pub(crate) fn control(&mut self, kind: u8, size: usize) {
+        let size = size as u64;
+        let (size_bits, extra, extra_len) = match size {
+            0..29 => (size as u8, 0, 0),
+            29..285 => (29, size - 29, 1),
+            285..65_821 => (30, size - 285, 2),
+            _ => (31, size - 65_821, 3),
+        };
+
+        // extended types are stored in a second byte, offset by 7
+        if kind > 7 {
+            self.buf.push(size_bits);
+            self.buf.push(kind - 7);
+        } else {
+            self.buf.push((kind << 5) | size_bits);
+        }
+
+        self.buf
+            .extend_from_slice(&extra.to_be_bytes()[8 - extra_len..]);
+    }
+
+    pub(crate) fn pointer(&mut self, ptr: u32) {
+        let ctrl = POINTER << 5;
+
+        match ptr {
+            0..0x800 => {
+                self.buf.push(ctrl | (ptr >> 8) as u8);
+                self.buf.push(ptr as u8);
+            }
+            0x800..0x8_0800 => {
+                let p = ptr - 0x800;
+                self.buf.push(ctrl | 0x08 | (p >> 16) as u8);
+                self.buf.extend_from_slice(&p.to_be_bytes()[2..]);
+            }
+            0x8_0800..0x808_0800 => {
+                let p = ptr - 0x8_0800;
+                self.buf.push(ctrl | 0x10 | (p >> 24) as u8);
+                self.buf.extend_from_slice(&p.to_be_bytes()[1..]);
+            }
+            _ => {
+                self.buf.push(ctrl | 0x18);
+                self.buf.extend_from_slice(&ptr.to_be_bytes());
+            }
+        }
+    }
+
+    pub(crate) fn string(&mut self, s: &str) {
+        if !self.inline_strings {
+            if let Some(ptr) = self.strings.get(s) {
+                self.pointer(*ptr);
+                return;
+            }
+
+            self.strings.insert(s.to_string(), self.buf.len() as u32);
+        }
+
+        self.control(STRING, s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    pub(crate) fn double(&mut self, v: f64) {
+        self.control(DOUBLE, 8);
+        self.buf.extend_from_slice(&v.to_be_bytes());
+    }
+
+    pub(crate) fn uint(&mut self, kind: u8, v: u64) {
+        let bytes = v.to_be_bytes();
+        let skip = (v.leading_zeros() / 8) as usize;
+
+        self.control(kind, bytes.len() - skip);
+        self.buf.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        error,
+        fs::File,
+        io::Write,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    };
+
+    use maxminddb::PathElement;
+
+    use crate::{CombinedDatabase, Database, GenericDatabase, Ipv4Database};
+
+    /// Thousands of nested networks with unique names, so the string pointers need more than one byte.
+    fn ipv4_csv() -> String {
+        let mut csv =
+            String::from("1.0.0.0,1.255.255.255,AU,Queensland,,Brisbane,,-27.4679,153.028,\n");
+
+        for i in 0..4000u32 {
+            let start = Ipv4Addr::from_bits(0x0100_0000 + i * 256);
+            let end = Ipv4Addr::from_bits(0x0100_0000 + i * 256 + 127);
+            let (lat, lng) = (
+                fastrand::f32() * 180.0 - 90.0,
+                fastrand::f32() * 360.0 - 180.0,
+            );
+
+            csv.push_str(&format!(
+                "{start},{end},CN,Region {i},,City {i},{i},{lat},{lng},Asia/Shanghai\n"
+            ));
+        }
+
+        csv.push_str("2.0.0.0,2.0.0.255,DE,,,,,51.1657,10.4515,\n");
+        csv
+    }
+
+    fn random_ipv4() -> Ipv4Addr {
+        Ipv4Addr::from_bits(fastrand::u32(0x0100_0000..0x0201_0000))
+    }
+
+    #[test]
+    fn single_round_trip() -> Result<(), Box<dyn error::Error>> {
+        let db = Ipv4Database::from_csv(ipv4_csv().as_bytes(), false)?;
+
+        let path = std::env::temp_dir().join(format!("ipgeo-{}.mmdb", fastrand::u64(..)));
+        db.write_mmdb(File::create(&path)?)?;
+
+        maxminddb::Reader::open_readfile(&path)?.verify()?;
+        let read = crate::detect(&path);
+        std::fs::remove_file(&path)?;
+
+        let Ok(GenericDatabase::Ipv4(read)) = read else {
+            panic!("expected an IPv4 database");
+        };
+
+        for ip in (0..10_000).map(|_| random_ipv4()) {
+            assert_eq!(db.get(ip), read.get(ip), "{ip}");
+        }
+
+        assert!(read.get(Ipv4Addr::new(2, 0, 0, 1)).unwrap().country_level);
+
+        Ok(())
+    }
+
+    #[test]
+    fn combined_round_trip() -> Result<(), Box<dyn error::Error>> {
+        let ipv6 =
+            "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP,Tokyo,,Tokyo,,35.6895,139.692\n";
+        let db = CombinedDatabase::from_csv(ipv4_csv().as_bytes(), ipv6.as_bytes(), false)?;
+
+        let mut bytes = Vec::new();
+        db.write_mmdb(&mut bytes)?;
+        bytes.flush()?;
+
+        let reader = maxminddb::Reader::from_source(bytes)?;
+        reader.verify()?;
+        assert_eq!(reader.metadata.ip_version, 6);
+
+        for ip in (0..1_000).map(|_| random_ipv4()) {
+            let city = reader
+                .lookup(ip.into())?
+                .decode_path::<String>(&[PathElement::Key("city")])?;
+
+            assert_eq!(
+                db.get(IpAddr::from(ip)).and_then(|i| i.loc.city),
+                city,
+                "{ip}"
+            );
+        }
+
+        let ip: Ipv6Addr = "2001:200::1".parse()?;
+        let city = reader
+            .lookup(ip.into())?
+            .decode_path::<String>(&[PathElement::Key("city")])?;
+        assert_eq!(city.as_deref(), Some("Tokyo"));
+
+        assert!(crate::reader::mmdb::has_ipv4(&reader));
+        let read = CombinedDatabase::from_mmdb(reader)?;
+
+        for ip in (0..1_000).map(|_| IpAddr::from(random_ipv4())) {
+            assert_eq!(db.get(ip), read.get(ip), "{ip}");
+        }
+
+        assert_eq!(db.get(IpAddr::V6(ip)), read.get(IpAddr::V6(ip)));
+        assert!(read.ipv6.iter().all(|(ip, _, _)| ip.to_bits() >> 32 != 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ipv6_only() -> Result<(), Box<dyn error::Error>> {
+        let ipv6 =
+            "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP,Tokyo,,Tokyo,,35.6895,139.692\n";
+        let db = crate::Ipv6Database::from_csv(ipv6.as_bytes(), false)?;
+
+        let mut bytes = Vec::new();
+        db.write_mmdb(&mut bytes)?;
+
+        let reader = maxminddb::Reader::from_source(bytes)?;
+        assert!(!crate::reader::mmdb::has_ipv4(&reader));
+
+        let read = CombinedDatabase::from_mmdb(reader)?;
+        assert_eq!(read.ipv4.len(), 0);
+        assert!(read.get("2001:200::1".parse()?).is_some());
+
+        Ok(())
+    }
+}
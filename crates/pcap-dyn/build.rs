@@ -15,6 +15,10 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         format!("{}/bpf_filter", env::var("OUT_DIR")?),
         build_bpf_filter(),
     )?;
+    fs::write(
+        format!("{}/bpf_filter_private", env::var("OUT_DIR")?),
+        build_bpf_filter_private(),
+    )?;
 
     Ok(())
 }
@@ -46,3 +50,12 @@ fn build_bpf_filter() -> Vec<u8> {
 
     msg
 }
+
+/// Like [build_bpf_filter], but keeps traffic between private addresses, e.g. to internal offices.
+fn build_bpf_filter_private() -> Vec<u8> {
+    let mut msg = b"(ip or ip6) and not (broadcast or multicast)".to_vec();
+
+    msg.push(0);
+
+    msg
+}
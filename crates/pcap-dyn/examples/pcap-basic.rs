@@ -18,7 +18,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("{device:?}");
 
-    let mut cap = api.open_capture(device)?;
+    let mut cap = api.open_capture(device, false)?;
 
     let recv = cap.start();
 
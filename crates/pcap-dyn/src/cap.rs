@@ -22,15 +22,30 @@ const BPF_FILTER: &CStr =
         Err(_) => panic!("build.rs produced invalid CStr"),
     };
 
+const BPF_FILTER_PRIVATE: &CStr = match CStr::from_bytes_with_nul(include_bytes!(concat!(
+    env!("OUT_DIR"),
+    "/bpf_filter_private"
+))) {
+    Ok(filter) => filter,
+    Err(_) => panic!("build.rs produced invalid CStr"),
+};
+
 /// A session currently capturing packets from the network device.
 pub struct Capture {
     raw: Arc<Container<Raw>>,
     handle: PcapTSend,
     thread_handle: Option<JoinHandle<()>>,
+    /// The device's own addresses if traffic between private addresses is kept,
+    /// to tell which side of a private link is local.
+    local_addrs: Option<Arc<[IpAddr]>>,
 }
 
 impl Capture {
-    pub(crate) fn open(raw: Arc<Container<Raw>>, device: Device) -> Result<Self, Error> {
+    pub(crate) fn open(
+        raw: Arc<Container<Raw>>,
+        device: Device,
+        local_addrs: Option<Vec<IpAddr>>,
+    ) -> Result<Self, Error> {
         // open the device for live capture
         let device_name = CString::new(device.name.clone()).unwrap();
 
@@ -44,11 +59,16 @@ impl Capture {
             tracing::warn!("Failed to set libpcap immediate mode");
         }
 
+        let filter = match local_addrs {
+            Some(_) => BPF_FILTER_PRIVATE,
+            None => BPF_FILTER,
+        };
+
         unsafe {
             let mut bpf_program = mem::zeroed();
 
             // Compile the string into bytecode
-            if raw.pcap_compile(handle_ptr, &mut bpf_program, BPF_FILTER.as_ptr(), 1, 0) == 0 {
+            if raw.pcap_compile(handle_ptr, &mut bpf_program, filter.as_ptr(), 1, 0) == 0 {
                 // Load the bytecode into the kernel
                 raw.pcap_setfilter(handle_ptr, &mut bpf_program);
                 raw.pcap_freecode(&mut bpf_program);
@@ -61,6 +81,7 @@ impl Capture {
             raw,
             handle,
             thread_handle: None,
+            local_addrs: local_addrs.map(Arc::from),
         })
     }
 
@@ -74,6 +95,7 @@ impl Capture {
             // We pass a copy of the pointer, but the Main thread owns the lifecycle.
             handle: self.handle.clone(),
             raw: self.raw.clone(),
+            local_addrs: self.local_addrs.clone(),
         };
 
         let join_handle = thread::spawn(move || {
@@ -115,6 +137,7 @@ struct CallbackState {
     packet_tx: Sender<Packet>,
     handle: PcapTSend,
     raw: Arc<Container<Raw>>,
+    local_addrs: Option<Arc<[IpAddr]>>,
 }
 
 impl CallbackState {
@@ -141,7 +164,7 @@ impl CallbackState {
         let state = unsafe { &mut *(slf as *mut Self) };
 
         unsafe {
-            if let Some(packet) = Packet::from_raw(header, packet)
+            if let Some(packet) = Packet::from_raw(header, packet, state.local_addrs.as_deref())
                 && state.packet_tx.send(packet).is_err()
             {
                 // Channel closed, Stop the loop.
@@ -160,7 +183,11 @@ pub struct Packet {
 }
 
 impl Packet {
-    unsafe fn from_raw(header: *const pcap_pkthdr, packet: *const libc::c_uchar) -> Option<Self> {
+    unsafe fn from_raw(
+        header: *const pcap_pkthdr,
+        packet: *const libc::c_uchar,
+        local_addrs: Option<&[IpAddr]>,
+    ) -> Option<Self> {
         let header = unsafe { *header };
         let packet = unsafe { slice::from_raw_parts(packet, header.caplen as _) };
 
@@ -185,8 +212,16 @@ impl Packet {
             // This is DOWNSTREAM traffic. We care about the source IP.
             (true, false) => (PacketDirection::Down, src),
 
-            // All other cases (local to local, remote to remote, etc.) are ignored.
-            _ => return None,
+            // Traffic between private IPs, e.g. to another office, is only kept
+            // if asked for, with the side that's one of our addresses as local.
+            (false, false) => match local_addrs {
+                Some(local) if local.contains(&src) => (PacketDirection::Up, dst),
+                Some(local) if local.contains(&dst) => (PacketDirection::Down, src),
+                _ => return None,
+            },
+
+            // Remote to remote traffic isn't ours, so it's ignored.
+            (true, true) => return None,
         };
 
         Some(Packet {
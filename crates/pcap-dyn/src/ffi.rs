@@ -1,6 +1,10 @@
 #![allow(non_camel_case_types)]
 
-use std::ffi::CStr;
+use std::{
+    ffi::CStr,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    ptr,
+};
 
 use dlopen2::wrapper::{Container, WrapperApi};
 use libc::{c_char, c_int, c_uchar, c_uint, c_ushort, sockaddr, timeval};
@@ -12,6 +16,13 @@ const FILE: &str = "libpcap.dylib";
 #[cfg(windows)]
 const FILE: &str = "wpcap.dll";
 
+#[cfg(unix)]
+use libc::{AF_INET, AF_INET6};
+#[cfg(windows)]
+const AF_INET: c_int = 2;
+#[cfg(windows)]
+const AF_INET6: c_int = 23;
+
 #[rustfmt::skip]
 #[derive(WrapperApi)]
 pub struct Raw {
@@ -181,6 +192,32 @@ pub(crate) fn cstr_to_string(ptr: *const c_char) -> Option<String> {
     }
 }
 
+/// Read the address out of a `sockaddr_in`/`sockaddr_in6`, which aren't defined by libc on every platform.
+///
+/// # Safety
+/// `addr` must be null or point to a valid socket address of its family.
+pub(crate) unsafe fn sockaddr_to_ip(addr: *const sockaddr) -> Option<IpAddr> {
+    if addr.is_null() {
+        return None;
+    }
+
+    let bytes = addr as *const u8;
+
+    // sockaddr_in: family (2), port (2), address (4)
+    // sockaddr_in6: family (2), port (2), flow info (4), address (16)
+    match unsafe { (*addr).sa_family } as c_int {
+        AF_INET => {
+            let octets = unsafe { ptr::read_unaligned(bytes.add(4) as *const [u8; 4]) };
+            Some(Ipv4Addr::from(octets).into())
+        }
+        AF_INET6 => {
+            let octets = unsafe { ptr::read_unaligned(bytes.add(8) as *const [u8; 16]) };
+            Some(Ipv6Addr::from(octets).into())
+        }
+        _ => None,
+    }
+}
+
 pub(crate) fn err_cap<T>(
     name: &'static str,
     mut f: impl FnMut(*mut c_char) -> T,
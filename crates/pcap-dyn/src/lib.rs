@@ -1,7 +1,9 @@
 #![doc = include_str!("../README.md")]
 
 use std::{
-    fmt, ptr,
+    fmt,
+    net::IpAddr,
+    ptr,
     sync::{Arc, LazyLock},
 };
 
@@ -37,19 +39,49 @@ impl Api {
 
     /// A list of all available network devices.
     pub fn devices(&self) -> Result<Vec<Device>, Error> {
+        self.find_all_devices(|dev| unsafe { device_from_raw(dev) })
+    }
+
+    /// The addresses assigned to a network device.
+    pub fn device_addresses(&self, name: &str) -> Result<Vec<IpAddr>, Error> {
+        let addresses = self.find_all_devices(|dev| {
+            (ffi::cstr_to_string(dev.name)? == name).then(|| unsafe { addresses_from_raw(dev) })
+        })?;
+
+        Ok(addresses.into_iter().flatten().collect())
+    }
+
+    /// Start capturing packets from the given device.
+    ///
+    /// Traffic between private addresses is dropped unless `keep_private` is set,
+    /// in which case it's kept if one side is an address of the device.
+    pub fn open_capture(&self, device: Device, keep_private: bool) -> Result<Capture, Error> {
+        let local_addrs = match keep_private {
+            true => Some(self.device_addresses(&device.name)?),
+            false => None,
+        };
+
+        Capture::open(self.raw.clone(), device, local_addrs)
+    }
+
+    /// Run `f` on every device from `pcap_findalldevs`, collecting the results.
+    fn find_all_devices<T>(
+        &self,
+        mut f: impl FnMut(pcap_if_t) -> Option<T>,
+    ) -> Result<Vec<T>, Error> {
         let mut all_devs: *mut pcap_if_t = ptr::null_mut();
 
         ffi::err_cap("pcap_findalldevs", |e| unsafe {
             self.raw.pcap_findalldevs(&mut all_devs, e)
         })?;
 
-        let mut devices = Vec::new();
+        let mut res = Vec::new();
 
         let mut dev = all_devs;
         unsafe {
             while !dev.is_null() {
-                if let Some(device) = device_from_raw(*dev) {
-                    devices.push(device);
+                if let Some(item) = f(*dev) {
+                    res.push(item);
                 }
 
                 dev = (*dev).next;
@@ -58,13 +90,25 @@ impl Api {
             self.raw.pcap_freealldevs(all_devs);
         }
 
-        Ok(devices)
+        Ok(res)
     }
+}
 
-    /// Start capturing packets from the given device.
-    pub fn open_capture(&self, device: Device) -> Result<Capture, Error> {
-        Capture::open(self.raw.clone(), device)
+unsafe fn addresses_from_raw(value: pcap_if_t) -> Vec<IpAddr> {
+    let mut addresses = Vec::new();
+
+    let mut addr = value.addresses;
+    unsafe {
+        while !addr.is_null() {
+            if let Some(ip) = ffi::sockaddr_to_ip((*addr).addr) {
+                addresses.push(ip);
+            }
+
+            addr = (*addr).next;
+        }
     }
+
+    addresses
 }
 
 unsafe fn device_from_raw(value: pcap_if_t) -> Option<Device> {
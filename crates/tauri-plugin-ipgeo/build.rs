@@ -5,9 +5,20 @@ const COMMANDS: &[&str] = &[
     "set_selected_database",
     "database_state",
     "lookup_ip",
+    "find_networks",
     "lookup_dns",
     "lookup_host",
     "my_location",
+    "diff_databases",
+    "set_database_priority",
+    "lookup_ips",
+    "nearest_location",
+    "locations_within",
+    "search_locations",
+    "database_stats",
+    "audit_database",
+    "evaluate_databases",
+    "lookup_ip_all",
 ];
 
 fn main() {
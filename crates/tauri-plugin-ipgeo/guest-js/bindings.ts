@@ -18,8 +18,10 @@ async refreshCache() : Promise<Result<DbStateInfo, string>> {
 },
 /**
  * Load a [`DatabaseSource`] from its origin.
+ * 
+ * Malformed CSV records are skipped, and listed in the returned [`ImportReport`].
  */
-async downloadSource(source: DatabaseSource, nameResp: TAURI_CHANNEL<string>, progResp: TAURI_CHANNEL<number>) : Promise<Result<null, string>> {
+async downloadSource(source: DatabaseSource, nameResp: TAURI_CHANNEL<string>, progResp: TAURI_CHANNEL<number>) : Promise<Result<ImportReport, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|download_source", { source, nameResp, progResp }) };
 } catch (e) {
@@ -45,6 +47,18 @@ async setSelectedDatabase(source: DatabaseSource) : Promise<Result<null, string>
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Set the databases tried in order for lookups before the selected ones,
+ * falling through to the next on a miss or a country-level result.
+ */
+async setDatabasePriority(priority: DatabaseSource[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|set_database_priority", { priority }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Retrieve the current [`DbStateInfo`] of the database.
  * 
@@ -55,11 +69,131 @@ async databaseState() : Promise<DbStateInfo> {
     return await TAURI_INVOKE("plugin:ipgeo|database_state");
 },
 /**
- * Lookup a given [`IpAddr`] in the currently selected database(s).
+ * Lookup a given [`IpAddr`] in the databases of the lookup chain,
+ * including the network and database that matched, and who owns it if an ASN database is loaded.
  */
-async lookupIp(ip: string) : Promise<LookupInfo | null> {
+async lookupIp(ip: string) : Promise<IpLookup> {
     return await TAURI_INVOKE("plugin:ipgeo|lookup_ip", { ip });
 },
+/**
+ * Lookup a given [`IpAddr`] in every loaded database, selected or not,
+ * to see how much they disagree and where most of them place it.
+ */
+async lookupIpAll(ip: string) : Promise<Result<IpConsensus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|lookup_ip_all", { ip }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Lookup many addresses at once in the databases of the lookup chain, e.g. from a log file.
+ * 
+ * Results are sent through `results` in chunks, in the order given,
+ * or written to `csv_path` as `ip,country,region,city,lat,lng` rows instead if it's set.
+ */
+async lookupIps(ips: IpList, csvPath: string | null, results: TAURI_CHANNEL<BatchLookup[]>) : Promise<Result<BatchReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|lookup_ips", { ips, csvPath, results }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Find every network that resolves to a location in the databases of the lookup chain,
+ * the priority databases and then the selected ones.
+ */
+async findNetworks(query: LocationQuery) : Promise<Result<Network[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|find_networks", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Find the closest known location to a coordinate, e.g. a point clicked on the map,
+ * in the databases of the lookup chain.
+ */
+async nearestLocation(coordinate: Coordinate) : Promise<Result<NearbyLocation | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|nearest_location", { coordinate }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Find every known location within `radius_km` of a coordinate
+ * in the databases of the lookup chain, closest first.
+ */
+async locationsWithin(coordinate: Coordinate, radiusKm: number) : Promise<Result<NearbyLocation[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|locations_within", { coordinate, radiusKm }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Search the city and region names of the databases in the lookup chain, e.g. to autocomplete
+ * `Sao Pa` to São Paulo, ignoring case and accents and allowing typos in longer queries.
+ */
+async searchLocations(query: string, limit: number) : Promise<Result<LocationMatch[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|search_locations", { query, limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Compare two loaded databases, e.g. yesterday's and today's release of the same source.
+ */
+async diffDatabases(old: DatabaseSource, updated: DatabaseSource) : Promise<Result<DatabaseDiff, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|diff_databases", { old, updated }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Count the prefixes, addresses and locations a loaded database covers.
+ */
+async databaseStats(source: DatabaseSource) : Promise<Result<DatabaseStats, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|database_stats", { source }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Check the locations of a loaded database for common data-quality problems.
+ */
+async auditDatabase(source: DatabaseSource) : Promise<Result<AuditReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|audit_database", { source }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Measure every loaded database against a CSV file of `ip,lat,lng,country` rows
+ * for addresses whose real location is known, e.g. offices and CDN points of presence.
+ */
+async evaluateDatabases(csvPath: string) : Promise<Result<EvaluationReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("plugin:ipgeo|evaluate_databases", { csvPath }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 /**
  * Get a hostname with the system for a given [`IpAddr`].
  */
@@ -99,6 +233,129 @@ dbStateChange: "plugin:ipgeo:db-state-change"
 
 /** user-defined types **/
 
+/**
+ * The autonomous system that owns a network.
+ */
+export type AsnInfo = { 
+/**
+ * Autonomous system number, e.g. `13335`
+ */
+number: number; 
+/**
+ * Name of the organization operating the system, e.g. `Cloudflare, Inc.`
+ */
+organization: string | null }
+/**
+ * A problem found by an [`Audit`].
+ */
+export type AuditCheck = 
+/**
+ * At or near 0,0, where addresses without a known location often end up.
+ */
+"nullIsland" | 
+/**
+ * A city at its country's centroid, where the city is likely a guess.
+ */
+"countryCentroid" | 
+/**
+ * Outside the bounding box of the location's country.
+ */
+"outsideCountry" | 
+/**
+ * Another city at the same coordinate, within the precision coordinates are stored with.
+ * 
+ * Imports keep the first location at a coordinate, and set the others aside for this check.
+ * Names that only differ in case or accents aren't a conflict.
+ */
+"conflictingNames" | 
+/**
+ * Only the country is known, but the coordinate is far from the country's centroid,
+ * so it looks like a specific place.
+ */
+"specificWithoutCity"
+/**
+ * A location that failed an [`AuditCheck`].
+ */
+export type AuditFinding = { check: AuditCheck; crd: Coordinate; loc: Location; 
+/**
+ * Network prefixes placed at the coordinate.
+ */
+prefixes: number; 
+/**
+ * The other location, for [`AuditCheck::ConflictingNames`].
+ */
+conflict: Location | null }
+/**
+ * The findings of an [`Audit`] of a database.
+ */
+export type AuditReport = { 
+/**
+ * Distinct coordinates with a location.
+ */
+locations: number; prefixes: number; 
+/**
+ * Every finding, sorted by country code, check, and coordinate.
+ */
+findings: AuditFinding[]; 
+/**
+ * Totals per country, sorted by country code, to compare databases region by region.
+ */
+countries: CountryAudit[] }
+/**
+ * Where a single address of an [`IpList`] is located.
+ */
+export type BatchLookup = { ip: string; info: LookupInfo | null }
+/**
+ * Summary of a [`lookup_ips`](crate::commands::lookup_ips) run.
+ */
+export type BatchReport = { 
+/**
+ * Addresses looked up.
+ */
+total: number; 
+/**
+ * Addresses that were found in a database.
+ */
+found: number; 
+/**
+ * Lines of the file that didn't start with an address.
+ */
+invalid: number }
+/**
+ * Where most databases place an address, and how far apart they are, see [`consensus`].
+ */
+export type Consensus = { 
+/**
+ * The country the most answers agree on, ties go to the earliest answer.
+ */
+countryCode: string; 
+/**
+ * Median latitude and longitude of the answers in `country_code`.
+ * 
+ * Longitudes are compared the short way around, so answers either side of the antimeridian
+ * have a median next to it rather than on the other side of the world.
+ */
+crd: Coordinate; 
+/**
+ * Answers combined.
+ */
+answers: number; 
+/**
+ * Answers placing the address in `country_code`.
+ */
+agreeing: number; 
+/**
+ * Greatest distance between any two answers.
+ */
+maxSpreadKm: number; 
+/**
+ * Mean distance between every pair of answers.
+ */
+meanSpreadKm: number }
+/**
+ * A continent, as assigned to countries by GeoNames.
+ */
+export type Continent = "africa" | "antarctica" | "asia" | "europe" | "northAmerica" | "oceania" | "southAmerica"
 /**
  * A basic latitude/longitude pair.
  */
@@ -111,12 +368,115 @@ lat: number;
  * Longitude
  */
 lng: number }
+/**
+ * Details of an ISO 3166-1 country, see [`CountryCode::country`].
+ */
+export type Country = { names: CountryNames; continent: Continent; 
+/**
+ * Flag emoji, made of the code's two regional indicator symbols.
+ */
+flag: string }
+/**
+ * Audited locations and prefixes in a single country.
+ */
+export type CountryAudit = { countryCode: string; 
+/**
+ * Distinct coordinates with a location in the country.
+ */
+locations: number; prefixes: number; 
+/**
+ * Locations with at least one finding.
+ */
+flaggedLocations: number; 
+/**
+ * Prefixes placed at a flagged location.
+ */
+flaggedPrefixes: number }
+/**
+ * Changed prefixes in a single country.
+ * 
+ * Added and relocated prefixes are counted in their new country, removed ones in their old.
+ */
+export type CountryDiff = { countryCode: string; added: number; removed: number; relocated: number }
+/**
+ * A country's name in each [`Language`].
+ */
+export type CountryNames = { en: string; es: string; zh: string }
+/**
+ * Prefixes and addresses placed in a single country.
+ */
+export type CountryStats = { countryCode: string; ipv4Prefixes: number; ipv4Addresses: number; ipv6Prefixes: number; ipv6Addresses: number }
+/**
+ * What changed between two releases of a database, see [`diff`].
+ */
+export type DatabaseDiff = { 
+/**
+ * Prefixes only in the new database.
+ */
+added: number; 
+/**
+ * Prefixes only in the old database.
+ */
+removed: number; 
+/**
+ * Prefixes in both databases that were moved to another coordinate.
+ */
+relocated: number; 
+/**
+ * Prefixes in both databases at the same coordinate.
+ */
+unchanged: number; 
+/**
+ * Changes per country, sorted by country code.
+ */
+countries: CountryDiff[]; 
+/**
+ * The [`DatabaseDiff::MAX_MOVES`] relocated prefixes that moved the furthest, furthest first.
+ */
+largestMoves: PrefixMove[] }
+/**
+ * How accurately a loaded database placed the addresses of a ground truth file.
+ */
+export type DatabaseEvaluation = { source: DatabaseSource; evaluation: Evaluation }
+/**
+ * Where a single loaded database places an address.
+ */
+export type DatabaseLookup = { source: DatabaseSource; network: Network | null; info: LookupInfo | null }
 /**
  * Sources for where this database came from, as given to the user.
  * This allows us to de-duplicate common databases and download them
  * in-application.
  */
-export type DatabaseSource = "dbipcombined" | "geolite2combined" | { file: string }
+export type DatabaseSource = "dbipcombined" | "geolite2combined" | { file: string } | "asn" | 
+/**
+ * An IPv4 and an IPv6 file, or a directory holding them, loaded as one combined database.
+ */
+{ files: string[] } | 
+/**
+ * A TOML or CSV file mapping private networks to named sites, e.g. internal offices,
+ * which is put at the front of the lookup priority chain.
+ */
+{ overlay: string }
+/**
+ * What a database covers, see [`Statistics`].
+ */
+export type DatabaseStats = { ipv4: FamilyStats; ipv6: FamilyStats; 
+/**
+ * Coverage of every country with at least one prefix, sorted by country code.
+ */
+countries: CountryStats[]; 
+/**
+ * Distinct locations, each at its own coordinate.
+ */
+locations: number; 
+/**
+ * Locations with nothing more specific than their country.
+ */
+countryLevelLocations: number; 
+/**
+ * Distinct city, region and other location name strings.
+ */
+strings: number }
 /**
  * Information about the loaded and selected databases in a [`DbSet`].
  */
@@ -130,15 +490,250 @@ export type DbStateChange = DbStateInfo
 /**
  * Summary of the loaded and selected databases for each IP type.
  */
-export type DbStateInfo = { ipv4: DbSetInfo; ipv6: DbSetInfo; combined: DbSetInfo }
+export type DbStateInfo = { ipv4: DbSetInfo; ipv6: DbSetInfo; combined: DbSetInfo; asn: DbSetInfo; 
+/**
+ * Databases tried in order for lookups before the selected ones, see [`DbState::set_priority`].
+ */
+priority: DatabaseSource[] }
+/**
+ * How accurately a database placed a set of [`GroundTruth`] addresses, see [`evaluate`].
+ */
+export type Evaluation = { 
+/**
+ * Addresses looked up.
+ */
+total: number; 
+/**
+ * Addresses that were found in the database.
+ */
+found: number; 
+/**
+ * Found addresses placed in their real country.
+ */
+countryMatches: number; 
+/**
+ * `country_matches` out of `found`, from 0 to 1.
+ */
+countryMatchRate: number; 
+/**
+ * Median distance between where found addresses were placed and where they are.
+ */
+medianErrorKm: number | null; 
+/**
+ * 90th percentile of the same distances, nine in ten found addresses are at least this close.
+ */
+p90ErrorKm: number | null; 
+/**
+ * Addresses that weren't found or were placed in another country, in the order given.
+ */
+misses: EvaluationMiss[] }
+/**
+ * A [`GroundTruth`] address the database didn't find, or placed in another country.
+ */
+export type EvaluationMiss = { ip: string; 
+/**
+ * The address's real country.
+ */
+expectedCountry: string; 
+/**
+ * The country the database placed the address in, if it was found.
+ */
+foundCountry: string | null; 
+/**
+ * Distance from the address's real coordinate, if it was found.
+ */
+errorKm: number | null }
+/**
+ * Summary of an [`evaluate_databases`](crate::commands::evaluate_databases) run.
+ */
+export type EvaluationReport = { 
+/**
+ * Rows of the file that weren't an `ip,lat,lng,country` record.
+ */
+invalid: number; 
+/**
+ * Every loaded database, lowest median error first.
+ */
+databases: DatabaseEvaluation[] }
+/**
+ * Coverage of a single address family.
+ */
+export type FamilyStats = { prefixes: number; 
+/**
+ * Addresses covered by the prefixes, each counted once, a float since IPv6 counts overflow any integer.
+ */
+addresses: number; 
+/**
+ * Prefixes placed at a location with nothing more specific than its country.
+ */
+countryLevelPrefixes: number; 
+/**
+ * Bytes used by the lookup table's trie nodes and results.
+ */
+tableBytes: number }
+/**
+ * A single malformed record.
+ */
+export type ImportIssue = { 
+/**
+ * One-based line number in the file, `0` if unknown.
+ */
+line: number; 
+/**
+ * Why the record was skipped.
+ */
+reason: string }
+/**
+ * What happened while leniently importing a database.
+ */
+export type ImportReport = { 
+/**
+ * Records that were read without issue.
+ */
+imported: number; 
+/**
+ * Malformed records that were skipped.
+ */
+skipped: number; 
+/**
+ * The first [`ImportReport::MAX_ISSUES`] skipped records, in the order they were found.
+ */
+issues: ImportIssue[] }
+/**
+ * What every loaded database knows about an IP address, see [`DbState::lookup_all`].
+ */
+export type IpConsensus = { 
+/**
+ * The answer of each database, including those that didn't find the address.
+ */
+answers: DatabaseLookup[]; 
+/**
+ * Where most databases place the address and how far apart they are,
+ * if any database found it.
+ */
+consensus: Consensus | null; 
+/**
+ * Who the address belongs to.
+ */
+asn: AsnInfo | null }
+/**
+ * Addresses to look up at once with [`lookup_ips`](crate::commands::lookup_ips).
+ */
+export type IpList = { ips: string[] } | 
+/**
+ * A text file with an address at the start of each line, e.g. an access log, see [`ipgeo::read_ips`].
+ */
+{ file: string }
+/**
+ * Everything the selected databases know about an IP address.
+ */
+export type IpLookup = { 
+/**
+ * Where the address is located.
+ */
+info: LookupInfo | null; 
+/**
+ * The most specific network containing the address in the database that answered.
+ */
+network: Network | null; 
+/**
+ * The database that answered, which matters once several are loaded.
+ */
+source: DatabaseSource | null; 
+/**
+ * Who the address belongs to.
+ */
+asn: AsnInfo | null }
 /**
  * A [`Coordinate`]'s associated city, region, and country.
  */
-export type Location = { city: string | null; region: string | null; countryCode: string }
+export type Location = { city: string | null; region: string | null; countryCode: string; 
+/**
+ * Second-level region (`state2`), e.g. a county or district.
+ */
+subregion: string | null; postcode: string | null; 
+/**
+ * IANA time zone name, e.g. `Asia/Shanghai`.
+ */
+timezone: string | null; 
+/**
+ * Name, continent and flag of `country_code`, if it's a known ISO 3166-1 country.
+ */
+country: Country | null }
+/**
+ * A location whose city or region name matched a [`DbState::search_locations`].
+ */
+export type LocationMatch = { crd: Coordinate; loc: Location; 
+/**
+ * Network prefixes mapped to the location.
+ */
+networks: number; 
+/**
+ * Typos between the query and the matched name, `0` for a prefix match.
+ */
+typos: number }
+/**
+ * A location to search for the networks that resolve to it.
+ */
+export type LocationQuery = 
+/**
+ * An exact coordinate, within the precision of the database.
+ */
+{ coordinate: Coordinate } | 
+/**
+ * A city name, matched case-insensitively.
+ */
+{ city: string } | 
+/**
+ * A region/state name, matched case-insensitively.
+ */
+{ region: string } | 
+/**
+ * An ISO 3166 2-digit country code.
+ */
+{ country: string } | 
+/**
+ * Every coordinate within a distance of another, e.g. a point clicked on a map.
+ * 
+ * This checks every coordinate in the database, search a [`SpatialIndex`](crate::SpatialIndex)
+ * and use [`ReverseLookup::find_networks_at`] instead to repeat it.
+ */
+{ near: { coordinate: Coordinate; radiusKm: number } }
 /**
  * A [`Coordinate`]/[`Location`] pair.
  */
-export type LookupInfo = { crd: Coordinate; loc: Location }
+export type LookupInfo = { crd: Coordinate; loc: Location; 
+/**
+ * Nothing more specific than the country is known, see [`Location::is_country_level`].
+ * 
+ * `crd` is then usually somewhere central in the country, but that's up to the database.
+ */
+countryLevel: boolean; 
+/**
+ * The address had an IPv4 address embedded in it (IPv4-mapped, 6to4, NAT64 or Teredo),
+ * which was looked up instead.
+ */
+translated: boolean }
+/**
+ * A location close to a searched coordinate.
+ */
+export type NearbyLocation = { crd: Coordinate; loc: Location; 
+/**
+ * Great-circle distance from the searched coordinate.
+ */
+distanceKm: number }
+/**
+ * An IP network prefix found in a database.
+ */
+export type Network = { ip: string; prefix: number }
+/**
+ * A prefix that was moved to another coordinate.
+ */
+export type PrefixMove = { network: Network; from: Coordinate; to: Coordinate; fromCountry: string; toCountry: string; 
+/**
+ * Great-circle distance between `from` and `to`.
+ */
+distanceKm: number }
 
 /** tauri-specta globals **/
 
@@ -4,6 +4,7 @@ import {
   type DbStateInfo,
   type DbSetInfo,
   type DatabaseSource,
+  type ImportReport,
 } from "./bindings";
 
 import * as dialog from "@tauri-apps/plugin-dialog";
@@ -16,18 +17,36 @@ const displayError = (messageText: string) => {
   dialog.message(messageText, { title: "Database Error", kind: "error" });
 };
 
+const displayImportReport = (name: string | null, report: ImportReport) => {
+  if (report.skipped == 0) return;
+
+  const lines = report.issues
+    .map((issue) => `line ${issue.line}: ${issue.reason}`)
+    .join("\n");
+  const more = report.skipped - report.issues.length;
+
+  dialog.message(
+    `Imported ${report.imported} records, skipped ${report.skipped} malformed records:\n\n${lines}` +
+      (more > 0 ? `\n...and ${more} more` : ""),
+    { title: `Partially Loaded ${name ?? "Database"}`, kind: "warning" },
+  );
+};
+
 type LoadingState = { name: string | null; progress: number | null } | null;
 
 class Database implements DbStateInfo {
   ipv4: DbSetInfo = $state({ loaded: [], selected: null });
   ipv6: DbSetInfo = $state({ loaded: [], selected: null });
   combined: DbSetInfo = $state({ loaded: [], selected: null });
+  asn: DbSetInfo = $state({ loaded: [], selected: null });
+  priority: DatabaseSource[] = $state([]);
 
   loading: LoadingState | null = $state(null);
 
   combinedEnabled: boolean = $derived(this.combined.selected != null);
   ipv4Enabled: boolean = $derived(this.ipv4.selected != null);
   ipv6Enabled: boolean = $derived(this.ipv6.selected != null);
+  asnEnabled: boolean = $derived(this.asn.selected != null);
 
   anyEnabled: boolean = $derived(
     this.ipv4Enabled || this.ipv6Enabled || this.combinedEnabled,
@@ -49,6 +68,8 @@ class Database implements DbStateInfo {
     this.ipv4 = state.ipv4;
     this.ipv6 = state.ipv6;
     this.combined = state.combined;
+    this.asn = state.asn;
+    this.priority = state.priority;
     if (!this.responseBack) this.responseBack = true;
   };
 
@@ -73,17 +94,22 @@ class Database implements DbStateInfo {
 
     if (res.status == "error") {
       displayError(res.error);
+    } else {
+      displayImportReport(this.loading?.name ?? null, res.data);
     }
 
     this.loading = null;
   };
 
+  /**
+   * Open a database file, or an IPv4 and an IPv6 file together as one combined database.
+   */
   openFile = async () => {
     if (this.loading) return;
 
-    const file = await dialog.open({
+    const files = await dialog.open({
       title: "Open IP Geolocation City Database",
-      multiple: false,
+      multiple: true,
       directory: false,
       filters: [
         {
@@ -93,11 +119,56 @@ class Database implements DbStateInfo {
       ],
     });
 
+    if (!files || files.length == 0) return;
+
+    console.log("opening database", files);
+
+    this.downloadSource(files.length == 1 ? { file: files[0] } : { files });
+  };
+
+  /**
+   * Open a directory holding an IPv4 and an IPv6 database as one combined database.
+   */
+  openDirectory = async () => {
+    if (this.loading) return;
+
+    const directory = await dialog.open({
+      title: "Open IP Geolocation City Database Directory",
+      multiple: false,
+      directory: true,
+    });
+
+    if (!directory) return;
+
+    console.log("opening database directory", directory);
+
+    this.downloadSource({ files: [directory] });
+  };
+
+  /**
+   * Open a TOML or CSV file mapping private networks to named sites, e.g. internal offices,
+   * which is looked up before every other database.
+   */
+  openOverlay = async () => {
+    if (this.loading) return;
+
+    const file = await dialog.open({
+      title: "Open Private Network Overlay",
+      multiple: false,
+      directory: false,
+      filters: [
+        {
+          name: "Private Network Overlay",
+          extensions: ["toml", "csv"],
+        },
+      ],
+    });
+
     if (!file) return;
 
-    console.log("opening database", file);
+    console.log("opening overlay", file);
 
-    this.downloadSource({ file });
+    this.downloadSource({ overlay: file });
   };
 
   /**
@@ -107,6 +178,15 @@ class Database implements DbStateInfo {
     if (name) commands.setSelectedDatabase(name);
   };
 
+  /**
+   * Set the databases tried in order for lookups before the selected ones,
+   * e.g. in-house overrides, then GeoLite2, then DB-IP.
+   */
+  setPriority = async (priority: DatabaseSource[]) => {
+    const res = await commands.setDatabasePriority(priority);
+    if (res.status == "error") displayError(res.error);
+  };
+
   /**
    * Unload the database, freeing up memory.
    */
@@ -115,6 +195,16 @@ class Database implements DbStateInfo {
   };
 
   lookupIp = commands.lookupIp;
+  lookupIpAll = commands.lookupIpAll;
+  lookupIps = commands.lookupIps;
+  findNetworks = commands.findNetworks;
+  nearestLocation = commands.nearestLocation;
+  locationsWithin = commands.locationsWithin;
+  searchLocations = commands.searchLocations;
+  diffDatabases = commands.diffDatabases;
+  databaseStats = commands.databaseStats;
+  auditDatabase = commands.auditDatabase;
+  evaluateDatabases = commands.evaluateDatabases;
   lookupDns = commands.lookupDns;
   lookupHost = commands.lookupHost;
 }
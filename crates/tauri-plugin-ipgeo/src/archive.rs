@@ -43,6 +43,35 @@ pub fn resource_dir_list(dir: &Path) -> anyhow::Result<impl Iterator<Item = (Pat
     Ok(r)
 }
 
+/// Delete the resources of every format version but `current` in a directory
+/// holding a `v<version>` subdirectory per version.
+///
+/// Resources are read without validation, so those of another version can't be read safely.
+/// Resources directly in `dir` are from before versioning and are deleted too,
+/// anything else is kept.
+pub fn remove_stale_versions(dir: &Path, current: u32) -> anyhow::Result<()> {
+    for (path, _) in resource_dir_list(dir)? {
+        tracing::info!("removing unversioned archive {path:?}");
+        fs::remove_file(path)?;
+    }
+
+    for entry in fs::read_dir(dir)?.filter_map(|d| d.ok()) {
+        let stale = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix('v'))
+            .and_then(|version| version.parse::<u32>().ok())
+            .is_some_and(|version| version != current);
+
+        if stale && entry.file_type().is_ok_and(|ft| ft.is_dir()) {
+            tracing::info!("removing stale archive version {:?}", entry.path());
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Represents a memory-mapped, checksummed [`rkyv`] archive file resource
 /// for any [`Serialize`] and [`Archive`] type.
 ///
@@ -74,9 +103,7 @@ where
     /// The resulting file's name is generated based on the checksum of the data, it can be found at [`FileResource::path`].
     ///
     pub fn create(dir: impl AsRef<Path>, data: &T) -> anyhow::Result<Self> {
-        if let Some(parent) = dir.as_ref().parent() {
-            fs::create_dir_all(parent)?;
-        }
+        fs::create_dir_all(dir.as_ref())?;
 
         let temp_path = dir
             .as_ref()
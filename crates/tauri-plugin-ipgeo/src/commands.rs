@@ -1,14 +1,29 @@
 //! Accessors to the runtime [`DbState`] for the frontend UI.
 
-use std::{net::IpAddr, path::PathBuf, time::Duration};
-
-use ipgeo::{CombinedDatabase, Database, LookupInfo, download::CombinedDatabaseSource};
-use tauri::{AppHandle, Runtime, State, ipc::Channel};
-
-use crate::{DatabaseSource, DbState, DbStateInfo, DynamicDatabase};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    net::IpAddr,
+    path::PathBuf,
+    time::Duration,
+};
+
+use ipgeo::{
+    AsnDatabase, AsnLookup, Audit, AuditReport, CombinedDatabase, Coordinate, Database,
+    DatabaseDiff, DatabaseStats, ImportReport, LocationQuery, NearbyLocation, Network,
+    ReverseLookup, Statistics, download::CombinedDatabaseSource,
+};
+use tauri::{AppHandle, Manager, Runtime, State, ipc::Channel};
+
+use crate::{
+    BatchLookup, BatchReport, DatabaseSource, DbState, DbStateInfo, DynamicDatabase,
+    EvaluationReport, IpConsensus, IpList, IpLookup, LocationMatch,
+};
 
 const DNS_LOOKUP_TIMEOUT: Duration = Duration::from_millis(300);
 const DOWNLOAD_REPORT_GAP: Duration = Duration::from_millis(200);
+/// How many [`lookup_ips`] results are sent through the channel at once.
+const BATCH_RESULTS_CHUNK: usize = 10_000;
 
 #[cfg(debug_assertions)]
 macro_rules! ip_location_db {
@@ -45,6 +60,8 @@ pub async fn refresh_cache<R: tauri::Runtime>(
 }
 
 /// Load a [`DatabaseSource`] from its origin.
+///
+/// Malformed CSV records are skipped, and listed in the returned [`ImportReport`].
 #[tauri::command]
 #[specta::specta]
 pub async fn download_source<R: Runtime>(
@@ -53,15 +70,15 @@ pub async fn download_source<R: Runtime>(
     source: DatabaseSource,
     name_resp: Channel<&str>,
     prog_resp: Channel<f64>,
-) -> Result<(), String> {
+) -> Result<ImportReport, String> {
     tracing::info!("downloading {source:?}");
 
     let name = source.to_string();
 
     let _ = name_resp.send(&name);
 
-    let db = match download_source_internal(prog_resp, &source).await {
-        Ok(db) => db,
+    let (db, report) = match download_source_internal(prog_resp, &source).await {
+        Ok(res) => res,
         Err(err) => {
             let err = format!("failed to download database: {err}");
             tracing::error!("{err}");
@@ -75,13 +92,17 @@ pub async fn download_source<R: Runtime>(
     })?;
     state.emit_info(&handle);
 
-    Ok(())
+    if !report.is_clean() {
+        tracing::warn!("skipped {} malformed records in {name}", report.skipped);
+    }
+
+    Ok(report)
 }
 
 async fn download_source_internal(
     progress_sender: Channel<f64>,
     source: &DatabaseSource,
-) -> anyhow::Result<DynamicDatabase> {
+) -> anyhow::Result<(DynamicDatabase, ImportReport)> {
     let cb = move |val: u64, max: u64| {
         let _ = progress_sender.send(val as f64 / max as f64);
     };
@@ -94,9 +115,9 @@ async fn download_source_internal(
                 is_num: true,
             };
 
-            CombinedDatabase::download(src, DOWNLOAD_REPORT_GAP, cb)
+            CombinedDatabase::download_lenient(src, DOWNLOAD_REPORT_GAP, cb)
                 .await
-                .map(DynamicDatabase::Combined)?
+                .map(|(db, report)| (DynamicDatabase::Combined(db), report))?
         }
         DatabaseSource::Geolite2Combined => {
             let src = CombinedDatabaseSource {
@@ -105,16 +126,46 @@ async fn download_source_internal(
                 is_num: true,
             };
 
-            CombinedDatabase::download(src, DOWNLOAD_REPORT_GAP, cb)
+            CombinedDatabase::download_lenient(src, DOWNLOAD_REPORT_GAP, cb)
+                .await
+                .map(|(db, report)| (DynamicDatabase::Combined(db), report))?
+        }
+        DatabaseSource::Asn => {
+            let src = CombinedDatabaseSource {
+                ipv4_csv_url: ip_location_db!("asn/asn-ipv4-num.csv"),
+                ipv6_csv_url: ip_location_db!("asn/asn-ipv6-num.csv"),
+                is_num: true,
+            };
+
+            AsnDatabase::download(src, DOWNLOAD_REPORT_GAP, cb)
                 .await
-                .map(DynamicDatabase::Combined)?
+                .map(|db| (DynamicDatabase::Asn(db), ImportReport::default()))?
         }
         DatabaseSource::File(path) => {
             let path = PathBuf::from(path);
 
-            tokio::task::spawn_blocking(move || ipgeo::detect(&path))
-                .await?
-                .map(DynamicDatabase::Generic)?
+            tokio::task::spawn_blocking(move || {
+                ipgeo::detect_any(&path).map(|(db, report)| (DynamicDatabase::from(db), report))
+            })
+            .await??
+        }
+        DatabaseSource::Files(paths) => {
+            let paths = paths.clone();
+
+            tokio::task::spawn_blocking(move || {
+                ipgeo::detect_combined(&paths)
+                    .map(|db| (DynamicDatabase::Combined(db), ImportReport::default()))
+            })
+            .await??
+        }
+        DatabaseSource::Overlay(path) => {
+            let path = PathBuf::from(path);
+
+            tokio::task::spawn_blocking(move || {
+                CombinedDatabase::from_overlay(&path)
+                    .map(|db| (DynamicDatabase::Combined(db), ImportReport::default()))
+            })
+            .await??
         }
     };
 
@@ -152,6 +203,25 @@ pub async fn set_selected_database<R: tauri::Runtime>(
     Ok(())
 }
 
+/// Set the databases tried in order for lookups before the selected ones,
+/// falling through to the next on a miss or a country-level result.
+#[tauri::command]
+#[specta::specta]
+pub fn set_database_priority<R: tauri::Runtime>(
+    app: tauri::AppHandle<R>,
+    state: State<'_, DbState>,
+    priority: Vec<DatabaseSource>,
+) -> Result<(), String> {
+    tracing::info!("set database priority to {priority:?}");
+
+    state
+        .set_priority(priority)
+        .map_err(|err| err.to_string())?;
+    state.emit_info(&app);
+
+    Ok(())
+}
+
 /// Retrieve the current [`DbStateInfo`] of the database.
 ///
 /// This info is given out in [`DbStateChange`](super::DbStateChange),
@@ -162,11 +232,217 @@ pub fn database_state(state: State<'_, DbState>) -> DbStateInfo {
     state.info()
 }
 
-/// Lookup a given [`IpAddr`] in the currently selected database(s).
+/// Lookup a given [`IpAddr`] in the databases of the lookup chain,
+/// including the network and database that matched, and who owns it if an ASN database is loaded.
+#[tauri::command]
+#[specta::specta]
+pub fn lookup_ip(state: State<'_, DbState>, ip: IpAddr) -> IpLookup {
+    let (matched, source) = state.lookup(ip).unzip();
+
+    IpLookup {
+        network: matched.as_ref().map(|matched| matched.network),
+        info: matched.map(|matched| matched.info),
+        source,
+        asn: state.get_asn(ip),
+    }
+}
+
+/// Lookup a given [`IpAddr`] in every loaded database, selected or not,
+/// to see how much they disagree and where most of them place it.
+#[tauri::command]
+#[specta::specta]
+pub async fn lookup_ip_all<R: Runtime>(
+    app: AppHandle<R>,
+    ip: IpAddr,
+) -> Result<IpConsensus, String> {
+    tokio::task::spawn_blocking(move || app.state::<DbState>().lookup_all(ip))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Lookup many addresses at once in the databases of the lookup chain, e.g. from a log file.
+///
+/// Results are sent through `results` in chunks, in the order given,
+/// or written to `csv_path` as `ip,country,region,city,lat,lng` rows instead if it's set.
+#[tauri::command]
+#[specta::specta]
+pub async fn lookup_ips<R: Runtime>(
+    app: AppHandle<R>,
+    ips: IpList,
+    csv_path: Option<String>,
+    results: Channel<Vec<BatchLookup>>,
+) -> Result<BatchReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let (ips, invalid) = match ips {
+            IpList::Ips(ips) => (ips, 0),
+            IpList::File(path) => ipgeo::read_ips(BufReader::new(File::open(path)?))?,
+        };
+
+        tracing::info!("looking up {} addresses", ips.len());
+
+        let matches = app.state::<DbState>().get_batch(&ips, true);
+        let lookups = ips
+            .iter()
+            .zip(&matches)
+            .map(|(ip, matched)| (*ip, matched.as_ref().map(|m| &m.info)));
+
+        match csv_path {
+            Some(path) => ipgeo::write_lookups_csv(BufWriter::new(File::create(path)?), lookups)?,
+            None => {
+                let lookups = lookups
+                    .map(|(ip, info)| BatchLookup {
+                        ip,
+                        info: info.cloned(),
+                    })
+                    .collect::<Vec<_>>();
+
+                for chunk in lookups.chunks(BATCH_RESULTS_CHUNK) {
+                    results.send(chunk.to_vec())?;
+                }
+            }
+        }
+
+        anyhow::Ok(BatchReport {
+            total: ips.len() as u64,
+            found: matches.iter().filter(|m| m.is_some()).count() as u64,
+            invalid,
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())
+}
+
+/// Compare two loaded databases, e.g. yesterday's and today's release of the same source.
 #[tauri::command]
 #[specta::specta]
-pub fn lookup_ip(state: State<'_, DbState>, ip: IpAddr) -> Option<LookupInfo> {
-    state.get(ip)
+pub async fn diff_databases(
+    state: State<'_, DbState>,
+    old: DatabaseSource,
+    updated: DatabaseSource,
+) -> Result<DatabaseDiff, String> {
+    let (Some(old), Some(updated)) = (state.get_archive(&old), state.get_archive(&updated)) else {
+        return Err("Database is not loaded".into());
+    };
+
+    tokio::task::spawn_blocking(move || ipgeo::diff(&old.db, &updated.db))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Count the prefixes, addresses and locations a loaded database covers.
+#[tauri::command]
+#[specta::specta]
+pub async fn database_stats(
+    state: State<'_, DbState>,
+    source: DatabaseSource,
+) -> Result<DatabaseStats, String> {
+    let Some(archive) = state.get_archive(&source) else {
+        return Err("Database is not loaded".into());
+    };
+
+    tokio::task::spawn_blocking(move || archive.db.stats())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Check the locations of a loaded database for common data-quality problems.
+#[tauri::command]
+#[specta::specta]
+pub async fn audit_database(
+    state: State<'_, DbState>,
+    source: DatabaseSource,
+) -> Result<AuditReport, String> {
+    let Some(archive) = state.get_archive(&source) else {
+        return Err("Database is not loaded".into());
+    };
+
+    tokio::task::spawn_blocking(move || archive.db.audit())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Measure every loaded database against a CSV file of `ip,lat,lng,country` rows
+/// for addresses whose real location is known, e.g. offices and CDN points of presence.
+#[tauri::command]
+#[specta::specta]
+pub async fn evaluate_databases<R: Runtime>(
+    app: AppHandle<R>,
+    csv_path: String,
+) -> Result<EvaluationReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let (truth, invalid) = ipgeo::read_ground_truth(BufReader::new(File::open(csv_path)?))?;
+
+        tracing::info!("evaluating databases against {} addresses", truth.len());
+
+        anyhow::Ok(EvaluationReport {
+            invalid,
+            databases: app.state::<DbState>().evaluate(&truth),
+        })
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())
+}
+
+/// Find every network that resolves to a location in the databases of the lookup chain,
+/// the priority databases and then the selected ones.
+#[tauri::command]
+#[specta::specta]
+pub async fn find_networks<R: Runtime>(
+    app: AppHandle<R>,
+    query: LocationQuery,
+) -> Result<Vec<Network>, String> {
+    tokio::task::spawn_blocking(move || app.state::<DbState>().find_networks(&query))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Find the closest known location to a coordinate, e.g. a point clicked on the map,
+/// in the databases of the lookup chain.
+#[tauri::command]
+#[specta::specta]
+pub async fn nearest_location<R: Runtime>(
+    app: AppHandle<R>,
+    coordinate: Coordinate,
+) -> Result<Option<NearbyLocation>, String> {
+    tokio::task::spawn_blocking(move || app.state::<DbState>().nearest_location(coordinate))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Find every known location within `radius_km` of a coordinate
+/// in the databases of the lookup chain, closest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn locations_within<R: Runtime>(
+    app: AppHandle<R>,
+    coordinate: Coordinate,
+    radius_km: f32,
+) -> Result<Vec<NearbyLocation>, String> {
+    tokio::task::spawn_blocking(move || {
+        app.state::<DbState>()
+            .locations_within(coordinate, radius_km)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Search the city and region names of the databases in the lookup chain, e.g. to autocomplete
+/// `Sao Pa` to São Paulo, ignoring case and accents and allowing typos in longer queries.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_locations<R: Runtime>(
+    app: AppHandle<R>,
+    query: String,
+    limit: u32,
+) -> Result<Vec<LocationMatch>, String> {
+    tokio::task::spawn_blocking(move || {
+        app.state::<DbState>()
+            .search_locations(&query, limit as usize)
+    })
+    .await
+    .map_err(|err| err.to_string())
 }
 
 async fn blocking_timeout<F, T>(f: F) -> Option<T>
@@ -7,9 +7,18 @@ use std::{
 };
 
 use ipgeo::{
-    ArchivedGenericDatabase, CombinedDatabase, Coordinate, Database, GenericDatabase, Location,
+    ArchivedGenericDatabase, AsnDatabase, AsnInfo, AsnLookup, Audit, AuditReport, CombinedDatabase,
+    Coordinate, Database, DatabaseStats, DetectedDatabase, GenericDatabase, Location,
+    LocationQuery, LocationSearch, Network, Prefix, Prefixes, ReverseLookup, SearchIndex,
+    SpatialIndex, SpatialLookup, Statistics,
 };
 
+/// Version of the archived [`DiskArchive`] layout, archives are kept in a directory per version.
+///
+/// Archives are read without validation, so this must be bumped whenever [`DiskArchive`]
+/// or any archived `ipgeo` type changes, and the archives of other versions are deleted.
+pub const ARCHIVE_VERSION: u32 = 2;
+
 /// The base structure stored in the file, identifying a generic IP-geolocation database.
 #[derive(rkyv::Archive, rkyv::Deserialize, rkyv::Serialize)]
 pub struct DiskArchive {
@@ -39,6 +48,12 @@ pub enum DatabaseSource {
     DbIpCombined,
     Geolite2Combined,
     File(String),
+    Asn,
+    /// An IPv4 and an IPv6 file, or a directory holding them, loaded as one combined database.
+    Files(Vec<String>),
+    /// A TOML or CSV file mapping private networks to named sites, e.g. internal offices,
+    /// which is put at the front of the lookup priority chain.
+    Overlay(String),
 }
 
 /// A generic database type that can represent any kind of IP address database.
@@ -51,6 +66,17 @@ pub enum DatabaseSource {
 pub enum DynamicDatabase {
     Combined(CombinedDatabase),
     Generic(GenericDatabase),
+    Asn(AsnDatabase),
+}
+
+impl From<DetectedDatabase> for DynamicDatabase {
+    fn from(db: DetectedDatabase) -> Self {
+        match db {
+            DetectedDatabase::Combined(db) => DynamicDatabase::Combined(db),
+            DetectedDatabase::Generic(db) => DynamicDatabase::Generic(db),
+            DetectedDatabase::Asn(db) => DynamicDatabase::Asn(db),
+        }
+    }
 }
 
 fn url_filename_guess(path: &str) -> &str {
@@ -59,12 +85,30 @@ fn url_filename_guess(path: &str) -> &str {
         .unwrap_or("unknown")
 }
 
+fn write_filenames<'a>(
+    f: &mut fmt::Formatter<'_>,
+    paths: impl Iterator<Item = &'a str>,
+) -> fmt::Result {
+    for (i, path) in paths.enumerate() {
+        if i > 0 {
+            f.write_str(" + ")?;
+        }
+
+        f.write_str(url_filename_guess(path))?;
+    }
+
+    Ok(())
+}
+
 impl fmt::Display for DatabaseSource {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DatabaseSource::DbIpCombined => f.write_str("DB-IP City"),
             DatabaseSource::Geolite2Combined => f.write_str("Geolite2 City"),
+            DatabaseSource::Asn => f.write_str("ASN"),
             DatabaseSource::File(path) => f.write_str(url_filename_guess(path)),
+            DatabaseSource::Files(paths) => write_filenames(f, paths.iter().map(String::as_str)),
+            DatabaseSource::Overlay(path) => f.write_str(url_filename_guess(path)),
         }
     }
 }
@@ -74,11 +118,23 @@ impl fmt::Display for ArchivedDatabaseSource {
         match self {
             ArchivedDatabaseSource::DbIpCombined => f.write_str("DB-IP City"),
             ArchivedDatabaseSource::Geolite2Combined => f.write_str("Geolite2 City"),
+            ArchivedDatabaseSource::Asn => f.write_str("ASN"),
             ArchivedDatabaseSource::File(path) => f.write_str(url_filename_guess(path)),
+            ArchivedDatabaseSource::Files(paths) => {
+                write_filenames(f, paths.iter().map(|p| p.as_str()))
+            }
+            ArchivedDatabaseSource::Overlay(path) => f.write_str(url_filename_guess(path)),
         }
     }
 }
 
+impl ArchivedDatabaseSource {
+    /// Overlays are only looked up through the priority chain, never selected by default.
+    pub fn is_overlay(&self) -> bool {
+        matches!(self, ArchivedDatabaseSource::Overlay(_))
+    }
+}
+
 impl PartialEq<DatabaseSource> for ArchivedDatabaseSource {
     fn eq(&self, other: &DatabaseSource) -> bool {
         match (self, other) {
@@ -87,6 +143,13 @@ impl PartialEq<DatabaseSource> for ArchivedDatabaseSource {
             }
             (ArchivedDatabaseSource::DbIpCombined, DatabaseSource::DbIpCombined) => true,
             (ArchivedDatabaseSource::Geolite2Combined, DatabaseSource::Geolite2Combined) => true,
+            (ArchivedDatabaseSource::Asn, DatabaseSource::Asn) => true,
+            (ArchivedDatabaseSource::Files(paths), DatabaseSource::Files(other_paths)) => {
+                paths.as_slice() == other_paths.as_slice()
+            }
+            (ArchivedDatabaseSource::Overlay(path), DatabaseSource::Overlay(other_path)) => {
+                path == other_path
+            }
             _ => false,
         }
     }
@@ -97,17 +160,22 @@ impl From<&ArchivedDatabaseSource> for DatabaseSource {
         match value {
             ArchivedDatabaseSource::DbIpCombined => DatabaseSource::DbIpCombined,
             ArchivedDatabaseSource::Geolite2Combined => DatabaseSource::Geolite2Combined,
+            ArchivedDatabaseSource::Asn => DatabaseSource::Asn,
             ArchivedDatabaseSource::File(path) => DatabaseSource::File(path.to_string()),
+            ArchivedDatabaseSource::Files(paths) => {
+                DatabaseSource::Files(paths.iter().map(|p| p.to_string()).collect())
+            }
+            ArchivedDatabaseSource::Overlay(path) => DatabaseSource::Overlay(path.to_string()),
         }
     }
 }
 
 impl Database<Ipv4Addr> for ArchivedDynamicDatabase {
-    fn get_coordinate(&self, ip: Ipv4Addr) -> Option<Coordinate> {
+    fn get_network(&self, ip: Ipv4Addr) -> Option<(Network, Coordinate)> {
         match self {
-            ArchivedDynamicDatabase::Combined(db) => db.get_coordinate(ip.into()),
+            ArchivedDynamicDatabase::Combined(db) => db.get_network(ip.into()),
             ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv4(db)) => {
-                db.get_coordinate(ip)
+                db.get_network(ip)
             }
             _ => None,
         }
@@ -125,11 +193,11 @@ impl Database<Ipv4Addr> for ArchivedDynamicDatabase {
 }
 
 impl Database<Ipv6Addr> for ArchivedDynamicDatabase {
-    fn get_coordinate(&self, ip: Ipv6Addr) -> Option<Coordinate> {
+    fn get_network(&self, ip: Ipv6Addr) -> Option<(Network, Coordinate)> {
         match self {
-            ArchivedDynamicDatabase::Combined(db) => db.get_coordinate(ip.into()),
+            ArchivedDynamicDatabase::Combined(db) => db.get_network(ip.into()),
             ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv6(db)) => {
-                db.get_coordinate(ip)
+                db.get_network(ip)
             }
             _ => None,
         }
@@ -147,17 +215,21 @@ impl Database<Ipv6Addr> for ArchivedDynamicDatabase {
 }
 
 impl Database<IpAddr> for ArchivedDynamicDatabase {
-    fn get_coordinate(&self, ip: IpAddr) -> Option<Coordinate> {
+    fn translate(&self, ip: &IpAddr) -> Option<IpAddr> {
+        ipgeo::translate(ip)
+    }
+
+    fn get_network(&self, ip: IpAddr) -> Option<(Network, Coordinate)> {
         match (self, ip) {
-            (ArchivedDynamicDatabase::Combined(db), ip) => db.get_coordinate(ip),
+            (ArchivedDynamicDatabase::Combined(db), ip) => db.get_network(ip),
             (
                 ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv4(db)),
                 IpAddr::V4(ip),
-            ) => db.get_coordinate(ip),
+            ) => db.get_network(ip),
             (
                 ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv6(db)),
                 IpAddr::V6(ip),
-            ) => db.get_coordinate(ip),
+            ) => db.get_network(ip),
             _ => None,
         }
     }
@@ -171,6 +243,84 @@ impl Database<IpAddr> for ArchivedDynamicDatabase {
             ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv6(db)) => {
                 db.get_location(crd)
             }
+            ArchivedDynamicDatabase::Asn(_) => None,
+        }
+    }
+}
+
+impl ReverseLookup for ArchivedDynamicDatabase {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.find_networks(query),
+            ArchivedDynamicDatabase::Generic(db) => db.find_networks(query),
+            ArchivedDynamicDatabase::Asn(_) => Vec::new(),
+        }
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.find_networks_at(coordinates),
+            ArchivedDynamicDatabase::Generic(db) => db.find_networks_at(coordinates),
+            ArchivedDynamicDatabase::Asn(_) => Vec::new(),
+        }
+    }
+}
+
+impl SpatialLookup for ArchivedDynamicDatabase {
+    fn spatial_index(&self) -> SpatialIndex {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.spatial_index(),
+            ArchivedDynamicDatabase::Generic(db) => db.spatial_index(),
+            ArchivedDynamicDatabase::Asn(_) => SpatialIndex::default(),
+        }
+    }
+}
+
+impl LocationSearch for ArchivedDynamicDatabase {
+    fn search_index(&self) -> SearchIndex {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.search_index(),
+            ArchivedDynamicDatabase::Generic(db) => db.search_index(),
+            ArchivedDynamicDatabase::Asn(_) => SearchIndex::default(),
+        }
+    }
+}
+
+impl Prefixes for ArchivedDynamicDatabase {
+    fn prefixes(&self) -> Box<dyn Iterator<Item = Prefix> + '_> {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.prefixes(),
+            ArchivedDynamicDatabase::Generic(db) => db.prefixes(),
+            ArchivedDynamicDatabase::Asn(_) => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl Audit for ArchivedDynamicDatabase {
+    fn audit(&self) -> AuditReport {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.audit(),
+            ArchivedDynamicDatabase::Generic(db) => db.audit(),
+            ArchivedDynamicDatabase::Asn(_) => AuditReport::default(),
+        }
+    }
+}
+
+impl Statistics for ArchivedDynamicDatabase {
+    fn stats(&self) -> DatabaseStats {
+        match self {
+            ArchivedDynamicDatabase::Combined(db) => db.stats(),
+            ArchivedDynamicDatabase::Generic(db) => db.stats(),
+            ArchivedDynamicDatabase::Asn(_) => DatabaseStats::default(),
+        }
+    }
+}
+
+impl AsnLookup for ArchivedDynamicDatabase {
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo> {
+        match self {
+            ArchivedDynamicDatabase::Asn(db) => db.get_asn(ip),
+            _ => None,
         }
     }
 }
@@ -19,7 +19,10 @@ mod model;
 
 pub use {
     disk::{DatabaseSource, DiskArchive, DynamicDatabase},
-    model::{DbState, DbStateInfo},
+    model::{
+        BatchLookup, BatchReport, DatabaseEvaluation, DatabaseLookup, DbState, DbStateInfo,
+        EvaluationReport, IpConsensus, IpList, IpLookup, LocationMatch,
+    },
 };
 
 const PLUGIN_NAME: &str = "ipgeo";
@@ -47,8 +50,19 @@ fn builder<R: Runtime>() -> tauri_specta::Builder<R> {
             commands::download_source::<tauri::Wry>,
             commands::unload_database::<tauri::Wry>,
             commands::set_selected_database::<tauri::Wry>,
+            commands::set_database_priority::<tauri::Wry>,
             commands::database_state,
             commands::lookup_ip,
+            commands::lookup_ip_all::<tauri::Wry>,
+            commands::lookup_ips::<tauri::Wry>,
+            commands::find_networks::<tauri::Wry>,
+            commands::nearest_location::<tauri::Wry>,
+            commands::locations_within::<tauri::Wry>,
+            commands::search_locations::<tauri::Wry>,
+            commands::diff_databases,
+            commands::database_stats,
+            commands::audit_database,
+            commands::evaluate_databases::<tauri::Wry>,
             commands::lookup_dns,
             commands::lookup_host,
         ])
@@ -2,12 +2,16 @@ use std::{
     fs,
     marker::PhantomData,
     net::{IpAddr, Ipv4Addr, Ipv6Addr},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
 
 use dashmap::{DashMap, DashSet};
-use ipgeo::{ArchivedGenericDatabase, Coordinate, Database, Location};
+use ipgeo::{
+    ArchivedGenericDatabase, AsnInfo, AsnLookup, Consensus, Coordinate, Database, Evaluation,
+    GroundTruth, Location, LocationQuery, LocationSearch, LookupInfo, LookupMatch, NearbyLocation,
+    Network, ReverseLookup, SearchIndex, SpatialIndex, SpatialLookup,
+};
 
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -16,33 +20,62 @@ use tauri_specta::Event;
 
 use crate::{
     archive::{self, FileResource},
-    disk::{ArchivedDynamicDatabase, DatabaseSource, DiskArchive, DynamicDatabase},
+    disk::{
+        ARCHIVE_VERSION, ArchivedDynamicDatabase, DatabaseSource, DiskArchive, DynamicDatabase,
+    },
 };
 
+/// Where the [`DbState::set_priority`] chain is saved in the cache directory.
+const PRIORITY_FILE: &str = "priority.json";
+
 /// Tracks the state of all loaded IP geolocation databases, including IPv4, IPv6,
-/// and combined IPv4/IPv6 archives.
+/// and combined IPv4/IPv6 archives, along with ASN archives.
 ///
 /// [`DbState`] manages the DB cache directory, loaded databases, and selection state.
 /// It provides methods for inserting new databases, removing or selecting them,
 /// refreshing the cache from disk, and emitting state change events to the frontend.
 pub struct DbState {
     cache_dir: PathBuf,
+    /// Where the archives of the current [`ARCHIVE_VERSION`] are kept in the cache directory.
+    archive_dir: PathBuf,
     ipv4: DbSet<Ipv4Addr>,
     ipv6: DbSet<Ipv6Addr>,
     combined: DbSet<IpAddr>,
+    asn: DbSet<Asn>,
     loaded_checksums: DashSet<u64>,
+    /// Databases tried in order for lookups before the selected ones.
+    priority: RwLock<Vec<DatabaseSource>>,
+    /// The resolved lookup chain, rebuilt whenever the databases or their order change.
+    chain: RwLock<Arc<[Arc<FileResource<DiskArchive>>]>>,
+    /// Spatial indices of loaded databases by checksum, built on their first nearby search.
+    spatial: DashMap<u64, Arc<SpatialIndex>>,
+    /// Name search indices of loaded databases by checksum, built on their first search.
+    search: DashMap<u64, Arc<SearchIndex>>,
 }
 
 impl DbState {
     /// Constructs a new [`DbState`] using the application's data directory.
     pub fn new<R: Runtime>(handle: &AppHandle<R>) -> Result<Self, tauri::Error> {
-        Ok(DbState {
-            cache_dir: handle.path().app_local_data_dir()?.join("dbs"),
+        Ok(Self::with_cache_dir(
+            handle.path().app_local_data_dir()?.join("dbs"),
+        ))
+    }
+
+    /// Constructs a new [`DbState`] keeping its archives in `cache_dir`.
+    fn with_cache_dir(cache_dir: PathBuf) -> Self {
+        DbState {
+            priority: RwLock::new(read_priority(&cache_dir)),
+            archive_dir: cache_dir.join(format!("v{ARCHIVE_VERSION}")),
+            cache_dir,
             ipv4: DbSet::default(),
             ipv6: DbSet::default(),
             combined: DbSet::default(),
+            asn: DbSet::default(),
             loaded_checksums: DashSet::default(),
-        })
+            chain: RwLock::new(Arc::new([])),
+            spatial: DashMap::default(),
+            search: DashMap::default(),
+        }
     }
 
     /// Returns a summary of the current database state,
@@ -52,6 +85,8 @@ impl DbState {
             ipv4: self.ipv4.info(),
             ipv6: self.ipv6.info(),
             combined: self.combined.info(),
+            asn: self.asn.info(),
+            priority: self.priority.read().expect("read priority").clone(),
         }
     }
 
@@ -63,11 +98,14 @@ impl DbState {
     /// Inserts a new database archive into the cache and updates the loaded/selected state.
     ///
     /// The database is serialized, checksummed, and memory-mapped before being added.
+    ///
+    /// Overlays are put at the front of the priority chain instead of being selected.
     pub async fn insert(&self, source: DatabaseSource, db: DynamicDatabase) -> anyhow::Result<()> {
-        let cache_dir = self.cache_dir.clone();
+        let archive_dir = self.archive_dir.clone();
+        let overlay = matches!(source, DatabaseSource::Overlay(_)).then(|| source.clone());
 
         let fa = tokio::task::spawn_blocking(move || {
-            FileResource::create(&cache_dir, &DiskArchive { source, db })
+            FileResource::create(&archive_dir, &DiskArchive { source, db })
         })
         .await??;
 
@@ -86,16 +124,304 @@ impl DbState {
             ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv6(_)) => {
                 self.ipv6.insert(fa)
             }
+            ArchivedDynamicDatabase::Asn(_) => self.asn.insert(fa),
         }
 
+        if let Some(overlay) = overlay {
+            let mut priority = self.priority.read().expect("read priority").clone();
+
+            if !priority.contains(&overlay) {
+                priority.insert(0, overlay);
+                self.set_priority(priority)?;
+            }
+        }
+
+        self.rebuild_chain();
+
         Ok(())
     }
 
-    /// Removes a database from all sets.
+    /// Removes a database from all sets and the priority chain, deleting its archive.
     pub fn remove(&self, source: &DatabaseSource) {
-        self.combined.remove(source);
-        self.ipv4.remove(source);
-        self.ipv6.remove(source);
+        let removed = [
+            self.combined.remove(source),
+            self.ipv4.remove(source),
+            self.ipv6.remove(source),
+            self.asn.remove(source),
+        ];
+
+        // the cached chain holds a reference to the archive until it's rebuilt without it
+        self.rebuild_chain();
+
+        let mut priority = self.priority.read().expect("read priority").clone();
+        if priority.contains(source) {
+            priority.retain(|s| s != source);
+
+            if let Err(err) = self.set_priority(priority) {
+                tracing::error!("failed to save database priority: {err}");
+            }
+        }
+
+        for archive in removed.into_iter().flatten() {
+            self.spatial.remove(&archive.checksum());
+            self.search.remove(&archive.checksum());
+
+            match Arc::into_inner(archive).map(|fa| fa.delete()) {
+                Some(Err(err)) => tracing::error!("failed to delete {source}: {err}"),
+                None => tracing::error!("failed to remove {source}, other references."),
+                _ => tracing::info!("Successfully removed database: {source}"),
+            }
+        }
+    }
+
+    /// Sets the chain of databases tried in order for lookups, saving it for the next launch.
+    ///
+    /// A lookup falls through to the next database on a miss or a country-level result,
+    /// and to the selected databases at the end of the chain.
+    pub fn set_priority(&self, priority: Vec<DatabaseSource>) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.cache_dir)?;
+        fs::write(
+            self.cache_dir.join(PRIORITY_FILE),
+            serde_json::to_vec(&priority)?,
+        )?;
+
+        *self.priority.write().expect("write priority") = priority;
+        self.rebuild_chain();
+
+        Ok(())
+    }
+
+    /// The loaded databases a lookup tries in order: the priority chain, then the selected databases.
+    fn lookup_chain(&self) -> Arc<[Arc<FileResource<DiskArchive>>]> {
+        self.chain.read().expect("read chain").clone()
+    }
+
+    /// Resolve the [lookup chain](DbState::lookup_chain) again after the databases,
+    /// their selection or the priority changed.
+    fn rebuild_chain(&self) {
+        let mut chain = self
+            .priority
+            .read()
+            .expect("read priority")
+            .iter()
+            .filter_map(|source| self.get_archive(source))
+            .collect::<Vec<_>>();
+
+        for db in [
+            self.ipv4.selected(),
+            self.ipv6.selected(),
+            self.combined.selected(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !chain.iter().any(|c| Arc::ptr_eq(c, &db)) {
+                chain.push(db);
+            }
+        }
+
+        *self.chain.write().expect("write chain") = chain.into();
+    }
+
+    /// Look up an address in each database of the [lookup chain](DbState::set_priority),
+    /// returning the first city-level result, or the first country-level one if there isn't any,
+    /// with the database that answered.
+    pub fn lookup(&self, ip: IpAddr) -> Option<(LookupMatch, DatabaseSource)> {
+        let mut country_level = None;
+
+        for archive in self.lookup_chain().iter() {
+            match archive.db.get_match(ip) {
+                Some(matched) if !matched.info.country_level => {
+                    return Some((matched, DatabaseSource::from(&archive.source)));
+                }
+                Some(matched) => {
+                    country_level
+                        .get_or_insert_with(|| (matched, DatabaseSource::from(&archive.source)));
+                }
+                None => (),
+            }
+        }
+
+        country_level
+    }
+
+    /// Every loaded database that can place an address, selected or not.
+    fn located(&self) -> Vec<Arc<FileResource<DiskArchive>>> {
+        [&self.ipv4.loaded, &self.ipv6.loaded, &self.combined.loaded]
+            .into_iter()
+            .flat_map(|loaded| loaded.iter().map(|kv| kv.value().clone()))
+            .collect()
+    }
+
+    /// Look up an address in every loaded database, the lookup chain first and the rest by name,
+    /// with where most of them place it.
+    pub fn lookup_all(&self, ip: IpAddr) -> IpConsensus {
+        let mut archives = self.lookup_chain().to_vec();
+
+        let mut rest = self
+            .located()
+            .into_iter()
+            .filter(|db| !archives.iter().any(|c| Arc::ptr_eq(c, db)))
+            .collect::<Vec<_>>();
+        rest.sort_by_cached_key(|db| db.source.to_string());
+        archives.extend(rest);
+
+        let answers = archives
+            .iter()
+            .map(|archive| {
+                let matched = archive.db.get_match(ip);
+
+                DatabaseLookup {
+                    source: DatabaseSource::from(&archive.source),
+                    network: matched.as_ref().map(|matched| matched.network),
+                    info: matched.map(|matched| matched.info),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        IpConsensus {
+            consensus: ipgeo::consensus(answers.iter().filter_map(|a| a.info.as_ref())),
+            answers,
+            asn: self.get_asn(ip),
+        }
+    }
+
+    /// Measure every loaded database against addresses whose real location is known,
+    /// lowest median error first, see [`ipgeo::evaluate`].
+    pub fn evaluate(&self, truth: &[GroundTruth]) -> Vec<DatabaseEvaluation> {
+        let mut evaluations = self
+            .located()
+            .iter()
+            .map(|archive| DatabaseEvaluation {
+                source: DatabaseSource::from(&archive.source),
+                evaluation: ipgeo::evaluate(&archive.db, truth),
+            })
+            .collect::<Vec<_>>();
+
+        evaluations.sort_by(|a, b| {
+            let median = |e: &DatabaseEvaluation| e.evaluation.median_error_km.unwrap_or(f32::MAX);
+            median(a).total_cmp(&median(b))
+        });
+        evaluations
+    }
+
+    /// The [`SpatialIndex`] of a loaded database, built the first time it's needed.
+    ///
+    /// It's built before taking the entry, so searches of other databases aren't held up.
+    fn spatial_index(&self, archive: &FileResource<DiskArchive>) -> Arc<SpatialIndex> {
+        if let Some(index) = self.spatial.get(&archive.checksum()) {
+            return index.clone();
+        }
+
+        let index = Arc::new(archive.db.spatial_index());
+        self.spatial
+            .entry(archive.checksum())
+            .or_insert(index)
+            .clone()
+    }
+
+    /// Find the closest location to a coordinate in any database of the lookup chain.
+    pub fn nearest_location(&self, center: Coordinate) -> Option<NearbyLocation> {
+        self.lookup_chain()
+            .iter()
+            .filter_map(|archive| {
+                let (crd, distance_km) = self.spatial_index(archive).nearest(center)?;
+
+                Some(NearbyLocation {
+                    loc: Database::<IpAddr>::get_location(&archive.db, crd)?,
+                    crd,
+                    distance_km,
+                })
+            })
+            .min_by(|a, b| a.distance_km.total_cmp(&b.distance_km))
+    }
+
+    /// Find every location within `radius_km` of a coordinate in the databases of the lookup chain,
+    /// closest first.
+    pub fn locations_within(&self, center: Coordinate, radius_km: f32) -> Vec<NearbyLocation> {
+        let mut locations = self
+            .lookup_chain()
+            .iter()
+            .flat_map(|archive| {
+                self.spatial_index(archive)
+                    .within_km(center, radius_km)
+                    .into_iter()
+                    .filter_map(move |(crd, distance_km)| {
+                        Some(NearbyLocation {
+                            loc: Database::<IpAddr>::get_location(&archive.db, crd)?,
+                            crd,
+                            distance_km,
+                        })
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        // the same place is often in several databases
+        locations.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        locations.dedup_by(|a, b| a.crd.approx_eq(&b.crd) && a.loc == b.loc);
+        locations
+    }
+
+    /// The [`SearchIndex`] of a loaded database, built the first time it's needed.
+    ///
+    /// Like [`DbState::spatial_index`], it's built before taking the entry.
+    fn search_index(&self, archive: &FileResource<DiskArchive>) -> Arc<SearchIndex> {
+        if let Some(index) = self.search.get(&archive.checksum()) {
+            return index.clone();
+        }
+
+        let index = Arc::new(archive.db.search_index());
+        self.search
+            .entry(archive.checksum())
+            .or_insert(index)
+            .clone()
+    }
+
+    /// Search the city and region names of the databases in the lookup chain,
+    /// see [`SearchIndex::search`] for how matches are ranked.
+    pub fn search_locations(&self, query: &str, limit: usize) -> Vec<LocationMatch> {
+        let mut matches = self
+            .lookup_chain()
+            .iter()
+            .flat_map(|archive| {
+                self.search_index(archive)
+                    .search(query, limit)
+                    .into_iter()
+                    .filter_map(move |hit| {
+                        Some(LocationMatch {
+                            loc: Database::<IpAddr>::get_location(&archive.db, hit.crd)?,
+                            crd: hit.crd,
+                            networks: hit.networks,
+                            typos: hit.typos,
+                        })
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        // keeps the order of each database's own ranking, and of the chain
+        matches.sort_by_key(|m| m.typos);
+
+        let mut unique = Vec::<LocationMatch>::with_capacity(limit);
+        for m in matches {
+            if unique.len() == limit {
+                break;
+            }
+
+            if !unique.iter().any(|u| u.loc == m.loc) {
+                unique.push(m);
+            }
+        }
+
+        unique
+    }
+
+    /// Get a loaded database from any set.
+    pub fn get_archive(&self, source: &DatabaseSource) -> Option<Arc<FileResource<DiskArchive>>> {
+        self.combined
+            .get_archive(source)
+            .or_else(|| self.ipv4.get_archive(source))
+            .or_else(|| self.ipv6.get_archive(source))
+            .or_else(|| self.asn.get_archive(source))
     }
 
     /// Sets the selected database for all sets if available.
@@ -103,21 +429,26 @@ impl DbState {
         self.combined.set_selected(source);
         self.ipv4.set_selected(source);
         self.ipv6.set_selected(source);
+        self.asn.set_selected(source);
+        self.rebuild_chain();
     }
 
     /// Loads any new archives from the cache directory, updating the loaded state.
     ///
     /// Skips databases that are already loaded, and logs errors for any corrupt or unreadable archives.
+    /// Archives of another [`ARCHIVE_VERSION`] are deleted, they have to be downloaded again.
     pub async fn refresh_cache(&self) -> anyhow::Result<()> {
         tracing::debug!("refreshing from cache dir {:?}", self.cache_dir);
 
         let loaded_checksums = self.loaded_checksums.clone();
         let cache_dir = self.cache_dir.clone();
+        let archive_dir = self.archive_dir.clone();
 
         let dbs = tokio::task::spawn_blocking(move || {
-            fs::create_dir_all(&cache_dir)?;
+            fs::create_dir_all(&archive_dir)?;
+            archive::remove_stale_versions(&cache_dir, ARCHIVE_VERSION)?;
 
-            let res = archive::resource_dir_list(&cache_dir)?
+            let res = archive::resource_dir_list(&archive_dir)?
                 .filter(|(_, c)| !loaded_checksums.contains(c))
                 .filter_map(|(path, _)| match FileResource::open(&path) {
                     Ok(db) => {
@@ -146,27 +477,87 @@ impl DbState {
                 ArchivedDynamicDatabase::Generic(ArchivedGenericDatabase::Ipv6(_)) => {
                     self.ipv6.insert(archive)
                 }
+                ArchivedDynamicDatabase::Asn(_) => self.asn.insert(archive),
             }
         }
 
+        self.rebuild_chain();
+
         Ok(())
     }
 }
 
+/// Read the saved priority chain, which is empty if it was never set.
+fn read_priority(cache_dir: &Path) -> Vec<DatabaseSource> {
+    fs::read(cache_dir.join(PRIORITY_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
 impl Database<IpAddr> for DbState {
-    fn get_coordinate(&self, ip: IpAddr) -> Option<Coordinate> {
-        match ip {
-            IpAddr::V4(ip) => self.ipv4.get_coordinate(ip),
-            IpAddr::V6(ip) => self.ipv6.get_coordinate(ip),
-        }
-        .or_else(|| self.combined.get_coordinate(ip))
+    /// [`DbState::lookup`], without the database that answered.
+    fn get_match(&self, ip: IpAddr) -> Option<LookupMatch> {
+        self.lookup(ip).map(|(matched, _)| matched)
+    }
+
+    fn get_network(&self, ip: IpAddr) -> Option<(Network, Coordinate)> {
+        self.get_match(ip)
+            .map(|matched| (matched.network, matched.info.crd))
     }
 
     fn get_location(&self, crd: Coordinate) -> Option<Location> {
-        self.combined
-            .get_location(crd)
-            .or_else(|| self.ipv4.get_location(crd))
-            .or_else(|| self.ipv6.get_location(crd))
+        self.lookup_chain()
+            .iter()
+            .find_map(|archive| Database::<IpAddr>::get_location(&archive.db, crd))
+    }
+}
+
+impl ReverseLookup for DbState {
+    /// [`LocationQuery::Near`] is answered with the cached [`SpatialIndex`] of each database.
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        let mut networks = self
+            .lookup_chain()
+            .iter()
+            .flat_map(|archive| match query {
+                LocationQuery::Near {
+                    coordinate,
+                    radius_km,
+                } => {
+                    let coordinates = self
+                        .spatial_index(archive)
+                        .within_km(*coordinate, *radius_km)
+                        .into_iter()
+                        .map(|(crd, _)| crd)
+                        .collect::<Vec<_>>();
+
+                    archive.db.find_networks_at(&coordinates)
+                }
+                query => archive.db.find_networks(query),
+            })
+            .collect::<Vec<_>>();
+
+        networks.sort_unstable();
+        networks.dedup();
+        networks
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        let mut networks = self
+            .lookup_chain()
+            .iter()
+            .flat_map(|archive| archive.db.find_networks_at(coordinates))
+            .collect::<Vec<_>>();
+
+        networks.sort_unstable();
+        networks.dedup();
+        networks
+    }
+}
+
+impl AsnLookup for DbState {
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo> {
+        self.asn.get_asn(ip)
     }
 }
 
@@ -176,6 +567,98 @@ pub struct DbStateInfo {
     pub ipv4: DbSetInfo,
     pub ipv6: DbSetInfo,
     pub combined: DbSetInfo,
+    pub asn: DbSetInfo,
+    /// Databases tried in order for lookups before the selected ones, see [`DbState::set_priority`].
+    pub priority: Vec<DatabaseSource>,
+}
+
+/// Everything the selected databases know about an IP address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct IpLookup {
+    /// Where the address is located.
+    pub info: Option<LookupInfo>,
+    /// The most specific network containing the address in the database that answered.
+    pub network: Option<Network>,
+    /// The database that answered, which matters once several are loaded.
+    pub source: Option<DatabaseSource>,
+    /// Who the address belongs to.
+    pub asn: Option<AsnInfo>,
+}
+
+/// What every loaded database knows about an IP address, see [`DbState::lookup_all`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct IpConsensus {
+    /// The answer of each database, including those that didn't find the address.
+    pub answers: Vec<DatabaseLookup>,
+    /// Where most databases place the address and how far apart they are,
+    /// if any database found it.
+    pub consensus: Option<Consensus>,
+    /// Who the address belongs to.
+    pub asn: Option<AsnInfo>,
+}
+
+/// Where a single loaded database places an address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct DatabaseLookup {
+    pub source: DatabaseSource,
+    pub network: Option<Network>,
+    pub info: Option<LookupInfo>,
+}
+
+/// Addresses to look up at once with [`lookup_ips`](crate::commands::lookup_ips).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum IpList {
+    Ips(Vec<IpAddr>),
+    /// A text file with an address at the start of each line, e.g. an access log, see [`ipgeo::read_ips`].
+    File(String),
+}
+
+/// Where a single address of an [`IpList`] is located.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct BatchLookup {
+    pub ip: IpAddr,
+    pub info: Option<LookupInfo>,
+}
+
+/// A location whose city or region name matched a [`DbState::search_locations`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct LocationMatch {
+    pub crd: Coordinate,
+    pub loc: Location,
+    /// Network prefixes mapped to the location.
+    pub networks: u32,
+    /// Typos between the query and the matched name, `0` for a prefix match.
+    pub typos: u32,
+}
+
+/// Summary of a [`lookup_ips`](crate::commands::lookup_ips) run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchReport {
+    /// Addresses looked up.
+    pub total: u64,
+    /// Addresses that were found in a database.
+    pub found: u64,
+    /// Lines of the file that didn't start with an address.
+    pub invalid: u64,
+}
+
+/// How accurately a loaded database placed the addresses of a ground truth file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+pub struct DatabaseEvaluation {
+    pub source: DatabaseSource,
+    pub evaluation: Evaluation,
+}
+
+/// Summary of an [`evaluate_databases`](crate::commands::evaluate_databases) run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationReport {
+    /// Rows of the file that weren't an `ip,lat,lng,country` record.
+    pub invalid: u64,
+    /// Every loaded database, lowest median error first.
+    pub databases: Vec<DatabaseEvaluation>,
 }
 
 /// Information about the loaded and selected databases in a [`DbSet`].
@@ -185,7 +668,11 @@ pub struct DbSetInfo {
     pub loaded: Vec<DatabaseSource>,
 }
 
-/// Manages a set of loaded database archives for a specific IP type (IPv4, IPv6, or combined).
+/// Marks the [`DbSet`] of ASN archives, which aren't looked up by an address family.
+pub struct Asn;
+
+/// Manages a set of loaded database archives for a specific IP type (IPv4, IPv6, or combined),
+/// or for ASN archives.
 ///
 /// [`DbSet`] tracks loaded databases, the currently selected database, and provides
 /// methods for insertion, removal, selection, and querying.
@@ -207,37 +694,39 @@ impl<C> Default for DbSet<C> {
 }
 
 impl<C> DbSet<C> {
-    /// Inserts a new database archive, making it the selected database.
+    /// Inserts a new database archive, making it the selected database unless it's an overlay.
     pub fn insert(&self, db: FileResource<DiskArchive>) {
         let db = Arc::new(db);
 
         self.loaded
             .insert(DatabaseSource::from(&db.source), db.clone());
-        self.selected.write().expect("open selected").replace(db);
+
+        if !db.source.is_overlay() {
+            self.selected.write().expect("open selected").replace(db);
+        }
     }
 
-    /// Removes and deletes a database archive by source,
-    /// updating the selected database if necessary.
-    pub fn remove(&self, name: &DatabaseSource) {
+    /// Removes a database archive by source, updating the selected database if necessary.
+    ///
+    /// Returns the archive to be deleted once nothing else references it.
+    pub fn remove(&self, name: &DatabaseSource) -> Option<Arc<FileResource<DiskArchive>>> {
         let mut selected = self.selected.write().expect("open selected");
 
         let selected_is_name = selected
             .as_ref()
             .is_some_and(|sel_db| &sel_db.source == name);
 
-        let Some((_, fa)) = self.loaded.remove(name) else {
-            return;
-        };
+        let (_, fa) = self.loaded.remove(name)?;
 
         if selected_is_name {
-            *selected = self.loaded.iter().map(|kv| kv.value().clone()).next();
+            *selected = self
+                .loaded
+                .iter()
+                .map(|kv| kv.value().clone())
+                .find(|db| !db.source.is_overlay());
         }
 
-        match Arc::into_inner(fa).map(|fa| fa.delete()) {
-            Some(Err(err)) => tracing::error!("failed to delete {name}: {err}"),
-            None => tracing::error!("failed to remove {name}, other references."),
-            _ => tracing::info!("Successfully removed database: {name}"),
-        }
+        Some(fa)
     }
 
     /// Returns true if no databases are loaded in this set.
@@ -252,6 +741,11 @@ impl<C> DbSet<C> {
         self.loaded.contains_key(&name)
     }
 
+    /// Returns the loaded database with the given source, if it exists in this set.
+    pub fn get_archive(&self, name: &DatabaseSource) -> Option<Arc<FileResource<DiskArchive>>> {
+        self.loaded.get(name).map(|kv| kv.value().clone())
+    }
+
     /// Returns information about the loaded and selected databases in this set.
     pub fn info(&self) -> DbSetInfo {
         DbSetInfo {
@@ -276,6 +770,11 @@ impl<C> DbSet<C> {
         }
     }
 
+    /// Returns the selected database, if any.
+    pub fn selected(&self) -> Option<Arc<FileResource<DiskArchive>>> {
+        self.selected.read().expect("read selected").clone()
+    }
+
     /// Executes a function on the selected database, if any.
     fn on_selected<T>(&self, f: impl Fn(&ArchivedDynamicDatabase) -> Option<T>) -> Option<T> {
         self.selected
@@ -291,8 +790,12 @@ where
     C: Copy,
     ArchivedDynamicDatabase: Database<C>,
 {
-    fn get_coordinate(&self, ip: C) -> Option<Coordinate> {
-        self.on_selected(|db| db.get_coordinate(ip))
+    fn translate(&self, ip: &C) -> Option<C> {
+        self.on_selected(|db| db.translate(ip))
+    }
+
+    fn get_network(&self, ip: C) -> Option<(Network, Coordinate)> {
+        self.on_selected(|db| db.get_network(ip))
     }
 
     fn get_location(&self, crd: Coordinate) -> Option<Location> {
@@ -300,8 +803,73 @@ where
     }
 }
 
+impl<C> ReverseLookup for DbSet<C> {
+    fn find_networks(&self, query: &LocationQuery) -> Vec<Network> {
+        self.on_selected(|db| Some(db.find_networks(query)))
+            .unwrap_or_default()
+    }
+
+    fn find_networks_at(&self, coordinates: &[Coordinate]) -> Vec<Network> {
+        self.on_selected(|db| Some(db.find_networks_at(coordinates)))
+            .unwrap_or_default()
+    }
+}
+
+impl<C> AsnLookup for DbSet<C> {
+    fn get_asn(&self, ip: IpAddr) -> Option<AsnInfo> {
+        self.on_selected(|db| db.get_asn(ip))
+    }
+}
+
 /// Event fired whenever the state of loaded or selected databases changes.
 ///
 /// Used to notify the frontend of updates to the database state.
 #[derive(Serialize, Deserialize, Debug, Clone, Type, Event)]
 pub struct DbStateChange(DbStateInfo);
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use ipgeo::CombinedDatabase;
+
+    use crate::disk::{DatabaseSource, DynamicDatabase};
+
+    use super::DbState;
+
+    #[tokio::test]
+    async fn remove_deletes_archive() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("ipgeo-plugin-{}", process::id()));
+        let state = DbState::with_cache_dir(dir.clone());
+
+        let db = CombinedDatabase::from_csv(
+            "1.0.0.0,1.0.0.255,AU,Queensland,,Brisbane,,-27.4679,153.028\n".as_bytes(),
+            "".as_bytes(),
+            false,
+        )?;
+        let source = DatabaseSource::File("brisbane.csv".into());
+        state
+            .insert(source.clone(), DynamicDatabase::Combined(db))
+            .await?;
+        state.set_selected(&source);
+        state.set_priority(vec![source.clone()])?;
+
+        let archive = state
+            .get_archive(&source)
+            .expect("loaded")
+            .path()
+            .to_owned();
+        assert!(archive.exists());
+        assert!(state.lookup("1.0.0.1".parse()?).is_some());
+
+        state.remove(&source);
+        let deleted = !archive.exists();
+        fs::remove_dir_all(&dir)?;
+
+        assert!(deleted, "{archive:?} wasn't deleted");
+        assert!(state.lookup("1.0.0.1".parse()?).is_none());
+        assert!(state.info().priority.is_empty());
+
+        Ok(())
+    }
+}
@@ -95,6 +95,10 @@ maxThroughput: number;
 last: boolean }
 export type Connection = { up: Throughput; down: Throughput }
 export type ConnectionDirection = "mixed" | "up" | "down"
+/**
+ * A continent, as assigned to countries by GeoNames.
+ */
+export type Continent = "africa" | "antarctica" | "asia" | "europe" | "northAmerica" | "oceania" | "southAmerica"
 /**
  * A basic latitude/longitude pair.
  */
@@ -107,6 +111,18 @@ lat: number;
  * Longitude
  */
 lng: number }
+/**
+ * Details of an ISO 3166-1 country, see [`CountryCode::country`].
+ */
+export type Country = { names: CountryNames; continent: Continent; 
+/**
+ * Flag emoji, made of the code's two regional indicator symbols.
+ */
+flag: string }
+/**
+ * A country's name in each [`Language`].
+ */
+export type CountryNames = { en: string; es: string; zh: string }
 /**
  * A network device reported from libpcap, e.g. "wlp3s0".
  */
@@ -134,11 +150,34 @@ export type Hop = { ips: string[]; loc: LookupInfo | null }
 /**
  * A [`Coordinate`]'s associated city, region, and country.
  */
-export type Location = { city: string | null; region: string | null; countryCode: string }
+export type Location = { city: string | null; region: string | null; countryCode: string; 
+/**
+ * Second-level region (`state2`), e.g. a county or district.
+ */
+subregion: string | null; postcode: string | null; 
+/**
+ * IANA time zone name, e.g. `Asia/Shanghai`.
+ */
+timezone: string | null; 
+/**
+ * Name, continent and flag of `country_code`, if it's a known ISO 3166-1 country.
+ */
+country: Country | null }
 /**
  * A [`Coordinate`]/[`Location`] pair.
  */
-export type LookupInfo = { crd: Coordinate; loc: Location }
+export type LookupInfo = { crd: Coordinate; loc: Location; 
+/**
+ * Nothing more specific than the country is known, see [`Location::is_country_level`].
+ * 
+ * `crd` is then usually somewhere central in the country, but that's up to the database.
+ */
+countryLevel: boolean; 
+/**
+ * The address had an IPv4 address embedded in it (IPv4-mapped, 6to4, NAT64 or Teredo),
+ * which was looked up instead.
+ */
+translated: boolean }
 export type PcapStateChange = ({ status: "Ok" } & PcapStateInfo) | ({ status: "Err" } & Error)
 export type PcapStateInfo = { 
 /**
@@ -157,7 +196,11 @@ capture: Device | null;
  * Where the user is suspected to currently be.
  */
 myLocation: LookupInfo }
-export type RunCapture = { device: Device; connectionTimeout: Duration; reportFrequency: Duration }
+export type RunCapture = { device: Device; connectionTimeout: Duration; reportFrequency: Duration; 
+/**
+ * Keep traffic between private addresses, e.g. to internal offices, instead of dropping it.
+ */
+keepPrivate: boolean }
 export type RunTraceroute = { ip: string; rounds: number }
 /**
  * Current stats for a single connection direction (up or down)
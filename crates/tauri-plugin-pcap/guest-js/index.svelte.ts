@@ -124,6 +124,9 @@ export class Pcap {
   /** The currently selected device for capturing. */
   device: Device | null = $state(null);
 
+  /** Keep traffic between private addresses, e.g. to internal offices, on the next capture. */
+  keepPrivate: boolean = $state(false);
+
   /** Current status of the capture state on the backend. */
   status: PcapStateInfo = $state({
     version: "",
@@ -131,7 +134,14 @@ export class Pcap {
     capture: null,
     myLocation: {
       crd: { lat: 0.0, lng: 0.0 },
-      loc: { city: null, region: null, countryCode: "??" },
+      loc: {
+        city: null,
+        region: null,
+        countryCode: "??",
+        subregion: null,
+        postcode: null,
+        timezone: null,
+      },
     },
   });
 
@@ -172,6 +182,7 @@ export class Pcap {
           device: this.device,
           connectionTimeout: CAPTURE_CONNECTION_TIMEOUT,
           reportFrequency: CAPTURE_REPORT_FREQUENCY,
+          keepPrivate: this.keepPrivate,
         },
         new Channel(this.capture.update),
       )
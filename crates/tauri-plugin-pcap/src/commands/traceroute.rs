@@ -83,7 +83,7 @@ pub struct Hop {
 
 impl Hop {
     pub fn new(ips: Vec<IpAddr>, db: &State<'_, DbState>) -> Self {
-        let loc = ips.iter().find_map(|ip| lookup_ip(db.clone(), *ip));
+        let loc = ips.iter().find_map(|ip| lookup_ip(db.clone(), *ip).info);
 
         Self { ips, loc }
     }
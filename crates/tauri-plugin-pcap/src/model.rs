@@ -11,7 +11,7 @@ use child_ipc::{
     ipc::{self, StopCallback},
 };
 use ipgeo::Database;
-use ipgeo::{Coordinate, Location, LookupInfo};
+use ipgeo::{Coordinate, Country, Location, LookupInfo};
 use public_ip_address::response::LookupResponse;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -99,14 +99,14 @@ pub struct PcapStateInfo {
 #[derive(Serialize, Deserialize, Debug, Clone, Type, Event)]
 #[serde(tag = "status")]
 pub enum PcapStateChange {
-    Ok(PcapStateInfo),
+    Ok(Box<PcapStateInfo>),
     Err(Error),
 }
 
 impl PcapStateChange {
     pub async fn emit<R: Runtime>(app: &AppHandle<R>) {
         let info = match app.state::<PcapState>().inner().info(app.clone()).await {
-            Ok(info) => Self::Ok(info),
+            Ok(info) => Self::Ok(Box::new(info)),
             Err(err) => Self::Err(err),
         };
 
@@ -174,10 +174,11 @@ async fn get_my_location<R: Runtime>(handle: &AppHandle<R>) -> (IpAddr, LookupIn
             LookupInfo {
                 crd: Coordinate { lat: 0.0, lng: 0.0 },
                 loc: Location {
-                    city: None,
-                    region: None,
                     country_code: "??".into(),
+                    ..Default::default()
                 },
+                country_level: false,
+                translated: false,
             },
         )
     };
@@ -190,6 +191,8 @@ async fn get_my_location<R: Runtime>(handle: &AppHandle<R>) -> (IpAddr, LookupIn
             city,
             region,
             country_code: Some(country_code),
+            postal_code: postcode,
+            time_zone: timezone,
             ..
         }) => (
             ip,
@@ -201,8 +204,14 @@ async fn get_my_location<R: Runtime>(handle: &AppHandle<R>) -> (IpAddr, LookupIn
                 loc: Location {
                     city,
                     region,
+                    country: Country::from_code(&country_code),
                     country_code,
+                    postcode,
+                    timezone,
+                    ..Default::default()
                 },
+                country_level: false,
+                translated: false,
             },
         ),
         Ok(LookupResponse { ip, .. }) => handle
@@ -274,6 +274,38 @@ where
             .map(move |(bits_matched, value)| (ip.mask(bits_matched), bits_matched, value))
     }
 
+    /// Returns iterator over every prefix contained in the supernet `ip`/`masklen`,
+    /// including the supernet itself, along with their values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use treebitmap::IpLookupTable;
+    /// use std::net::Ipv4Addr;
+    ///
+    /// let mut table = IpLookupTable::new();
+    /// table.insert(Ipv4Addr::new(10, 0, 0, 0), 8, "foo");
+    /// table.insert(Ipv4Addr::new(10, 1, 0, 0), 16, "bar");
+    /// table.insert(Ipv4Addr::new(10, 1, 2, 0), 24, "baz");
+    /// table.insert(Ipv4Addr::new(10, 2, 0, 0), 16, "qux");
+    ///
+    /// let mut within = table.within(Ipv4Addr::new(10, 1, 0, 0), 16);
+    /// assert_eq!(within.next(), Some((Ipv4Addr::new(10, 1, 0, 0), 16, &"bar")));
+    /// assert_eq!(within.next(), Some((Ipv4Addr::new(10, 1, 2, 0), 24, &"baz")));
+    /// assert_eq!(within.next(), None);
+    /// ```
+    pub fn within(&self, ip: A, masklen: u32) -> impl Iterator<Item = (A, u32, &T)>
+    where
+        A: PartialEq,
+    {
+        let supernet = ip.mask(masklen);
+        Iter::<'_, A, T> {
+            inner: self.inner.iter_within(supernet.nibbles().as_ref(), masklen),
+            _addrtype: PhantomData,
+        }
+        .filter(move |(ip, len, _)| *len >= masklen && ip.mask(masklen) == supernet)
+    }
+
     /// Returns iterator over prefixes and values.
     ///
     /// # Examples
@@ -2,8 +2,9 @@ use crate::ArchivedIpLookupTable;
 use crate::address::Address;
 use crate::tree_bitmap::node::ArchivedNode;
 use crate::tree_bitmap::{
-    ArchivedTreeBitmap, TrieAccess,
+    ArchivedTreeBitmap, PathElem, TrieAccess,
     allocator::{AllocatorHandle, ArchivedAllocator, ArchivedBucketVec, choose_bucket},
+    next,
     node::Node,
 };
 use rkyv::{Archive, Archived};
@@ -19,6 +20,12 @@ impl<A: Address, T: Archive + Clone + Copy + Default> ArchivedIpLookupTable<A, T
         self.len() == 0
     }
 
+    /// Returns the number of bytes used by the trie nodes and the results,
+    /// like [`IpLookupTable::mem_usage`](crate::IpLookupTable::mem_usage).
+    pub fn mem_usage(&self) -> (usize, usize) {
+        self.inner.mem_usage()
+    }
+
     /// Longest match lookup of `ip`
     pub fn longest_match(&self, ip: A) -> Option<(A, u32, &Archived<T>)> {
         match self.inner.longest_match(ip.nibbles().as_ref()) {
@@ -31,6 +38,35 @@ impl<A: Address, T: Archive + Clone + Copy + Default> ArchivedIpLookupTable<A, T
     pub fn exact_match(&self, ip: A, masklen: u32) -> Option<&Archived<T>> {
         self.inner.exact_match(ip.nibbles().as_ref(), masklen)
     }
+
+    /// Perform match lookup of `ip` and return all matching prefixes,
+    /// designated by ip, masklen, along with their values.
+    pub fn matches(&self, ip: A) -> impl Iterator<Item = (A, u32, &Archived<T>)> {
+        self.inner
+            .matches(ip.nibbles().as_ref())
+            .map(move |(bits_matched, value)| (ip.mask(bits_matched), bits_matched, value))
+    }
+
+    /// Returns iterator over every prefix contained in the supernet `ip`/`masklen`,
+    /// including the supernet itself, along with their values.
+    pub fn within(&self, ip: A, masklen: u32) -> impl Iterator<Item = (A, u32, &Archived<T>)>
+    where
+        A: PartialEq,
+    {
+        let supernet = ip.mask(masklen);
+        self.inner
+            .iter_within(supernet.nibbles().as_ref(), masklen)
+            .map(|(nibbles, masklen, value)| (A::from_nibbles(&nibbles[..]), masklen, value))
+            .filter(move |(ip, len, _)| *len >= masklen && ip.mask(masklen) == supernet)
+    }
+
+    /// Returns iterator over prefixes and values, in the same "tree"-order
+    /// as [`IpLookupTable::iter`](crate::IpLookupTable::iter).
+    pub fn iter(&self) -> impl Iterator<Item = (A, u32, &Archived<T>)> {
+        self.inner
+            .iter()
+            .map(|(nibbles, masklen, value)| (A::from_nibbles(&nibbles[..]), masklen, value))
+    }
 }
 
 impl<T: Archive + Clone + Copy + Default> ArchivedTreeBitmap<T> {
@@ -39,6 +75,10 @@ impl<T: Archive + Clone + Copy + Default> ArchivedTreeBitmap<T> {
         self.len.to_native() as usize
     }
 
+    pub fn mem_usage(&self) -> (usize, usize) {
+        (self.trienodes.mem_usage(), self.results.mem_usage())
+    }
+
     /// Longest match lookup of `nibbles`. Returns bits matched as u32, and reference to T.
     pub fn longest_match(&self, nibbles: &[u8]) -> Option<(u32, &Archived<T>)> {
         match self.longest_match_internal(nibbles) {
@@ -54,6 +94,73 @@ impl<T: Archive + Clone + Copy + Default> ArchivedTreeBitmap<T> {
         self.exact_match_internal(nibbles, masklen)
             .map(|(result_hdl, result_index)| self.results.get(&result_hdl, result_index))
     }
+
+    /// All matches lookup of `nibbles`. Returns an iterator of bits matched as u32, and reference to T.
+    pub fn matches(&self, nibbles: &[u8]) -> ArchivedMatches<'_, T> {
+        let path = self.matches_internal(nibbles).into_iter();
+        ArchivedMatches { inner: self, path }
+    }
+
+    /// Returns iterator over the subtree holding every prefix contained in `nibbles`/`masklen`.
+    /// The caller still has to filter out prefixes that are shorter or outside of it.
+    pub fn iter_within(&self, nibbles: &[u8], masklen: u32) -> ArchivedIter<'_, T> {
+        let (path, nibbles) = self.subtree_path(nibbles, masklen);
+        ArchivedIter {
+            inner: self,
+            path,
+            nibbles,
+        }
+    }
+
+    /// Returns iterator over nibbles, masklen and values without deserializing the trie.
+    pub fn iter(&self) -> ArchivedIter<'_, T> {
+        ArchivedIter {
+            inner: self,
+            path: vec![PathElem {
+                node: self.get_node(&self.root_handle(), 0),
+                pos: 0,
+            }],
+            nibbles: vec![0],
+        }
+    }
+}
+
+pub struct ArchivedIter<'a, T: Archive> {
+    inner: &'a ArchivedTreeBitmap<T>,
+    path: Vec<PathElem>,
+    nibbles: Vec<u8>,
+}
+
+impl<'a, T: Archive + Clone + Copy + Default> Iterator for ArchivedIter<'a, T> {
+    type Item = (Vec<u8>, u32, &'a Archived<T>); //(nibbles, masklen, &T)
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next(self.inner, &mut self.path, &mut self.nibbles).map(
+            |(path, bits_matched, hdl, index)| {
+                (path, bits_matched, self.inner.results.get(&hdl, index))
+            },
+        )
+    }
+}
+
+pub struct ArchivedMatches<'a, T: Archive> {
+    inner: &'a ArchivedTreeBitmap<T>,
+    path: std::vec::IntoIter<(u32, AllocatorHandle, u32)>,
+}
+
+impl<'a, T: Archive + Clone + Copy + Default> Iterator for ArchivedMatches<'a, T> {
+    type Item = (u32, &'a Archived<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.path
+            .next()
+            .map(|(bits_matched, result_hdl, result_index)| {
+                (
+                    bits_matched,
+                    self.inner.results.get(&result_hdl, result_index),
+                )
+            })
+    }
 }
 
 impl<T: Archive + Clone + Copy + Default> TrieAccess for ArchivedTreeBitmap<T> {
@@ -68,6 +175,10 @@ impl<T: Archive + Clone + Copy + Default> ArchivedAllocator<T> {
         let bucket_index = choose_bucket(hdl.len) as usize;
         self.buckets[bucket_index].get_slot_entry(hdl.offset, index)
     }
+
+    pub fn mem_usage(&self) -> usize {
+        self.buckets.iter().map(ArchivedBucketVec::mem_usage).sum()
+    }
 }
 
 impl<T: Archive + Clone + Copy + Default> ArchivedBucketVec<T> {
@@ -77,6 +188,11 @@ impl<T: Archive + Clone + Copy + Default> ArchivedBucketVec<T> {
         let offset = (slot + index) as usize;
         &self.buf[offset]
     }
+
+    /// An archive has no spare capacity, so this only counts the stored items.
+    pub fn mem_usage(&self) -> usize {
+        (size_of::<Archived<T>>() * self.buf.len()) + (self.freelist.len() * size_of::<u32>())
+    }
 }
 
 impl From<&ArchivedNode> for Node {
@@ -91,9 +207,9 @@ impl From<&ArchivedNode> for Node {
 
 #[cfg(test)]
 mod tests {
-    use crate::IpLookupTable;
+    use crate::{Address, ArchivedIpLookupTable, IpLookupTable};
     use rkyv::rancor;
-    use std::net::Ipv6Addr;
+    use std::net::{Ipv4Addr, Ipv6Addr};
 
     #[test]
     fn test_rkyv() {
@@ -111,4 +227,95 @@ mod tests {
         assert_eq!(table.longest_match(ip_2), Some((less_specific, 32, &123)));
         rkyv::to_bytes::<rancor::Error>(&table).unwrap();
     }
+
+    #[test]
+    fn test_rkyv_iter() {
+        let mut table = IpLookupTable::new();
+        let less_specific = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0);
+        let more_specific = Ipv6Addr::new(0x2001, 0xdb8, 0xdead, 0, 0, 0, 0, 0);
+        table.insert(less_specific, 32, 123u32);
+        table.insert(more_specific, 48, 321u32);
+
+        let bytes = rkyv::to_bytes::<rancor::Error>(&table).unwrap();
+        let archived =
+            unsafe { rkyv::access_unchecked::<ArchivedIpLookupTable<Ipv6Addr, u32>>(&bytes) };
+
+        // the archive drops spare capacity, the owned table's allocation is an upper bound
+        let (nodes, results) = archived.mem_usage();
+        assert!(nodes > 0 && results > 0);
+        assert!(nodes <= table.mem_usage().0 && results <= table.mem_usage().1);
+
+        let owned: Vec<_> = table.iter().map(|(ip, len, v)| (ip, len, *v)).collect();
+        let archived: Vec<_> = archived
+            .iter()
+            .map(|(ip, len, v)| (ip, len, v.to_native()))
+            .collect();
+
+        assert_eq!(owned, archived);
+    }
+
+    #[test]
+    fn test_rkyv_matches_within() {
+        let mut table = IpLookupTable::new();
+        let prefixes = [
+            (Ipv4Addr::new(10, 0, 0, 0), 8),
+            (Ipv4Addr::new(10, 1, 0, 0), 16),
+            (Ipv4Addr::new(10, 1, 2, 0), 23),
+            (Ipv4Addr::new(10, 1, 2, 0), 24),
+            (Ipv4Addr::new(10, 1, 2, 3), 32),
+            (Ipv4Addr::new(10, 2, 0, 0), 15),
+            (Ipv4Addr::new(10, 128, 0, 0), 9),
+            (Ipv4Addr::new(192, 168, 0, 0), 16),
+        ];
+        for (i, (ip, len)) in prefixes.into_iter().enumerate() {
+            table.insert(ip, len, i as u32);
+        }
+
+        let bytes = rkyv::to_bytes::<rancor::Error>(&table).unwrap();
+        let archived =
+            unsafe { rkyv::access_unchecked::<ArchivedIpLookupTable<Ipv4Addr, u32>>(&bytes) };
+
+        let ip = Ipv4Addr::new(10, 1, 2, 3);
+        let owned: Vec<_> = table
+            .matches(ip)
+            .map(|(ip, len, v)| (ip, len, *v))
+            .collect();
+        let matches: Vec<_> = archived
+            .matches(ip)
+            .map(|(ip, len, v)| (ip, len, v.to_native()))
+            .collect();
+        assert_eq!(owned.len(), 5);
+        assert_eq!(owned, matches);
+
+        let supernets = [
+            (Ipv4Addr::new(0, 0, 0, 0), 0),
+            (Ipv4Addr::new(10, 0, 0, 0), 8),
+            (Ipv4Addr::new(10, 0, 0, 0), 9),
+            (Ipv4Addr::new(10, 1, 0, 0), 16),
+            (Ipv4Addr::new(10, 1, 0, 0), 22),
+            (Ipv4Addr::new(10, 1, 2, 3), 32),
+            (Ipv4Addr::new(10, 3, 0, 0), 16),
+            (Ipv4Addr::new(172, 16, 0, 0), 12),
+        ];
+        for (supernet, masklen) in supernets {
+            let mut expected: Vec<_> = table
+                .iter()
+                .filter(|(ip, len, _)| *len >= masklen && ip.mask(masklen) == supernet)
+                .map(|(ip, len, v)| (ip, len, *v))
+                .collect();
+            let mut owned: Vec<_> = table
+                .within(supernet, masklen)
+                .map(|(ip, len, v)| (ip, len, *v))
+                .collect();
+            let mut within: Vec<_> = archived
+                .within(supernet, masklen)
+                .map(|(ip, len, v)| (ip, len, v.to_native()))
+                .collect();
+            expected.sort();
+            owned.sort();
+            within.sort();
+            assert_eq!(owned, expected, "{supernet}/{masklen}");
+            assert_eq!(within, expected, "{supernet}/{masklen}");
+        }
+    }
 }
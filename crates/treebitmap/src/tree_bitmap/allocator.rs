@@ -17,7 +17,7 @@ use std::mem;
 )]
 pub struct BucketVec<T> {
     pub(crate) buf: Vec<T>,
-    freelist: Vec<u32>,
+    pub(crate) freelist: Vec<u32>,
     len: u32,
     pub(crate) spacing: u32,
 }
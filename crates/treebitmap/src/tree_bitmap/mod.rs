@@ -113,57 +113,6 @@ impl<T: Sized + Clone + Copy + Default> TreeBitmap<T> {
         }
     }
 
-    /// All matches lookup of ```nibbles```. Returns of Vec of tuples, each containing bits matched as u32,
-    /// result handle and result index as u32.
-    fn matches_internal(&self, nibbles: &[u8]) -> Vec<(u32, AllocatorHandle, u32)> {
-        let mut cur_hdl = self.root_handle();
-        let mut cur_index = 0;
-        let mut bits_searched = 0;
-        let mut matches = Vec::new();
-
-        let mut loop_count = 0;
-        loop {
-            let nibble = if loop_count < nibbles.len() {
-                nibbles[loop_count]
-            } else {
-                0
-            };
-            loop_count += 1;
-            let nibble = &nibble;
-            let cur_node = *self.trienodes.get(&cur_hdl, cur_index);
-
-            for i in 0..5 {
-                let prefix = *nibble & (!0 << (4 - i));
-                let bitmap = node::gen_bitmap(prefix, i as u32) & node::END_BIT_MASK;
-                if let MatchResult::Match(result_hdl, result_index, _) =
-                    cur_node.match_internal(bitmap)
-                {
-                    let bits_matched = bits_searched + (i as u32);
-                    matches.push((bits_matched, result_hdl, result_index));
-                }
-            }
-
-            if cur_node.is_endnode() {
-                break;
-            }
-
-            let match_mask = node::MATCH_MASKS[*nibble as usize];
-            match cur_node.match_external(match_mask) {
-                MatchResult::Chase(child_hdl, child_index) => {
-                    bits_searched += 4;
-                    cur_hdl = child_hdl;
-                    cur_index = child_index;
-                }
-                MatchResult::None => {
-                    break;
-                }
-                _ => unreachable!(),
-            }
-        }
-
-        matches
-    }
-
     /// All matches lookup of ```nibbles```. Returns of iterator of tuples, each containing bits matched as u32 and a reference to T.
     pub fn matches(&self, nibbles: &[u8]) -> Matches<'_, T> {
         let path = self.matches_internal(nibbles).into_iter();
@@ -361,6 +310,17 @@ impl<T: Sized + Clone + Copy + Default> TreeBitmap<T> {
         }
     }
 
+    /// Returns iterator over the subtree holding every prefix contained in ```nibbles```/```masklen```.
+    /// The caller still has to filter out prefixes that are shorter or outside of it.
+    pub fn iter_within(&self, nibbles: &[u8], masklen: u32) -> Iter<'_, T> {
+        let (path, nibbles) = self.subtree_path(nibbles, masklen);
+        Iter {
+            inner: self,
+            path,
+            nibbles,
+        }
+    }
+
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         let root_hdl = self.root_handle();
         let root_node = *self.trienodes.get(&root_hdl, 0);
@@ -382,9 +342,9 @@ impl<T: Sized + Clone + Copy + Default> Default for TreeBitmap<T> {
 }
 
 #[derive(Debug)]
-struct PathElem {
-    node: Node,
-    pos: usize,
+pub(crate) struct PathElem {
+    pub(crate) node: Node,
+    pub(crate) pos: usize,
 }
 
 pub struct Iter<'a, T: 'a> {
@@ -409,8 +369,8 @@ static PREFIX_OF_BIT: [u8; 32] = [// 0       1       2      3        4       5
                                   // 24      25      26      27      28      29      30      31
                                   0b1000, 0b1001, 0b1010, 0b1011, 0b1100, 0b1101, 0b1110, 0b1111];
 
-fn next<T: Sized>(
-    trie: &TreeBitmap<T>,
+pub(crate) fn next(
+    trie: &impl TrieAccess,
     path: &mut Vec<PathElem>,
     nibbles: &mut Vec<u8>,
 ) -> Option<(Vec<u8>, u32, AllocatorHandle, u32)> {
@@ -442,10 +402,10 @@ fn next<T: Sized>(
                 return Some((nibbles.clone(), bits_matched, result_hdl, result_index));
             }
         } else if let MatchResult::Chase(child_hdl, child_index) = cur_node.match_external(bitmap) {
-            let child_node = trie.trienodes.get(&child_hdl, child_index);
+            let child_node = trie.get_node(&child_hdl, child_index);
             nibbles.push(0);
             path.push(PathElem {
-                node: *child_node,
+                node: child_node,
                 pos: 0,
             });
         }
@@ -622,6 +582,94 @@ pub(crate) trait TrieAccess {
         best_match
     }
 
+    /// All matches lookup of ```nibbles```. Returns of Vec of tuples, each containing bits matched as u32,
+    /// result handle and result index as u32.
+    #[inline]
+    fn matches_internal(&self, nibbles: &[u8]) -> Vec<(u32, AllocatorHandle, u32)> {
+        let mut cur_hdl = self.root_handle();
+        let mut cur_index = 0;
+        let mut bits_searched = 0;
+        let mut matches = Vec::new();
+
+        let mut loop_count = 0;
+        loop {
+            let nibble = if loop_count < nibbles.len() {
+                nibbles[loop_count]
+            } else {
+                0
+            };
+            loop_count += 1;
+            let nibble = &nibble;
+            let cur_node = self.get_node(&cur_hdl, cur_index);
+
+            for i in 0..5 {
+                let prefix = *nibble & (!0 << (4 - i));
+                let bitmap = node::gen_bitmap(prefix, i as u32) & node::END_BIT_MASK;
+                if let MatchResult::Match(result_hdl, result_index, _) =
+                    cur_node.match_internal(bitmap)
+                {
+                    let bits_matched = bits_searched + (i as u32);
+                    matches.push((bits_matched, result_hdl, result_index));
+                }
+            }
+
+            if cur_node.is_endnode() {
+                break;
+            }
+
+            let match_mask = node::MATCH_MASKS[*nibble as usize];
+            match cur_node.match_external(match_mask) {
+                MatchResult::Chase(child_hdl, child_index) => {
+                    bits_searched += 4;
+                    cur_hdl = child_hdl;
+                    cur_index = child_index;
+                }
+                MatchResult::None => {
+                    break;
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        matches
+    }
+
+    /// Walk down towards the node covering the first `masklen` bits of ```nibbles```.
+    /// Returns an iteration path and nibble stack that only visits that node's subtree,
+    /// which holds every prefix contained in ```nibbles```/```masklen``` (and some of its siblings).
+    fn subtree_path(&self, nibbles: &[u8], masklen: u32) -> (Vec<PathElem>, Vec<u8>) {
+        let depth = cmp::min(masklen as usize / 4, nibbles.len());
+        let mut path = Vec::with_capacity(depth + 1);
+        let mut path_nibbles = Vec::with_capacity(depth + 1);
+        let mut cur_node = self.get_node(&self.root_handle(), 0);
+
+        for &nibble in &nibbles[..depth] {
+            if cur_node.is_endnode() {
+                break;
+            }
+            match cur_node.match_external(node::MATCH_MASKS[nibble as usize]) {
+                MatchResult::Chase(child_hdl, child_index) => {
+                    // ancestors are marked as exhausted so iteration stops at the end of the subtree
+                    path.push(PathElem {
+                        node: cur_node,
+                        pos: 32,
+                    });
+                    path_nibbles.push(nibble);
+                    cur_node = self.get_node(&child_hdl, child_index);
+                }
+                MatchResult::None => break,
+                _ => unreachable!(),
+            }
+        }
+
+        path.push(PathElem {
+            node: cur_node,
+            pos: 0,
+        });
+        path_nibbles.push(0);
+        (path, path_nibbles)
+    }
+
     #[inline]
     fn exact_match_internal(&self, nibbles: &[u8], masklen: u32) -> Option<(AllocatorHandle, u32)> {
         let mut cur_hdl = self.root_handle();
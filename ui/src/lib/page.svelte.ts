@@ -35,6 +35,8 @@ export const pageState = new PageStateManager();
 export interface MapArgs {
   capture?: CaptureSession | null;
   focused?: string | null;
+  /** Called with the coordinate of a click on the map itself, not a marker. */
+  onmapclick?: (crd: Coordinate) => void;
 }
 
 export interface MapComponent {
@@ -1,4 +1,4 @@
-import type { Location } from "tauri-plugin-ipgeo-api";
+import type { DatabaseSource, Location } from "tauri-plugin-ipgeo-api";
 
 export const CAPTURE_SHOW_ARCS = true;
 export const CAPTURE_SHOW_MARKERS = true;
@@ -6,10 +6,24 @@ export const CAPTURE_COLORS = true;
 export const CAPTURE_VARY_SIZE = true;
 export const CAPTURE_SHOW_NOT_FOUND = true;
 
-export const regionNames = new Intl.DisplayNames(["en"], { type: "region" });
+/** The country's flag and name from the backend's ISO 3166 table, or just its code if unknown. */
+export const renderCountryName = (l: Location) =>
+  l.country ? `${l.country.flag} ${l.country.names.en}` : l.countryCode;
 
 export const renderLocationName = (l: Location) =>
-  `${l.city ?? "Unknown City"}${l.region ? `, ${l.region}` : ""}, ${regionNames.of(l.countryCode)}`;
+  `${l.city ?? "Unknown City"}${l.region ? `, ${l.region}` : ""}, ${renderCountryName(l)}`;
+
+const fileName = (path: string) => path.split(/[/\\]/).pop() ?? path;
+
+/** A short name for a database, matching its `Display` implementation on the backend. */
+export const renderSourceName = (source: DatabaseSource): string => {
+  if (source == "dbipcombined") return "DB-IP City";
+  if (source == "geolite2combined") return "Geolite2 City";
+  if (source == "asn") return "ASN";
+  if ("file" in source) return fileName(source.file);
+  if ("overlay" in source) return fileName(source.overlay);
+  return source.files.map(fileName).join(" + ");
+};
 
 export const lerp = (
   value: number,